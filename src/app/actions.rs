@@ -1,14 +1,52 @@
-use std::time::Instant;
-use crate::models::ResponseMetadata;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use crate::data::history::{encode_body, HistoryEntry, ResponseData};
+use crate::models::{CollectionItem, RedirectHop, ResponseMetadata, SavedRequest, Timings};
+use crate::models::collection::HttpVersion;
+use crate::utils::graphql::{GraphQlSchema, INTROSPECTION_QUERY};
+use crate::utils::http::{is_binary_body, mime_type};
+use crate::utils::openapi;
+use crate::utils::proxy::{display_proxy, ProxyRoutes};
+use crate::utils::timing::{breakdown, millis, probe_connection, TimingResolver};
 use super::state::App;
+use super::websocket::WebSocketSession;
+use super::event_stream::EventStream;
+use super::grpc::{self, GrpcCall};
 use log::{debug, error, info};
 use std::collections::HashMap;
 use chrono::Utc;
 
 pub struct RequestHandler;
 
+// Time allowed for the response headers and body together
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(30);
+// Time allowed to open a WebSocket or gRPC connection
+const CONNECT_TIMEOUT: Duration = Duration::from_secs(10);
+
 impl RequestHandler {
     pub async fn send_request(app: &mut App) {
+        if app.is_websocket() {
+            return Self::send_websocket(app).await;
+        }
+        if let Some(stream) = app.event_stream.take() {
+            stream.log.cancel();
+        }
+        if let Some(stream) = app.grpc_stream.take() {
+            stream.log.cancel();
+        }
+        app.contract_check = None;
+        if app.is_grpc() {
+            return Self::send_grpc(app).await;
+        }
+        let body = match app.request_body() {
+            Ok(body) => body,
+            Err(e) => {
+                app.response = Some(format!("Error: {:#}", e));
+                app.response_bytes = None;
+                return;
+            }
+        };
+
         // Validate URL
         if !app.url.starts_with("http://") && !app.url.starts_with("https://") {
             app.url = format!("http://{}", app.url);
@@ -31,20 +69,28 @@ impl RequestHandler {
 
         app.headers.insert(
             "Content-Length".to_string(), 
-            app.body.len().to_string()
+            body.len().to_string()
         );
-        debug!("Updated Content-Length to: {}", app.body.len());
+        debug!("Updated Content-Length to: {}", body.len());
 
         debug!("Preparing to send request to: {}", app.url);
         debug!("Method: {}", app.method.as_str());
+        let method = match reqwest::Method::from_bytes(app.method.as_str().as_bytes()) {
+            Ok(method) => method,
+            Err(_) => {
+                app.response = Some(format!("Error: invalid method {:?}", app.method.as_str()));
+                app.response_bytes = None;
+                return;
+            }
+        };
         
         // Filter enabled headers
-        let enabled_headers: HashMap<_, _> = app.headers.iter()
+        let mut enabled_headers: HashMap<_, _> = app.headers.iter()
             .filter(|(key, _)| *app.header_enabled.get(&**key).unwrap_or(&true))
             .map(|(k, v)| {
                 let value = match k.as_str() {
                     "Content-Length" if v == "<calculated>" => {
-                        app.body.len().to_string()
+                        body.len().to_string()
                     },
                     "Host" if v == "<host of the machine>" => {
                         if let Ok(url) = reqwest::Url::parse(&app.url) {
@@ -58,61 +104,228 @@ impl RequestHandler {
                 (k.clone(), value)
             })
             .collect();
+        // GraphQL payloads are always JSON
+        if app.graphql.is_some() {
+            enabled_headers.retain(|k, _| !k.eq_ignore_ascii_case("content-type"));
+            enabled_headers.insert("Content-Type".to_string(), "application/json".to_string());
+        }
         
         debug!("Enabled headers: {:?}", enabled_headers);
 
+        // Redirects are followed by hand so that every hop can be recorded
+        let (follow_redirects, max_redirects) = app.redirect_policy();
+        let redirects: Arc<Mutex<Vec<RedirectHop>>> = Arc::default();
+        let policy = {
+            let redirects = redirects.clone();
+            reqwest::redirect::Policy::custom(move |attempt| {
+                // `previous` holds the original URL plus every hop so far
+                if !follow_redirects || attempt.previous().len() > max_redirects {
+                    return attempt.stop();
+                }
+                redirects.lock().unwrap().push(RedirectHop {
+                    url: attempt.previous().last().map(|u| u.to_string()).unwrap_or_default(),
+                    status: attempt.status().as_u16(),
+                    location: attempt.url().to_string(),
+                });
+                attempt.follow()
+            })
+        };
+
+        let routes = if app.request_options.bypass_proxy {
+            ProxyRoutes::default()
+        } else {
+            match ProxyRoutes::from_settings(&app.config.app.proxy) {
+                Ok(routes) => routes,
+                Err(e) => {
+                    error!("Invalid proxy settings: {}", e);
+                    app.response = Some(format!("Error: invalid proxy settings: {}", e));
+                    app.response_bytes = None;
+                    return;
+                }
+            }
+        };
+        let proxy = reqwest::Url::parse(&app.url).ok().and_then(|url| routes.proxy_for(&url));
+        if let Some(proxy) = &proxy {
+            debug!("Sending through proxy {}", display_proxy(proxy));
+        }
+
+        // Times the DNS lookups the client makes
+        let resolver = TimingResolver::default();
+
         // Create client with timeouts
-        let client = reqwest::Client::builder()
-            .timeout(std::time::Duration::from_secs(30))
+        // The overall timeout is applied around `send` and the body read
+        // instead, so that event streams can stay open
+        let mut builder = reqwest::Client::builder()
             .connect_timeout(std::time::Duration::from_secs(10))
             .danger_accept_invalid_certs(true)
-            .build()
-            .unwrap_or_default();
+            .cookie_provider(app.cookie_jar.clone())
+            .redirect(policy)
+            .dns_resolver(Arc::new(resolver.clone()))
+            // Proxies come from `routes` only, including the environment ones
+            .no_proxy();
+        if !routes.is_empty() {
+            builder = builder.proxy(reqwest::Proxy::custom(move |url| routes.proxy_for(url)));
+        }
+        let http_version = app.http_version();
+        builder = match http_version {
+            HttpVersion::Auto => builder,
+            HttpVersion::Http1 => builder.http1_only(),
+            // ALPN offers h2 on TLS connections by default; the response is
+            // checked for it below
+            HttpVersion::Http2 => builder,
+            HttpVersion::Http2PriorKnowledge => builder.http2_prior_knowledge(),
+        };
+        let client = builder.build().unwrap_or_default();
 
         let mut request = client.request(
-            reqwest::Method::from_bytes(app.method.as_str().as_bytes()).unwrap(),
+            method,
             &app.url
         );
 
         // Add enabled headers
-        for (key, value) in enabled_headers {
+        for (key, value) in &enabled_headers {
             request = request.header(key, value);
         }
 
         // Add body if present
-        if !app.body.is_empty() {
-            debug!("Request body: {}", app.body);
-            request = request.body(app.body.clone());
+        if !body.is_empty() {
+            debug!("Request body: {}", body);
+            request = request.body(body);
         }
 
+        let request = match request.build() {
+            Ok(request) => request,
+            Err(e) => {
+                error!("Invalid request: {}", e);
+                app.response = Some(format!("Error: {}", e));
+                app.response_bytes = None;
+                return;
+            }
+        };
+        // Kept to reconnect if the response turns out to be an event stream
+        let replay = request.try_clone();
+
         debug!("Sending request to {} with method {}", app.url, app.method.as_str());
         let start_time = Instant::now();
-        match request.send().await {
+        let sent = match tokio::time::timeout(REQUEST_TIMEOUT, client.execute(request)).await {
+            Ok(sent) => sent,
+            Err(_) => {
+                error!("Request timed out");
+                app.response = Some(format!("Error: request timed out after {}s", REQUEST_TIMEOUT.as_secs()));
+                app.response_bytes = None;
+                Self::record_history(app, enabled_headers, None);
+                return;
+            }
+        };
+        match sent {
             Ok(response) => {
+                let headers_at = start_time.elapsed();
                 debug!("Got response with status: {}", response.status());
                 let status = response.status();
-                let headers = response.headers().iter()
+                let final_url = response.url().to_string();
+                let negotiated = format!("{:?}", response.version());
+                // HTTP/2 was asked for, so a server that only speaks 1.1 fails the request
+                if http_version == HttpVersion::Http2 && response.version() != reqwest::Version::HTTP_2 {
+                    error!("HTTP/2 was not negotiated, server used {}", negotiated);
+                    app.response = Some(format!(
+                        "Error: HTTP/2 was not negotiated, server used {} (use auto to accept it, or h2c for plain HTTP)",
+                        negotiated
+                    ));
+                    app.response_bytes = None;
+                    Self::record_history(app, enabled_headers, None);
+                    return;
+                }
+                let redirects = std::mem::take(&mut *redirects.lock().unwrap());
+                if follow_redirects && status.is_redirection() && redirects.len() >= max_redirects {
+                    app.status_message = Some(format!("Stopped after {} redirects", redirects.len()));
+                }
+                // Every value of repeated headers, for history
+                let header_list: Vec<(String, String)> = response.headers().iter()
                     .map(|(k, v)| (k.to_string(), v.to_str().unwrap_or("").to_string()))
                     .collect();
+                let headers = header_list.iter().cloned().collect();
+                let set_cookies = response.headers()
+                    .get_all(reqwest::header::SET_COOKIE)
+                    .iter()
+                    .filter_map(|v| v.to_str().ok())
+                    .map(|v| v.to_string())
+                    .collect();
                 info!("Response received: {} {}", status.as_u16(), status.as_str());
                 
-                match response.text().await {
-                    Ok(body) => {
+                let content_type = response.headers()
+                    .get(reqwest::header::CONTENT_TYPE)
+                    .and_then(|v| v.to_str().ok())
+                    .unwrap_or("")
+                    .to_string();
+                let content_encoding = response.headers()
+                    .get(reqwest::header::CONTENT_ENCODING)
+                    .and_then(|v| v.to_str().ok())
+                    .unwrap_or("")
+                    .to_string();
+
+                let body = if mime_type(&content_type) == "text/event-stream" {
+                    // Events are read in the background until the stream is cancelled
+                    app.event_stream = Some(EventStream::start(client.clone(), replay, response));
+                    app.event_stream_scroll = 0;
+                    app.status_message = Some("Streaming events, 'x' to stop".to_string());
+                    Ok(Vec::new())
+                } else {
+                    match tokio::time::timeout(REQUEST_TIMEOUT.saturating_sub(headers_at), response.bytes()).await {
+                        Ok(bytes) => bytes.map(|b| b.to_vec()).map_err(|e| e.to_string()),
+                        Err(_) => Err(format!("timed out after {}s", REQUEST_TIMEOUT.as_secs())),
+                    }
+                };
+                match body {
+                    Ok(bytes) => {
                         let elapsed = start_time.elapsed();
-                        debug!("Response body received, length: {}", body.len());
-                        app.response = Some(body.clone());
+                        debug!("Response body received, length: {}", bytes.len());
+                        let is_binary = is_binary_body(&content_type, &content_encoding, &bytes);
+                        let timings = Self::connection_timings(&resolver, proxy.is_some(), &app.url, headers_at, elapsed).await;
+                        app.response = if is_binary {
+                            None
+                        } else {
+                            Some(String::from_utf8_lossy(&bytes).into_owned())
+                        };
+                        app.response_scroll = 0;
                         app.response_metadata = Some(ResponseMetadata {
                             status: status.as_u16(),
                             status_text: status.to_string(),
                             time_ms: elapsed.as_millis(),
-                            size_bytes: body.len(),
+                            size_bytes: bytes.len(),
+                            is_binary,
                             response_headers: headers,
+                            set_cookies,
+                            url: final_url,
+                            redirects,
+                            http_version: negotiated.clone(),
+                            proxy: proxy.as_ref().map(display_proxy),
+                            timings: timings.clone(),
                             timestamp: Utc::now(),
+                            from_history: false,
                         });
+                        Self::check_contract(app, status.as_u16(), &content_type, &bytes);
+                        let (body, encoding) = encode_body(&bytes, is_binary);
+                        let size_bytes = bytes.len();
+                        app.response_bytes = Some(bytes);
+
+                        let response_data = ResponseData {
+                            status: Some(status.as_u16()),
+                            status_text: Some(status.to_string()),
+                            headers: header_list,
+                            body,
+                            encoding,
+                            time_ms: elapsed.as_millis(),
+                            size_bytes,
+                            http_version: Some(negotiated),
+                            timings: Some(timings),
+                        };
+                        Self::record_history(app, enabled_headers, Some(response_data));
                     }
                     Err(e) => {
                         error!("Failed to read response body: {}", e);
                         app.response = Some(format!("Error reading response: {}", e));
+                        app.response_bytes = None;
+                        Self::record_history(app, enabled_headers, None);
                     }
                 }
             }
@@ -126,7 +339,324 @@ impl RequestHandler {
                     error!("Request error: {}", e);
                 }
                 app.response = Some(format!("Error: {}", e));
+                app.response_bytes = None;
+                Self::record_history(app, enabled_headers, None);
+            }
+        }
+    }
+
+    // Connects to a ws:// or wss:// URL, or sends the body as a text message
+    // when the connection is already open.
+    async fn send_websocket(app: &mut App) {
+        app.url = app.url.trim().to_string();
+        if let Some(ws) = app.open_websocket() {
+            if let Err(e) = ws.send_text(&app.body) {
+                app.status_message = Some(format!("WebSocket: {}", e));
+            }
+            app.websocket_scroll = 0;
+            return;
+        }
+        if let Some(previous) = app.websocket.take() {
+            let _ = previous.close();
+        }
+
+        let mut headers = Self::plain_headers(app);
+        // Cookies are matched as for the equivalent http(s) URL
+        if let Ok(mut url) = reqwest::Url::parse(&app.url) {
+            let scheme = if url.scheme() == "wss" { "https" } else { "http" };
+            if url.set_scheme(scheme).is_ok() {
+                if let Some(cookies) = reqwest::cookie::CookieStore::cookies(&*app.cookie_jar, &url) {
+                    headers.insert("Cookie".to_string(), cookies.to_str().unwrap_or("").to_string());
+                }
             }
         }
+
+        debug!("Opening WebSocket to {}", app.url);
+        let start_time = Instant::now();
+        app.response = None;
+        app.response_bytes = None;
+        app.response_metadata = None;
+        app.websocket_scroll = 0;
+        match WebSocketSession::connect(&app.url, &headers, CONNECT_TIMEOUT).await {
+            Ok((session, handshake)) => {
+                let elapsed = start_time.elapsed();
+                info!("WebSocket connected: {}", handshake.status_text);
+                app.response_metadata = Some(ResponseMetadata {
+                    status: handshake.status,
+                    status_text: handshake.status_text,
+                    time_ms: elapsed.as_millis(),
+                    size_bytes: 0,
+                    is_binary: false,
+                    response_headers: handshake.headers,
+                    set_cookies: Vec::new(),
+                    url: app.url.clone(),
+                    redirects: Vec::new(),
+                    http_version: "HTTP/1.1".to_string(),
+                    proxy: None,
+                    timings: Timings {
+                        ttfb_ms: millis(elapsed),
+                        ..Timings::default()
+                    },
+                    timestamp: Utc::now(),
+                    from_history: false,
+                });
+                app.websocket = Some(session);
+            }
+            Err(e) => {
+                error!("WebSocket connection failed: {:#}", e);
+                app.response = Some(format!("Error: {:#}", e));
+            }
+        }
+    }
+
+    // Calls the selected method of a grpc:// or grpcs:// URL with the body as
+    // the request message. Messages of server-streaming calls go to a log.
+    async fn send_grpc(app: &mut App) {
+        app.url = app.url.trim().to_string();
+        app.response = None;
+        app.response_bytes = None;
+        app.response_metadata = None;
+        app.grpc_stream_scroll = 0;
+
+        app.drop_stale_grpc_pool();
+        if app.grpc_pool.is_none() && !Self::load_grpc_definitions(app).await {
+            app.response = app.status_message.take().map(|e| format!("Error: {}", e));
+            return;
+        }
+        let Some(method) = app.grpc_method() else {
+            app.response = Some("Error: pick a gRPC method first (Enter on the method box)".to_string());
+            return;
+        };
+
+        debug!("Calling {} on {}", app.grpc_target.method, app.url);
+        let start_time = Instant::now();
+        let headers = Self::plain_headers(app);
+        let call = match grpc::connect(&app.url, CONNECT_TIMEOUT).await
+            .and_then(|channel| GrpcCall::new(channel, method, &app.body, &headers))
+        {
+            Ok(call) => call,
+            Err(e) => {
+                error!("gRPC call failed: {:#}", e);
+                app.response = Some(format!("Error: {:#}", e));
+                return;
+            }
+        };
+        let response = if call.is_server_streaming() {
+            let (response, stream) = call.server_streaming(&app.url, REQUEST_TIMEOUT).await;
+            app.grpc_stream = stream;
+            response
+        } else {
+            call.unary(REQUEST_TIMEOUT).await
+        };
+        let elapsed = start_time.elapsed();
+        info!("gRPC call finished: {}", response.status_text());
+
+        let body = match &response.body {
+            Some(body) => serde_json::to_string_pretty(body).unwrap_or_default(),
+            None if response.code == tonic::Code::Ok => String::new(),
+            None => format!("{}: {}", response.status_text(), response.message),
+        };
+        app.response_metadata = Some(ResponseMetadata {
+            status: response.http_status(),
+            status_text: response.status_text(),
+            time_ms: elapsed.as_millis(),
+            size_bytes: body.len(),
+            is_binary: false,
+            response_headers: response.metadata,
+            set_cookies: Vec::new(),
+            url: app.url.clone(),
+            redirects: Vec::new(),
+            http_version: "HTTP/2.0".to_string(),
+            proxy: None,
+            timings: Timings {
+                ttfb_ms: millis(elapsed),
+                ..Timings::default()
+            },
+            timestamp: Utc::now(),
+            from_history: false,
+        });
+        app.response_bytes = Some(body.clone().into_bytes());
+        app.response = Some(body);
+        app.response_scroll = 0;
+    }
+
+    // Loads the gRPC service definitions from the request's .proto files, or
+    // from the server's reflection service when there are none.
+    pub async fn load_grpc_definitions(app: &mut App) -> bool {
+        let result = if app.grpc_target.proto_files.is_empty() {
+            debug!("Loading gRPC definitions from {} by reflection", app.url);
+            async {
+                let channel = grpc::connect(&app.url, CONNECT_TIMEOUT).await?;
+                tokio::time::timeout(REQUEST_TIMEOUT, grpc::reflect(channel))
+                    .await
+                    .map_err(|_| anyhow::anyhow!("server reflection timed out"))?
+            }.await
+        } else {
+            grpc::load_proto_files(&app.grpc_target.proto_files)
+        };
+        match result {
+            Ok(pool) => {
+                app.status_message = Some(format!("Loaded {} gRPC methods", grpc::method_names(&pool).len()));
+                app.set_grpc_pool(pool);
+                true
+            }
+            Err(e) => {
+                error!("Loading gRPC definitions failed: {:#}", e);
+                app.status_message = Some(format!("Loading gRPC definitions failed: {:#}", e));
+                false
+            }
+        }
+    }
+
+    // Fetches the schema of the GraphQL endpoint at the request URL with the
+    // introspection query, sending the enabled headers.
+    pub async fn introspect(app: &mut App) {
+        if !app.url.starts_with("http://") && !app.url.starts_with("https://") {
+            app.url = format!("http://{}", app.url);
+        }
+        let headers = Self::plain_headers(app);
+        let routes = if app.request_options.bypass_proxy {
+            Ok(ProxyRoutes::default())
+        } else {
+            ProxyRoutes::from_settings(&app.config.app.proxy)
+        };
+
+        debug!("Fetching GraphQL schema from {}", app.url);
+        let result: anyhow::Result<GraphQlSchema> = async {
+            let routes = routes?;
+            let mut builder = reqwest::Client::builder()
+                .timeout(REQUEST_TIMEOUT)
+                .danger_accept_invalid_certs(true)
+                .cookie_provider(app.cookie_jar.clone())
+                .no_proxy();
+            if !routes.is_empty() {
+                builder = builder.proxy(reqwest::Proxy::custom(move |url| routes.proxy_for(url)));
+            }
+            let mut request = builder.build()?
+                .post(&app.url)
+                .body(serde_json::json!({ "query": INTROSPECTION_QUERY }).to_string());
+            for (key, value) in headers.iter().filter(|(k, _)| !k.eq_ignore_ascii_case("content-type")) {
+                request = request.header(key, value);
+            }
+            let response: serde_json::Value = request
+                .header(reqwest::header::CONTENT_TYPE, "application/json")
+                .send().await?
+                .error_for_status()?
+                .json().await?;
+            GraphQlSchema::from_introspection(&response)
+        }.await;
+
+        match result {
+            Ok(schema) => {
+                app.status_message = Some(format!("Schema loaded: {} types", schema.browsable_types().len()));
+                app.graphql_schema = Some(schema);
+            }
+            Err(e) => {
+                error!("Introspection failed: {:#}", e);
+                app.status_message = Some(format!("Introspection failed: {:#}", e));
+            }
+        }
+    }
+
+    // DNS comes from the client's own lookup; connect and TLS are timed on a
+    // probe to the same address once the body is in. Through a proxy the
+    // client connects to the proxy instead, so those phases stay in waiting.
+    async fn connection_timings(resolver: &TimingResolver, proxied: bool, url: &str, headers_at: Duration, elapsed: Duration) -> Timings {
+        let headers_ms = millis(headers_at);
+        let download_ms = millis(elapsed.saturating_sub(headers_at));
+        let Some(url) = reqwest::Url::parse(url).ok().filter(|_| !proxied) else {
+            return breakdown(None, None, headers_ms, download_ms);
+        };
+        let lookup = url.host_str().and_then(|host| resolver.lookup(host));
+        let addrs = lookup.as_ref().map(|l| l.addrs.clone()).unwrap_or_default();
+        let connection = match probe_connection(&url, &addrs, Duration::from_secs(5)).await {
+            Ok(connection) => Some(connection),
+            Err(e) => {
+                debug!("Connection probe failed: {:#}", e);
+                None
+            }
+        };
+        breakdown(lookup.map(|l| l.ms), connection.as_ref(), headers_ms, download_ms)
+    }
+
+    // Checks the response against the OpenAPI operation the loaded request
+    // was imported from, if it's still the same method and path
+    fn check_contract(app: &mut App, status: u16, content_type: &str, body: &[u8]) {
+        let Some(link) = app.openapi_link.as_ref().filter(|link| {
+            link.method.eq_ignore_ascii_case(app.method.as_str()) && openapi::url_matches_path(&app.url, &link.path)
+        }) else {
+            return;
+        };
+        let check = openapi::check_response(link, status, content_type, body).map_err(|e| format!("{:#}", e));
+        if let Ok(check) = &check {
+            if !check.violations.is_empty() {
+                app.status_message = Some(format!("{} OpenAPI contract violation(s), see the Contract tab", check.violations.len()));
+            }
+        }
+        app.contract_check = Some(check);
+    }
+
+    // Sends every HTTP request of a collection in order, folders included,
+    // and returns what was recorded in history for each. The editor is left
+    // with the last request loaded.
+    pub async fn run_collection(app: &mut App, collection_name: &str) -> Vec<HistoryEntry> {
+        fn requests(items: &[CollectionItem], out: &mut Vec<SavedRequest>) {
+            for item in items {
+                match item {
+                    CollectionItem::Request(saved) if saved.request.grpc.is_none() => out.push(saved.as_ref().clone()),
+                    CollectionItem::Folder(folder) => requests(&folder.item, out),
+                    _ => {}
+                }
+            }
+        }
+        let mut saved = Vec::new();
+        if let Some(collection) = app.collection_manager.get_collection(collection_name) {
+            requests(&collection.requests, &mut saved);
+        }
+
+        let mut entries = Vec::new();
+        for request in &saved {
+            app.load_saved_request(collection_name, request);
+            if app.is_websocket() {
+                continue;
+            }
+            let before = app.history.get_entries().last().cloned();
+            app.send_request().await;
+            match app.history.get_entries().last() {
+                Some(entry) if Some(entry) != before.as_ref() => entries.push(entry.clone()),
+                _ => {}
+            }
+        }
+        if let Some(stream) = app.event_stream.take() {
+            stream.log.cancel();
+        }
+        entries
+    }
+
+    // Enabled headers without the placeholders such as "<generated>", which
+    // are only filled in by `send_request`
+    pub fn plain_headers(app: &App) -> HashMap<String, String> {
+        app.headers.iter()
+            .filter(|(key, _)| *app.header_enabled.get(&**key).unwrap_or(&true))
+            .filter(|(_, value)| !(value.starts_with('<') && value.ends_with('>')))
+            // Derived from the URL and body by the client
+            .filter(|(key, _)| !key.eq_ignore_ascii_case("content-length") && !key.eq_ignore_ascii_case("host"))
+            .map(|(k, v)| (k.clone(), v.clone()))
+            .collect()
+    }
+
+    fn record_history(app: &mut App, headers: HashMap<String, String>, response: Option<ResponseData>) {
+        let body = app.request_body().ok().filter(|body| !body.is_empty());
+        let disabled = app.headers.iter()
+            .filter(|(key, _)| !*app.header_enabled.get(&**key).unwrap_or(&true))
+            .map(|(k, v)| (k.clone(), v.clone()))
+            .collect();
+        let selected = app.selected_history_entry().map(|entry| entry.id);
+        if let Err(e) = app.history.add_entry(app.url.clone(), app.method.clone(), headers, disabled, body, response) {
+            error!("Failed to save history: {:?}", e);
+        }
+        // A full history drops its oldest entry, which shifts the others
+        app.refresh_history_view();
+        app.select_history_entry(selected);
     }
 } 
\ No newline at end of file
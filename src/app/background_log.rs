@@ -0,0 +1,108 @@
+use std::future::Future;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use tokio::task::AbortHandle;
+use crate::models::grpc::GrpcEntry;
+use crate::models::sse::StreamEntry;
+use crate::models::websocket::WsMessage;
+
+// Entries kept in a log; older ones are dropped as new ones arrive
+const MAX_LOG_ENTRIES: usize = 5000;
+
+// Entry of a background log that can carry a note such as "Cancelled"
+pub trait LogEntry: Clone + Send + 'static {
+    fn info(text: String) -> Self;
+}
+
+impl LogEntry for StreamEntry {
+    fn info(text: String) -> Self {
+        StreamEntry::info(text)
+    }
+}
+
+impl LogEntry for GrpcEntry {
+    fn info(text: String) -> Self {
+        GrpcEntry::info(text)
+    }
+}
+
+impl LogEntry for WsMessage {
+    fn info(text: String) -> Self {
+        WsMessage::info(text)
+    }
+}
+
+// Log filled by a background task (an event stream, a gRPC stream or a
+// WebSocket connection) and read by the UI on every tick. It stays open
+// until the task returns or is cancelled.
+#[derive(Clone)]
+pub struct BackgroundLog<T> {
+    entries: Arc<Mutex<Vec<T>>>,
+    open: Arc<AtomicBool>,
+    task: AbortHandle,
+}
+
+// The task's handle for appending to the log
+#[derive(Clone)]
+pub struct LogWriter<T> {
+    entries: Arc<Mutex<Vec<T>>>,
+}
+
+impl<T: LogEntry> LogWriter<T> {
+    pub fn push(&self, entry: T) {
+        let mut entries = self.entries.lock().unwrap();
+        entries.push(entry);
+        if entries.len() > MAX_LOG_ENTRIES {
+            let excess = entries.len() - MAX_LOG_ENTRIES;
+            entries.drain(..excess);
+        }
+    }
+}
+
+impl<T: LogEntry> BackgroundLog<T> {
+    pub fn spawn<F, Fut>(initial: Vec<T>, run: F) -> Self
+    where
+        F: FnOnce(LogWriter<T>) -> Fut,
+        Fut: Future<Output = ()> + Send + 'static,
+    {
+        let entries = Arc::new(Mutex::new(initial));
+        let open = Arc::new(AtomicBool::new(true));
+
+        let task = run(LogWriter { entries: entries.clone() });
+        let task_open = open.clone();
+        let task = tokio::spawn(async move {
+            task.await;
+            task_open.store(false, Ordering::SeqCst);
+        });
+
+        BackgroundLog {
+            entries,
+            open,
+            task: task.abort_handle(),
+        }
+    }
+
+    pub fn is_open(&self) -> bool {
+        self.open.load(Ordering::SeqCst)
+    }
+
+    pub fn entries(&self) -> Vec<T> {
+        self.entries.lock().unwrap().clone()
+    }
+
+    pub fn entry_count(&self) -> usize {
+        self.entries.lock().unwrap().len()
+    }
+
+    pub fn clear(&self) {
+        self.entries.lock().unwrap().clear();
+    }
+
+    pub fn cancel(&self) {
+        if self.is_open() {
+            self.task.abort();
+            self.open.store(false, Ordering::SeqCst);
+            self.entries.lock().unwrap().push(T::info("Cancelled".to_string()));
+        }
+    }
+}
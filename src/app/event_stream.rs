@@ -0,0 +1,96 @@
+use std::time::Duration;
+use log::debug;
+use reqwest::header::{HeaderValue, CONTENT_TYPE};
+use crate::app::background_log::BackgroundLog;
+use crate::models::sse::StreamEntry;
+use crate::utils::http::mime_type;
+use crate::utils::sse::SseParser;
+
+// Reconnection delay until the server sends a `retry` field
+const DEFAULT_RETRY: Duration = Duration::from_secs(3);
+
+// A `text/event-stream` response being read in the background. Events are
+// appended to a shared log as they arrive; when the server closes the
+// stream it is reopened with `Last-Event-ID`, until cancelled.
+#[derive(Clone)]
+pub struct EventStream {
+    pub url: String,
+    pub log: BackgroundLog<StreamEntry>,
+}
+
+impl EventStream {
+    // `request` is a copy of the request that produced `response`, used to
+    // reconnect. Without it (a body that can't be replayed) the stream just ends.
+    pub fn start(client: reqwest::Client, request: Option<reqwest::Request>, response: reqwest::Response) -> Self {
+        let url = response.url().to_string();
+        let initial = vec![StreamEntry::info(format!("Connected to {}", url))];
+        let log = BackgroundLog::spawn(initial, |log| async move {
+            let push = |entry: StreamEntry| log.push(entry);
+            let mut parser = SseParser::new();
+            let mut response = response;
+            loop {
+                loop {
+                    match response.chunk().await {
+                        Ok(Some(chunk)) => {
+                            for event in parser.feed(&chunk) {
+                                push(StreamEntry::Event(event));
+                            }
+                        }
+                        Ok(None) => {
+                            push(StreamEntry::info("Stream closed by the server"));
+                            break;
+                        }
+                        Err(e) => {
+                            debug!("Event stream read failed: {}", e);
+                            push(StreamEntry::info(format!("Connection lost: {}", e)));
+                            break;
+                        }
+                    }
+                }
+
+                // Network errors while reconnecting are retried after the same delay
+                let next = loop {
+                    let Some(mut retry) = request.as_ref().and_then(|r| r.try_clone()) else { break None };
+                    let delay = parser.retry_ms.map(Duration::from_millis).unwrap_or(DEFAULT_RETRY);
+                    if let Some(id) = parser.last_event_id.as_deref().filter(|id| !id.is_empty()) {
+                        if let Ok(value) = HeaderValue::from_str(id) {
+                            retry.headers_mut().insert("Last-Event-ID", value);
+                        }
+                    }
+                    push(StreamEntry::info(match &parser.last_event_id {
+                        Some(id) => format!("Reconnecting in {}ms with Last-Event-ID: {}", delay.as_millis(), id),
+                        None => format!("Reconnecting in {}ms", delay.as_millis()),
+                    }));
+                    tokio::time::sleep(delay).await;
+                    match client.execute(retry).await {
+                        Ok(next) => break Some(next),
+                        Err(e) => push(StreamEntry::info(format!("Reconnect failed: {}", e))),
+                    }
+                };
+                match next {
+                    Some(next) if next.status().is_success() && is_event_stream(&next) => {
+                        push(StreamEntry::info("Reconnected"));
+                        parser.reset();
+                        response = next;
+                    }
+                    // A 204 or any other answer tells the client to stop
+                    Some(next) => {
+                        push(StreamEntry::info(format!("Server answered {}, not reconnecting", next.status())));
+                        break;
+                    }
+                    None => break,
+                }
+            }
+        });
+
+        EventStream { url, log }
+    }
+}
+
+pub fn is_event_stream(response: &reqwest::Response) -> bool {
+    response.headers()
+        .get(CONTENT_TYPE)
+        .and_then(|v| v.to_str().ok())
+        .map(|ct| mime_type(ct) == "text/event-stream")
+        .unwrap_or(false)
+}
@@ -0,0 +1,439 @@
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+use anyhow::{anyhow, Context, Result};
+use base64::alphabet::STANDARD;
+use base64::engine::{DecodePaddingMode, GeneralPurpose, GeneralPurposeConfig};
+use base64::Engine as _;
+use chrono::Utc;
+use log::debug;
+use prost::Message as _;
+use prost_reflect::{DescriptorPool, DynamicMessage, MessageDescriptor, MethodDescriptor, SerializeOptions};
+use prost_types::FileDescriptorProto;
+use serde_json::Value;
+use tonic::codec::{Codec, DecodeBuf, Decoder, EncodeBuf, Encoder, Streaming};
+use tonic::codegen::http::uri::PathAndQuery;
+use tonic::metadata::{BinaryMetadataValue, KeyAndValueRef, MetadataKey, MetadataMap};
+use tonic::transport::{Channel, ClientTlsConfig, Endpoint};
+use tonic::{Code, Status};
+use tonic_reflection::pb::server_reflection_client::ServerReflectionClient;
+use tonic_reflection::pb::server_reflection_request::MessageRequest;
+use tonic_reflection::pb::server_reflection_response::MessageResponse;
+use tonic_reflection::pb::ServerReflectionRequest;
+use crate::app::background_log::BackgroundLog;
+use crate::models::grpc::GrpcEntry;
+
+// Headers of the headers pane that belong to the HTTP/2 transport, not to
+// the call's metadata
+const TRANSPORT_HEADERS: &[&str] = &[
+    "accept", "accept-encoding", "connection", "content-type", "keep-alive",
+    "te", "transfer-encoding", "upgrade", "user-agent",
+];
+
+// Binary (`-bin`) metadata is base64 on the wire; senders may leave out
+// the padding
+const BINARY_METADATA: GeneralPurpose = GeneralPurpose::new(
+    &STANDARD,
+    GeneralPurposeConfig::new().with_decode_padding_mode(DecodePaddingMode::Indifferent),
+);
+
+// grpc:// and grpcs:// URLs make gRPC calls instead of HTTP requests
+pub fn is_grpc_url(url: &str) -> bool {
+    let url = url.trim_start().to_ascii_lowercase();
+    url.starts_with("grpc://") || url.starts_with("grpcs://")
+}
+
+// Scheme and address of a gRPC URL, which the loaded definitions belong to
+pub fn server(url: &str) -> String {
+    let url = url.trim().to_ascii_lowercase();
+    match url.split_once("://") {
+        Some((scheme, rest)) => format!("{}://{}", scheme, rest.split('/').next().unwrap_or_default()),
+        None => url,
+    }
+}
+
+// grpc://host:port is HTTP/2 without TLS, grpcs://host:port with TLS. A path
+// after the address is ignored.
+pub async fn connect(url: &str, timeout: Duration) -> Result<Channel> {
+    let url = url.trim();
+    let (scheme, rest) = url.split_once("://").ok_or_else(|| anyhow!("invalid gRPC URL"))?;
+    let tls = scheme.eq_ignore_ascii_case("grpcs");
+    let authority = rest.split('/').next().unwrap_or_default();
+    let uri = format!("{}://{}", if tls { "https" } else { "http" }, authority);
+
+    let mut endpoint = Endpoint::from_shared(uri)
+        .context("invalid gRPC URL")?
+        .connect_timeout(timeout);
+    if tls {
+        endpoint = endpoint.tls_config(ClientTlsConfig::new())?;
+    }
+    endpoint.connect().await.with_context(|| format!("could not connect to {}", authority))
+}
+
+// Service definitions from `.proto` files. Each file's directory is an import
+// path; the google/protobuf well-known types are built in.
+pub fn load_proto_files(paths: &[String]) -> Result<DescriptorPool> {
+    let paths: Vec<PathBuf> = paths.iter()
+        .map(|path| match path.strip_prefix("~/") {
+            Some(rest) => dirs::home_dir().unwrap_or_default().join(rest),
+            None => PathBuf::from(path),
+        })
+        .collect();
+    if let Some(missing) = paths.iter().find(|path| !path.is_file()) {
+        return Err(anyhow!("{} not found", missing.display()));
+    }
+    let includes: Vec<&Path> = paths.iter()
+        .map(|path| path.parent().filter(|dir| !dir.as_os_str().is_empty()).unwrap_or(Path::new(".")))
+        .collect();
+    let pool = protox::Compiler::new(includes)?
+        .include_imports(true)
+        .open_files(&paths)?
+        .descriptor_pool();
+    Ok(pool)
+}
+
+// Service definitions from the server's reflection service: the files
+// defining each listed service, plus the files they import.
+pub async fn reflect(channel: Channel) -> Result<DescriptorPool> {
+    let mut client = ServerReflectionClient::new(channel);
+    let services = match reflection_call(&mut client, MessageRequest::ListServices(String::new())).await? {
+        MessageResponse::ListServicesResponse(list) => list.service,
+        _ => return Err(anyhow!("unexpected reflection response")),
+    };
+
+    let mut files: HashMap<String, FileDescriptorProto> = HashMap::new();
+    let mut pending: Vec<MessageRequest> = services
+        .into_iter()
+        .filter(|service| !service.name.starts_with("grpc.reflection."))
+        .map(|service| MessageRequest::FileContainingSymbol(service.name))
+        .collect();
+    while let Some(request) = pending.pop() {
+        if matches!(&request, MessageRequest::FileByFilename(name) if files.contains_key(name)) {
+            continue;
+        }
+        let MessageResponse::FileDescriptorResponse(response) = reflection_call(&mut client, request).await? else {
+            return Err(anyhow!("unexpected reflection response"));
+        };
+        for bytes in response.file_descriptor_proto {
+            let file = FileDescriptorProto::decode(bytes.as_slice())?;
+            pending.extend(file.dependency.iter()
+                .filter(|dependency| !files.contains_key(*dependency))
+                .map(|dependency| MessageRequest::FileByFilename(dependency.clone())));
+            files.insert(file.name().to_string(), file);
+        }
+    }
+
+    let mut pool = DescriptorPool::new();
+    pool.add_file_descriptor_protos(files.into_values())?;
+    Ok(pool)
+}
+
+async fn reflection_call(client: &mut ServerReflectionClient<Channel>, request: MessageRequest) -> Result<MessageResponse> {
+    let request = ServerReflectionRequest {
+        host: String::new(),
+        message_request: Some(request),
+    };
+    let mut responses = client
+        .server_reflection_info(futures_util::stream::iter([request]))
+        .await
+        .map_err(|status| anyhow!("reflection failed: {}", status_line(&status)))?
+        .into_inner();
+    match responses.message().await.map_err(|status| anyhow!("reflection failed: {}", status_line(&status)))? {
+        Some(response) => match response.message_response {
+            Some(MessageResponse::ErrorResponse(error)) => Err(anyhow!("reflection failed: {}", error.error_message)),
+            Some(response) => Ok(response),
+            None => Err(anyhow!("empty reflection response")),
+        },
+        None => Err(anyhow!("no reflection response")),
+    }
+}
+
+// "package.Service/Method" for every method in the pool
+pub fn method_names(pool: &DescriptorPool) -> Vec<String> {
+    pool.services()
+        .flat_map(|service| service.methods().collect::<Vec<_>>())
+        .map(|method| format!("{}/{}", method.parent_service().full_name(), method.name()))
+        .collect()
+}
+
+pub fn find_method(pool: &DescriptorPool, name: &str) -> Option<MethodDescriptor> {
+    let (service, method) = name.rsplit_once('/')?;
+    pool.get_service_by_name(service)?.methods().find(|m| m.name() == method)
+}
+
+// The method's request message with every field at its default, as a
+// starting point for the body
+pub fn request_template(method: &MethodDescriptor) -> String {
+    to_json(&DynamicMessage::new(method.input()))
+        .and_then(|json| Ok(serde_json::to_string_pretty(&json)?))
+        .unwrap_or_default()
+}
+
+fn to_json(message: &DynamicMessage) -> Result<Value> {
+    let options = SerializeOptions::new().skip_default_fields(false);
+    Ok(message.serialize_with_options(serde_json::value::Serializer, &options)?)
+}
+
+// Outcome of a call: the status, response metadata (headers and trailers)
+// and, for a successful unary call, the response message
+pub struct GrpcResponse {
+    pub code: Code,
+    pub message: String,
+    pub metadata: HashMap<String, String>,
+    pub body: Option<Value>,
+}
+
+impl GrpcResponse {
+    fn from_status(status: &Status) -> Self {
+        GrpcResponse {
+            code: status.code(),
+            message: status.message().to_string(),
+            metadata: metadata_map(status.metadata()),
+            body: None,
+        }
+    }
+
+    // e.g. "0 OK", "14 Unavailable"
+    pub fn status_text(&self) -> String {
+        match self.code {
+            Code::Ok => "0 OK".to_string(),
+            code => format!("{} {:?}", code as i32, code),
+        }
+    }
+
+    // The HTTP status a gateway would answer with, used to colour the status
+    pub fn http_status(&self) -> u16 {
+        match self.code {
+            Code::Ok => 200,
+            Code::InvalidArgument | Code::FailedPrecondition | Code::OutOfRange => 400,
+            Code::Unauthenticated => 401,
+            Code::PermissionDenied => 403,
+            Code::NotFound => 404,
+            Code::AlreadyExists | Code::Aborted => 409,
+            Code::ResourceExhausted => 429,
+            Code::Cancelled => 499,
+            Code::Unimplemented => 501,
+            Code::Unavailable => 503,
+            Code::DeadlineExceeded => 504,
+            _ => 500,
+        }
+    }
+}
+
+// A call ready to be sent: the request message parsed from the JSON body and
+// the metadata taken from the enabled headers
+pub struct GrpcCall {
+    channel: Channel,
+    method: MethodDescriptor,
+    request: tonic::Request<DynamicMessage>,
+}
+
+impl GrpcCall {
+    pub fn new(channel: Channel, method: MethodDescriptor, body: &str, headers: &HashMap<String, String>) -> Result<Self> {
+        if method.is_client_streaming() {
+            return Err(anyhow!("client-streaming methods aren't supported"));
+        }
+        let message = if body.trim().is_empty() {
+            DynamicMessage::new(method.input())
+        } else {
+            let mut deserializer = serde_json::Deserializer::from_str(body);
+            let message = DynamicMessage::deserialize(method.input(), &mut deserializer)
+                .with_context(|| format!("body is not a valid {}", method.input().full_name()))?;
+            deserializer.end()?;
+            message
+        };
+
+        let mut request = tonic::Request::new(message);
+        for (name, value) in headers {
+            let name = name.to_ascii_lowercase();
+            if TRANSPORT_HEADERS.contains(&name.as_str()) || name.starts_with("grpc-") {
+                continue;
+            }
+            let invalid = || format!("invalid metadata: {}", name);
+            if name.ends_with("-bin") {
+                // Typed as base64, like the value is shown in responses
+                let key = MetadataKey::from_bytes(name.as_bytes()).with_context(invalid)?;
+                let bytes = BINARY_METADATA.decode(value.trim())
+                    .with_context(|| format!("metadata {} is not valid base64", name))?;
+                request.metadata_mut().insert_bin(key, BinaryMetadataValue::from_bytes(&bytes));
+            } else {
+                let key = MetadataKey::from_bytes(name.as_bytes()).with_context(invalid)?;
+                request.metadata_mut().insert(key, value.parse().with_context(invalid)?);
+            }
+        }
+        Ok(GrpcCall { channel, method, request })
+    }
+
+    pub fn is_server_streaming(&self) -> bool {
+        self.method.is_server_streaming()
+    }
+
+    pub async fn unary(self, timeout: Duration) -> GrpcResponse {
+        let GrpcCall { channel, method, mut request } = self;
+        request.set_timeout(timeout);
+        let mut grpc = tonic::client::Grpc::new(channel);
+        match tokio::time::timeout(timeout, grpc.ready()).await {
+            Ok(Ok(())) => {}
+            Ok(Err(e)) => return GrpcResponse::from_status(&Status::unavailable(e.to_string())),
+            Err(_) => return GrpcResponse::from_status(&timed_out(timeout)),
+        }
+        match grpc.unary(request, path(&method), DynamicCodec(method.output())).await {
+            Ok(response) => {
+                let metadata = metadata_map(response.metadata());
+                let body = to_json(response.get_ref());
+                match body {
+                    Ok(body) => GrpcResponse { code: Code::Ok, message: String::new(), metadata, body: Some(body) },
+                    Err(e) => GrpcResponse { code: Code::Internal, message: e.to_string(), metadata, body: None },
+                }
+            }
+            Err(status) => GrpcResponse::from_status(&status),
+        }
+    }
+
+    // Starts a server-streaming call. The response carries the headers; the
+    // messages are read into the stream's log. `timeout` applies until the
+    // headers arrive, the stream itself stays open until cancelled.
+    pub async fn server_streaming(self, url: &str, timeout: Duration) -> (GrpcResponse, Option<GrpcStream>) {
+        let GrpcCall { channel, method, request } = self;
+        let mut grpc = tonic::client::Grpc::new(channel);
+        match tokio::time::timeout(timeout, grpc.ready()).await {
+            Ok(Ok(())) => {}
+            Ok(Err(e)) => return (GrpcResponse::from_status(&Status::unavailable(e.to_string())), None),
+            Err(_) => return (GrpcResponse::from_status(&timed_out(timeout)), None),
+        }
+        let call = grpc.server_streaming(request, path(&method), DynamicCodec(method.output()));
+        let Ok(result) = tokio::time::timeout(timeout, call).await else {
+            return (GrpcResponse::from_status(&timed_out(timeout)), None);
+        };
+        match result {
+            Ok(response) => {
+                let headers = GrpcResponse {
+                    code: Code::Ok,
+                    message: String::new(),
+                    metadata: metadata_map(response.metadata()),
+                    body: None,
+                };
+                (headers, Some(GrpcStream::start(url, response.into_inner())))
+            }
+            Err(status) => (GrpcResponse::from_status(&status), None),
+        }
+    }
+}
+
+fn path(method: &MethodDescriptor) -> PathAndQuery {
+    let path = format!("/{}/{}", method.parent_service().full_name(), method.name());
+    PathAndQuery::try_from(path).expect("service and method names are valid path segments")
+}
+
+fn timed_out(timeout: Duration) -> Status {
+    Status::deadline_exceeded(format!("timed out after {}s", timeout.as_secs()))
+}
+
+fn status_line(status: &Status) -> String {
+    format!("{:?}: {}", status.code(), status.message())
+}
+
+fn metadata_map(metadata: &MetadataMap) -> HashMap<String, String> {
+    metadata.iter()
+        .map(|entry| match entry {
+            KeyAndValueRef::Ascii(key, value) => (key.to_string(), value.to_str().unwrap_or("").to_string()),
+            KeyAndValueRef::Binary(key, value) => {
+                let text = value.to_bytes().map(|bytes| BINARY_METADATA.encode(bytes)).unwrap_or_default();
+                (key.to_string(), text)
+            }
+        })
+        .collect()
+}
+
+// Messages of a server-streaming call, appended to a shared log by a
+// background task as they arrive
+#[derive(Clone)]
+pub struct GrpcStream {
+    pub url: String,
+    pub log: BackgroundLog<GrpcEntry>,
+}
+
+impl GrpcStream {
+    fn start(url: &str, mut messages: Streaming<DynamicMessage>) -> Self {
+        let log = BackgroundLog::spawn(Vec::new(), |log| async move {
+            let push = |entry: GrpcEntry| log.push(entry);
+            loop {
+                match messages.message().await {
+                    Ok(Some(message)) => match to_json(&message) {
+                        Ok(json) => push(GrpcEntry::Message { received_at: Utc::now(), json: json.to_string() }),
+                        Err(e) => push(GrpcEntry::info(format!("Unreadable message: {}", e))),
+                    },
+                    Ok(None) => {
+                        push(GrpcEntry::info("Stream finished: 0 OK"));
+                        break;
+                    }
+                    Err(status) => {
+                        debug!("gRPC stream failed: {}", status);
+                        push(GrpcEntry::info(format!("Stream failed: {}", status_line(&status))));
+                        break;
+                    }
+                }
+            }
+        });
+
+        GrpcStream { url: url.to_string(), log }
+    }
+}
+
+// Encodes and decodes messages of types only known at runtime
+#[derive(Clone)]
+struct DynamicCodec(MessageDescriptor);
+
+impl Codec for DynamicCodec {
+    type Encode = DynamicMessage;
+    type Decode = DynamicMessage;
+    type Encoder = DynamicCodec;
+    type Decoder = DynamicCodec;
+
+    fn encoder(&mut self) -> Self::Encoder {
+        self.clone()
+    }
+
+    fn decoder(&mut self) -> Self::Decoder {
+        self.clone()
+    }
+}
+
+impl Encoder for DynamicCodec {
+    type Item = DynamicMessage;
+    type Error = Status;
+
+    fn encode(&mut self, item: DynamicMessage, dst: &mut EncodeBuf<'_>) -> Result<(), Status> {
+        item.encode(dst).map_err(|e| Status::internal(e.to_string()))
+    }
+}
+
+impl Decoder for DynamicCodec {
+    type Item = DynamicMessage;
+    type Error = Status;
+
+    fn decode(&mut self, src: &mut DecodeBuf<'_>) -> Result<Option<DynamicMessage>, Status> {
+        DynamicMessage::decode(self.0.clone(), src)
+            .map(Some)
+            .map_err(|e| Status::internal(e.to_string()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn binary_metadata_is_shown_as_base64() {
+        let mut metadata = MetadataMap::new();
+        metadata.insert_bin("trace-bin", BinaryMetadataValue::from_bytes(&[0, 1, 0xfe, 0xff]));
+        metadata.insert("plain", "text".parse().unwrap());
+        let map = metadata_map(&metadata);
+        assert_eq!(map["trace-bin"], "AAH+/w==");
+        assert_eq!(map["plain"], "text");
+        // Typed values may leave out the padding
+        assert_eq!(BINARY_METADATA.decode("AAH+/w").unwrap(), vec![0, 1, 0xfe, 0xff]);
+        assert_eq!(BINARY_METADATA.decode("AAH+/w==").unwrap(), vec![0, 1, 0xfe, 0xff]);
+        assert!(BINARY_METADATA.decode("not base64!").is_err());
+    }
+}
@@ -0,0 +1,250 @@
+use chrono::{Duration, Local, NaiveDate};
+use regex::{Regex, RegexBuilder};
+use crate::data::history::HistoryEntry;
+
+const METHODS: [&str; 9] = ["GET", "POST", "PUT", "DELETE", "PATCH", "HEAD", "OPTIONS", "TRACE", "CONNECT"];
+
+// What the history list is narrowed down to, typed in its filter bar.
+// Words are combined with AND; several methods or statuses match either.
+//   get, POST                        method
+//   2xx, 404                         status class or exact status
+//   after:2026-10-01, before:today   days (inclusive, local time), also on:
+//   body:token                       text in the request or response body
+//   anything else                    part of the URL
+#[derive(Debug, Clone, Default)]
+pub struct HistoryFilter {
+    pub query: String,
+    pub cursor: usize,
+    pub error: Option<String>,
+    methods: Vec<String>,
+    statuses: Vec<Status>,
+    after: Option<NaiveDate>,
+    before: Option<NaiveDate>,
+    body: Vec<Regex>,
+    url: Vec<Regex>,
+}
+
+#[derive(Debug, Clone, Copy)]
+enum Status {
+    Class(u16),
+    Exact(u16),
+}
+
+impl HistoryFilter {
+    pub fn is_active(&self) -> bool {
+        !self.query.trim().is_empty()
+    }
+
+    pub fn clear(&mut self) {
+        *self = Self::default();
+    }
+
+    // Parses the query again after it was edited. Words that can't be
+    // understood are left out and reported.
+    pub fn update(&mut self) {
+        let query = std::mem::take(&mut self.query);
+        *self = Self { cursor: self.cursor, ..Self::default() };
+        let today = Local::now().date_naive();
+        let mut invalid = Vec::new();
+
+        for word in query.split_whitespace() {
+            let upper = word.to_uppercase();
+            if METHODS.contains(&upper.as_str()) {
+                self.methods.push(upper);
+            } else if let Some(status) = status(word) {
+                self.statuses.push(status);
+            } else if let Some((key, value)) = word.split_once(':').filter(|(key, _)| ["after", "before", "on", "body"].contains(key)) {
+                if key == "body" {
+                    self.body.extend(literal(value));
+                    continue;
+                }
+                let Some(day) = day(value, today) else {
+                    invalid.push(word);
+                    continue;
+                };
+                if key != "before" {
+                    self.after = Some(day);
+                }
+                if key != "after" {
+                    self.before = Some(day);
+                }
+            } else {
+                self.url.extend(literal(word));
+            }
+        }
+        if !invalid.is_empty() {
+            self.error = Some(format!("not a date: {} (YYYY-MM-DD, today, yesterday or 3d)", invalid.join(" ")));
+        }
+        self.query = query;
+    }
+
+    pub fn matches(&self, entry: &HistoryEntry) -> bool {
+        let request = &entry.request;
+        let status = entry.response.as_ref().and_then(|response| response.status);
+        let day = entry.timestamp.with_timezone(&Local).date_naive();
+        (self.methods.is_empty() || self.methods.contains(&request.method.to_uppercase()))
+            && (self.statuses.is_empty() || self.statuses.iter().any(|s| status.is_some_and(|code| s.matches(code))))
+            && self.after.is_none_or(|after| day >= after)
+            && self.before.is_none_or(|before| day <= before)
+            && self.url.iter().all(|re| re.is_match(&request.url))
+            && self.body.iter().all(|re| {
+                request.body.as_deref().is_some_and(|body| re.is_match(body))
+                    || entry.response.as_ref().is_some_and(|response| !response.is_binary() && re.is_match(&response.body))
+            })
+    }
+}
+
+impl Status {
+    fn matches(self, code: u16) -> bool {
+        match self {
+            Status::Class(class) => code / 100 == class,
+            Status::Exact(exact) => code == exact,
+        }
+    }
+}
+
+// `2xx` or `404`
+fn status(word: &str) -> Option<Status> {
+    let lower = word.to_lowercase();
+    if let Some(class) = lower.strip_suffix("xx").and_then(|digit| digit.parse::<u16>().ok()) {
+        return (1..=5).contains(&class).then_some(Status::Class(class));
+    }
+    let code: u16 = word.parse().ok()?;
+    (word.len() == 3 && (100..600).contains(&code)).then_some(Status::Exact(code))
+}
+
+// `2026-10-01`, `today`, `yesterday` or `3d` (three days ago)
+fn day(value: &str, today: NaiveDate) -> Option<NaiveDate> {
+    match value {
+        "today" => Some(today),
+        "yesterday" => Some(today - Duration::days(1)),
+        _ => match value.strip_suffix('d').and_then(|days| days.parse::<i64>().ok()) {
+            Some(days) => Some(today - Duration::days(days)),
+            None => NaiveDate::parse_from_str(value, "%Y-%m-%d").ok(),
+        },
+    }
+}
+
+// Case-insensitive plain text, as regexes to avoid lowercasing every body
+fn literal(text: &str) -> Option<Regex> {
+    if text.is_empty() {
+        return None;
+    }
+    RegexBuilder::new(&regex::escape(text)).case_insensitive(true).build().ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+    use chrono::Utc;
+    use crate::data::history::{encode_body, RequestData, ResponseData};
+    use crate::models::collection::next_request_id;
+
+    fn filter(query: &str) -> HistoryFilter {
+        let mut filter = HistoryFilter { query: query.to_string(), ..HistoryFilter::default() };
+        filter.update();
+        filter
+    }
+
+    fn entry(method: &str, url: &str, status: Option<u16>, days_ago: i64) -> HistoryEntry {
+        HistoryEntry {
+            id: next_request_id(),
+            timestamp: (Local::now() - Duration::days(days_ago)).with_timezone(&Utc),
+            request: RequestData {
+                url: url.to_string(),
+                method: method.to_string(),
+                headers: HashMap::new(),
+                disabled_headers: HashMap::new(),
+                body: None,
+            },
+            response: status.map(|status| ResponseData {
+                status: Some(status),
+                status_text: None,
+                headers: Vec::new(),
+                body: String::new(),
+                encoding: None,
+                time_ms: 12,
+                size_bytes: 0,
+                http_version: None,
+                timings: None,
+            }),
+        }
+    }
+
+    fn with_bodies(mut entry: HistoryEntry, request: &str, response: &[u8], is_binary: bool) -> HistoryEntry {
+        entry.request.body = Some(request.to_string());
+        if let Some(data) = entry.response.as_mut() {
+            (data.body, data.encoding) = encode_body(response, is_binary);
+        }
+        entry
+    }
+
+    #[test]
+    fn methods_and_statuses() {
+        let get_ok = entry("GET", "https://api.example.com/users", Some(200), 0);
+        let post_ok = entry("POST", "https://api.example.com/users", Some(201), 0);
+        let get_missing = entry("GET", "https://api.example.com/users/9", Some(404), 0);
+        let failed = entry("GET", "https://down.example.com/", None, 0);
+
+        let ok_gets = filter("get 2xx");
+        assert!(ok_gets.matches(&get_ok));
+        assert!(!ok_gets.matches(&post_ok));
+        assert!(!ok_gets.matches(&get_missing));
+        assert!(!ok_gets.matches(&failed));
+
+        // Several methods or statuses match either
+        let either = filter("GET post 404 201");
+        assert!(either.matches(&post_ok));
+        assert!(either.matches(&get_missing));
+        assert!(!either.matches(&get_ok));
+
+        // Neither a class nor a status, so part of the URL
+        let not_statuses = filter("6xx 2001");
+        assert!(not_statuses.statuses.is_empty());
+        assert_eq!(not_statuses.url.len(), 2);
+        assert!(filter("").matches(&failed));
+        assert!(!filter("").is_active());
+    }
+
+    #[test]
+    fn days() {
+        let today = entry("GET", "https://example.com/", Some(200), 0);
+        let last_week = entry("GET", "https://example.com/", Some(200), 7);
+
+        assert!(filter("after:3d").matches(&today));
+        assert!(!filter("after:3d").matches(&last_week));
+        assert!(filter("before:yesterday").matches(&last_week));
+        assert!(!filter("before:yesterday").matches(&today));
+        assert!(filter("on:today").matches(&today));
+        assert!(filter("on:7d").matches(&last_week));
+        assert!(!filter("on:6d").matches(&last_week));
+        assert!(filter("before:2000-01-01").error.is_none());
+        assert!(!filter("before:2000-01-01").matches(&last_week));
+
+        let invalid = filter("after:soon users");
+        assert_eq!(invalid.error.as_deref(), Some("not a date: after:soon (YYYY-MM-DD, today, yesterday or 3d)"));
+        // The other words still apply
+        assert_eq!(invalid.url.len(), 1);
+        assert!(invalid.after.is_none());
+    }
+
+    #[test]
+    fn urls_and_bodies() {
+        let search = entry("GET", "https://example.com/search?q=a+b&page=1", Some(200), 0);
+        assert!(filter("Example.COM q=a+b").matches(&search));
+        assert!(!filter("example.com page=2").matches(&search));
+
+        let pets = with_bodies(entry("POST", "https://example.com/pets", Some(201), 0), "{\"name\":\"Rex\"}", b"{\"id\":7}", false);
+        assert!(filter("body:rex").matches(&pets));
+        assert!(filter("body:\"id\":7").matches(&pets));
+        assert!(!filter("body:rex body:fido").matches(&pets));
+        // Words naming other keys are URL text
+        assert_eq!(filter("pets other:x").url.len(), 2);
+
+        // Binary bodies aren't searched, base64 or not
+        let image = with_bodies(entry("POST", "https://example.com/upload", Some(200), 0), "", b"\x89PNG", true);
+        let encoded = image.response.as_ref().unwrap().body.clone();
+        assert!(!filter(&format!("body:{}", encoded)).matches(&image));
+    }
+}
@@ -5,10 +5,15 @@ use super::state::{
     HttpMethod
 };
 use super::{
-    CollectionsFocus, CollectionView
+    CollectionsFocus, CollectionView, PromptKind, GraphQlPane, RequestHandler
 };
+use super::grpc;
 use chrono::Utc;
 use crate::models::collection::{Collection, CollectionInfo};
+use crate::models::CollectionItem;
+use crate::utils::http::suggested_file_name;
+use crate::utils::har;
+use super::state::user_path;
 use arboard::Clipboard;
 
 pub struct InputHandler;
@@ -23,6 +28,23 @@ impl InputHandler {
             _ => debug!("Other key pressed: {:?}", key.code),
         }
         
+        // A damaged file is dealt with before anything else
+        if !app.damaged_files.is_empty() {
+            match key.code {
+                KeyCode::Char('r') => app.restore_damaged_file(),
+                KeyCode::Char('s') => app.set_aside_damaged_file(),
+                KeyCode::Char('c') | KeyCode::Esc => app.keep_damaged_file(),
+                KeyCode::Char('q') => return true,
+                _ => {}
+            }
+            return false;
+        }
+
+        if app.prompt.is_some() {
+            Self::handle_prompt(app, key).await;
+            return false;
+        }
+
         match app.input_mode {
             InputMode::Normal => Self::handle_normal_mode(app, key).await,
             InputMode::Editing(field) => Self::handle_editing_mode(app, field, key),
@@ -56,19 +78,59 @@ impl InputHandler {
                             app.active_field = Field::NavPanel;
                         }
                         CollectionView::Requests => {
-                            app.collection_view = CollectionView::List;
-                            app.selected_collection = None;
+                            app.request_selected_index = 0;
+                            if app.collection_folder.pop().is_none() {
+                                app.collection_view = CollectionView::List;
+                                app.selected_collection = None;
+                            }
                         }
                     }
                     return false;
                 }
+                KeyCode::Char('i') if app.collection_view == CollectionView::List => {
+                    app.open_prompt(PromptKind::ImportCollection, String::new());
+                    return false;
+                }
+                KeyCode::Char('v') if app.collection_view == CollectionView::Requests => {
+                    app.open_prompt(PromptKind::CollectionVariable, String::new());
+                    return false;
+                }
+                KeyCode::Char('r') if app.collection_view == CollectionView::Requests => {
+                    let name = app.selected_collection.clone().unwrap_or_default();
+                    app.open_prompt(PromptKind::RunCollection, format!("{}.har", name));
+                    return false;
+                }
                 KeyCode::Enter => {
-                    if app.collection_view == CollectionView::List {
-                        let collections = app.collection_manager.get_collections();
-                        if let Some(collection) = collections.values().nth(app.collection_selected_index) {
-                            app.selected_collection = Some(collection.info.name.clone());
-                            app.collection_view = CollectionView::Requests;
-                            app.request_selected_index = 0;
+                    match app.collection_view {
+                        CollectionView::List => {
+                            let collections = app.collection_manager.get_collections();
+                            if let Some(name) = collections.values().nth(app.collection_selected_index).map(|c| c.info.name.clone()) {
+                                if let Err(e) = app.refresh_collection_source(&name) {
+                                    app.status_message = Some(format!("Could not read {}: {:#}", name, e));
+                                }
+                                app.selected_collection = Some(name);
+                                app.collection_view = CollectionView::Requests;
+                                app.collection_folder.clear();
+                                app.request_selected_index = 0;
+                            }
+                        }
+                        CollectionView::Requests => {
+                            let selected = app.collection_items().get(app.request_selected_index).cloned();
+                            if let (Some(item), Some(name)) = (selected, app.selected_collection.clone()) {
+                                match &item {
+                                    CollectionItem::Request(saved) => app.load_saved_request(&name, saved),
+                                    CollectionItem::WebSocket(saved) => app.load_saved_websocket(&name, saved),
+                                    CollectionItem::Folder(folder) => {
+                                        app.collection_folder.push(folder.name.clone());
+                                        app.request_selected_index = 0;
+                                        return false;
+                                    }
+                                }
+                                app.show_collections = false;
+                                app.collection_view = CollectionView::List;
+                                app.collection_folder.clear();
+                                app.active_field = Field::Url;
+                            }
                         }
                     }
                     return false;
@@ -81,13 +143,7 @@ impl InputHandler {
                             }
                         }
                         CollectionView::Requests => {
-                            if let Some(collection_name) = &app.selected_collection {
-                                if let Some(collection) = app.collection_manager.get_collection(collection_name) {
-                                    if app.request_selected_index > 0 {
-                                        app.request_selected_index -= 1;
-                                    }
-                                }
-                            }
+                            app.request_selected_index = app.request_selected_index.saturating_sub(1);
                         }
                     }
                     return false;
@@ -101,13 +157,9 @@ impl InputHandler {
                             }
                         }
                         CollectionView::Requests => {
-                            if let Some(collection_name) = &app.selected_collection {
-                                if let Some(collection) = app.collection_manager.get_collection(collection_name) {
-                                    let len = collection.requests.len();
-                                    if len > 0 {
-                                        app.request_selected_index = (app.request_selected_index + 1) % len;
-                                    }
-                                }
+                            let len = app.collection_items().len();
+                            if len > 0 {
+                                app.request_selected_index = (app.request_selected_index + 1) % len;
                             }
                         }
                     }
@@ -117,6 +169,127 @@ impl InputHandler {
             }
         }
 
+        // GraphQL schema explorer
+        if app.show_schema {
+            match key.code {
+                KeyCode::Esc | KeyCode::Backspace | KeyCode::Left => {
+                    if app.schema_path.pop().is_none() {
+                        app.show_schema = false;
+                    }
+                    app.schema_selected_index = 0;
+                }
+                KeyCode::Up => {
+                    app.schema_selected_index = app.schema_selected_index.saturating_sub(1);
+                }
+                KeyCode::Down if app.schema_selected_index + 1 < app.schema_entry_count() => {
+                    app.schema_selected_index += 1;
+                }
+                KeyCode::Enter | KeyCode::Right => app.schema_open_selected(),
+                _ => {}
+            }
+            return false;
+        }
+
+        // Cookie manager
+        if app.show_cookies {
+            match key.code {
+                KeyCode::Esc => {
+                    app.show_cookies = false;
+                    app.active_field = Field::NavPanel;
+                }
+                KeyCode::Up => {
+                    app.cookies_selected_index = app.cookies_selected_index.saturating_sub(1);
+                }
+                KeyCode::Down => {
+                    let len = app.cookie_jar.cookies().len();
+                    if app.cookies_selected_index + 1 < len {
+                        app.cookies_selected_index += 1;
+                    }
+                }
+                KeyCode::Enter | KeyCode::Char('e') => {
+                    if let Some(cookie) = app.selected_stored_cookie() {
+                        app.open_prompt(PromptKind::EditCookie, cookie.value);
+                    }
+                }
+                KeyCode::Char('d') | KeyCode::Delete => {
+                    if let Some(cookie) = app.selected_stored_cookie() {
+                        debug!("Deleting cookie {} for {}", cookie.name, cookie.domain);
+                        app.cookie_jar.remove(&cookie);
+                        app.cookies_selected_index = app.cookies_selected_index.saturating_sub(1);
+                    }
+                }
+                KeyCode::Char('D') => {
+                    app.cookie_jar.clear();
+                    app.cookies_selected_index = 0;
+                }
+                _ => {}
+            }
+            return false;
+        }
+
+        // History list: Space marks entries for export, '/' filters the list,
+        // 'd' deletes the marked or selected entries and 'D' all shown. 'r'
+        // sends the selected request again, 's' saves it to a collection.
+        if app.show_history && app.active_field == Field::History {
+            match key.code {
+                KeyCode::Char('r') => {
+                    if let Some(entry) = app.selected_history_entry().cloned() {
+                        app.load_history_entry(&entry);
+                        app.show_history = false;
+                        app.active_field = Field::Url;
+                        app.send_request().await;
+                    }
+                    return false;
+                }
+                KeyCode::Char('s') => {
+                    if let Some(entry) = app.selected_history_entry().cloned() {
+                        app.load_history_entry(&entry);
+                        app.show_history = false;
+                        app.active_field = Field::SaveButton;
+                        app.save_dialog_visible = true;
+                        app.save_dialog_selected_index = 0;
+                    }
+                    return false;
+                }
+                KeyCode::Char(' ') => {
+                    app.toggle_history_mark();
+                    return false;
+                }
+                KeyCode::Char('/') => {
+                    app.history_filter.cursor = app.history_filter.query.chars().count();
+                    app.input_mode = InputMode::Editing(Field::HistoryFilter);
+                    return false;
+                }
+                KeyCode::Char('d') | KeyCode::Delete => {
+                    app.delete_history_entries(false);
+                    return false;
+                }
+                KeyCode::Char('D') => {
+                    app.delete_history_entries(true);
+                    return false;
+                }
+                KeyCode::Esc if app.history_filter.is_active() => {
+                    app.history_filter.clear();
+                    app.refresh_history_view();
+                    return false;
+                }
+                KeyCode::Esc => {
+                    app.show_history = false;
+                    app.active_field = Field::NavPanel;
+                    return false;
+                }
+                KeyCode::Char('e') => {
+                    app.open_prompt(PromptKind::ExportHar, "raquet.har".to_string());
+                    return false;
+                }
+                KeyCode::Char('i') => {
+                    app.open_prompt(PromptKind::ImportHar, String::new());
+                    return false;
+                }
+                _ => {}
+            }
+        }
+
         // Handle method selector
         if app.show_method_selector {
             match key.code {
@@ -125,9 +298,7 @@ impl InputHandler {
                     return false;
                 }
                 KeyCode::Enter => {
-                    let methods = HttpMethod::all();
-                    app.method = methods[app.selector_method_index];
-                    app.show_method_selector = false;
+                    app.choose_selected_method();
                     return false;
                 }
                 KeyCode::Up => {
@@ -137,14 +308,29 @@ impl InputHandler {
                     return false;
                 }
                 KeyCode::Down => {
-                    let methods = HttpMethod::all();
-                    app.selector_method_index = (app.selector_method_index + 1) % methods.len();
+                    // One extra entry for the custom method
+                    let entries = HttpMethod::all().len() + 1;
+                    app.selector_method_index = (app.selector_method_index + 1) % entries;
                     return false;
                 }
                 _ => return false,
             }
         }
 
+        // gRPC method picker
+        if app.show_grpc_methods {
+            match key.code {
+                KeyCode::Esc => app.show_grpc_methods = false,
+                KeyCode::Enter => app.choose_grpc_method(),
+                KeyCode::Up => app.grpc_method_index = app.grpc_method_index.saturating_sub(1),
+                KeyCode::Down if app.grpc_method_index + 1 < app.grpc_methods().len() => {
+                    app.grpc_method_index += 1;
+                }
+                _ => {}
+            }
+            return false;
+        }
+
         // Handle save dialog
         if app.save_dialog_visible {
             match key.code {
@@ -162,6 +348,7 @@ impl InputHandler {
                     if let Some(name) = collection_name {
                         if let Err(e) = app.save_to_collection(&name) {
                             debug!("Failed to save to collection: {}", e);
+                            app.status_message = Some(format!("Save failed: {:#}", e));
                         }
                     }
                     app.save_dialog_visible = false;
@@ -267,6 +454,335 @@ impl InputHandler {
             }
         }
 
+        // WebSocket mode: ping, close and binary frames from the request row,
+        // scrolling the message log from the response body
+        if app.is_websocket() {
+            if matches!(app.active_field, Field::Url | Field::SendButton | Field::ResponseBody) {
+                if let KeyCode::Char(c @ ('b' | 'i' | 'x')) = key.code {
+                    let result = match app.open_websocket() {
+                        Some(ws) => match c {
+                            'b' => ws.send_binary(app.body.as_bytes().to_vec()),
+                            'i' => ws.ping(),
+                            _ => ws.close(),
+                        },
+                        None => Err(anyhow::anyhow!("not connected")),
+                    };
+                    if let Err(e) = result {
+                        app.status_message = Some(format!("WebSocket: {}", e));
+                    }
+                    app.websocket_scroll = 0;
+                    return false;
+                }
+            }
+            if app.active_field == Field::ResponseBody {
+                let len = app.websocket.as_ref().map(|ws| ws.log.entry_count()).unwrap_or(0);
+                if Self::scroll_log(&mut app.websocket_scroll, len, key.code) {
+                    return false;
+                }
+                if key.code == KeyCode::Char('c') {
+                    if let Some(ws) = &app.websocket {
+                        ws.log.clear();
+                    }
+                    app.websocket_scroll = 0;
+                    return false;
+                }
+            }
+        }
+
+        // Server-Sent Events: 'x' stops the stream, the log scrolls like the WebSocket one
+        if let Some(stream) = app.event_stream.clone() {
+            if matches!(app.active_field, Field::Url | Field::SendButton | Field::ResponseBody)
+                && key.code == KeyCode::Char('x')
+            {
+                stream.log.cancel();
+                app.status_message = Some("Event stream stopped".to_string());
+                return false;
+            }
+            if app.active_field == Field::ResponseBody {
+                if Self::scroll_log(&mut app.event_stream_scroll, stream.log.entry_count(), key.code) {
+                    return false;
+                }
+                if key.code == KeyCode::Char('c') {
+                    stream.log.clear();
+                    app.event_stream_scroll = 0;
+                    return false;
+                }
+            }
+        }
+
+        // gRPC mode: Enter on the method box picks the method to call, loading
+        // the service definitions first; 'P' sets .proto files, 'R' reloads
+        if app.is_grpc() {
+            match (app.active_field, key.code) {
+                (Field::Method, KeyCode::Enter) => {
+                    app.drop_stale_grpc_pool();
+                    if app.grpc_pool.is_some() || RequestHandler::load_grpc_definitions(app).await {
+                        app.open_grpc_methods();
+                    }
+                    return false;
+                }
+                (Field::Url | Field::SendButton, KeyCode::Char('P')) => {
+                    let current = app.grpc_target.proto_files.join(", ");
+                    app.open_prompt(PromptKind::ProtoFiles, current);
+                    return false;
+                }
+                (Field::Url | Field::SendButton, KeyCode::Char('R')) => {
+                    RequestHandler::load_grpc_definitions(app).await;
+                    return false;
+                }
+                _ => {}
+            }
+        }
+
+        // Server-streaming gRPC calls: 'x' cancels, the log scrolls like the event stream
+        if let Some(stream) = app.grpc_stream.clone() {
+            if matches!(app.active_field, Field::Url | Field::SendButton | Field::ResponseBody)
+                && key.code == KeyCode::Char('x')
+            {
+                stream.log.cancel();
+                app.status_message = Some("gRPC stream cancelled".to_string());
+                return false;
+            }
+            if app.active_field == Field::ResponseBody {
+                if Self::scroll_log(&mut app.grpc_stream_scroll, stream.log.entry_count(), key.code) {
+                    return false;
+                }
+                if key.code == KeyCode::Char('c') {
+                    stream.log.clear();
+                    app.grpc_stream_scroll = 0;
+                    return false;
+                }
+            }
+        }
+
+        // JSON responses are shown as a tree with a selected node
+        if app.active_field == Field::ResponseBody && app.response_json.is_some() {
+            let selected = app.json_tree.selected;
+            match key.code {
+                KeyCode::Up => {
+                    app.select_json_row(selected.saturating_sub(1));
+                    return false;
+                }
+                KeyCode::Down => {
+                    app.select_json_row(selected + 1);
+                    return false;
+                }
+                KeyCode::PageUp => {
+                    app.select_json_row(selected.saturating_sub(20));
+                    return false;
+                }
+                KeyCode::PageDown => {
+                    app.select_json_row(selected + 20);
+                    return false;
+                }
+                KeyCode::Home => {
+                    app.select_json_row(0);
+                    return false;
+                }
+                KeyCode::End => {
+                    app.select_json_row(usize::MAX);
+                    return false;
+                }
+                KeyCode::Enter | KeyCode::Char(' ') => {
+                    app.toggle_selected_json_node(None);
+                    return false;
+                }
+                KeyCode::Left => {
+                    app.toggle_selected_json_node(Some(false));
+                    return false;
+                }
+                KeyCode::Right => {
+                    app.toggle_selected_json_node(Some(true));
+                    return false;
+                }
+                KeyCode::Char('+') => {
+                    app.json_tree.expand_all();
+                    app.refresh_response_lines();
+                    app.refresh_search();
+                    return false;
+                }
+                KeyCode::Char('-') => {
+                    if let Some(json) = &app.response_json {
+                        app.json_tree.collapse_all(json);
+                    }
+                    app.response_scroll = 0;
+                    app.refresh_response_lines();
+                    app.refresh_search();
+                    return false;
+                }
+                KeyCode::Char('y') | KeyCode::Char('Y') => {
+                    if let Some((path, value)) = app.selected_json_node() {
+                        let (what, text) = if key.code == KeyCode::Char('y') {
+                            ("path", path)
+                        } else {
+                            ("value", serde_json::to_string_pretty(&value).unwrap_or_default())
+                        };
+                        let copied = Clipboard::new()
+                            .and_then(|mut clipboard| clipboard.set_text(text))
+                            .is_ok();
+                        app.status_message = Some(if copied {
+                            format!("Copied {}", what)
+                        } else {
+                            "Clipboard unavailable".to_string()
+                        });
+                    }
+                    return false;
+                }
+                KeyCode::Char('n') | KeyCode::Char('N') => {
+                    let line = if key.code == KeyCode::Char('n') {
+                        app.response_search.next_match().map(|m| m.line)
+                    } else {
+                        app.response_search.previous_match().map(|m| m.line)
+                    };
+                    if let Some(line) = line {
+                        app.select_json_row(line);
+                    }
+                    return false;
+                }
+                _ => {}
+            }
+        }
+
+        if app.active_field == Field::ResponseBody {
+            let max_scroll = app.response_row_count().saturating_sub(1);
+            match key.code {
+                KeyCode::Up => {
+                    app.response_scroll = app.response_scroll.saturating_sub(1);
+                    return false;
+                }
+                KeyCode::Down => {
+                    app.response_scroll = (app.response_scroll + 1).min(max_scroll);
+                    return false;
+                }
+                KeyCode::PageUp => {
+                    app.response_scroll = app.response_scroll.saturating_sub(20);
+                    return false;
+                }
+                KeyCode::PageDown => {
+                    app.response_scroll = (app.response_scroll + 20).min(max_scroll);
+                    return false;
+                }
+                KeyCode::Home => {
+                    app.response_scroll = 0;
+                    return false;
+                }
+                KeyCode::End => {
+                    app.response_scroll = max_scroll;
+                    return false;
+                }
+                KeyCode::Char('s') => {
+                    if app.response_bytes.is_some() {
+                        let file_name = suggested_file_name(&app.url, app.response_content_type());
+                        app.open_prompt(PromptKind::SaveResponse, file_name);
+                    }
+                    return false;
+                }
+                KeyCode::Char(']') => {
+                    app.response_tab = app.response_tab.next();
+                    return false;
+                }
+                KeyCode::Char('[') => {
+                    app.response_tab = app.response_tab.previous();
+                    return false;
+                }
+                KeyCode::Char('f') => {
+                    app.response_filter_cursor = app.response_filter.chars().count();
+                    app.input_mode = InputMode::Editing(Field::ResponseFilter);
+                    return false;
+                }
+                KeyCode::Char('/') => {
+                    app.response_search.cursor = app.response_search.query.chars().count();
+                    app.input_mode = InputMode::Editing(Field::ResponseSearch);
+                    return false;
+                }
+                KeyCode::Char('n') => {
+                    if let Some(line) = app.response_search.next_match().map(|m| m.line) {
+                        app.scroll_to_line(line);
+                    }
+                    return false;
+                }
+                KeyCode::Char('N') => {
+                    if let Some(line) = app.response_search.previous_match().map(|m| m.line) {
+                        app.scroll_to_line(line);
+                    }
+                    return false;
+                }
+                KeyCode::Esc if app.response_search.is_active() => {
+                    app.response_search.clear();
+                    return false;
+                }
+                _ => {}
+            }
+        }
+
+        // Body mode: 'g' switches GraphQL on and off. In GraphQL mode ↑/↓ pick
+        // the query or variables pane, 'o' sets the operation name, 'I' fetches
+        // the schema and 'S' opens the schema explorer.
+        if app.active_field == Field::RequestBody {
+            match key.code {
+                KeyCode::Char('g') => {
+                    app.toggle_graphql();
+                    return false;
+                }
+                KeyCode::Up if app.graphql.is_some() => {
+                    app.graphql_pane = GraphQlPane::Query;
+                    return false;
+                }
+                KeyCode::Down if app.graphql.is_some() => {
+                    app.graphql_pane = GraphQlPane::Variables;
+                    return false;
+                }
+                KeyCode::Char('o') if app.graphql.is_some() => {
+                    let current = app.graphql.as_ref().map(|g| g.operation_name.clone()).unwrap_or_default();
+                    app.open_prompt(PromptKind::OperationName, current);
+                    return false;
+                }
+                KeyCode::Char('I') if app.graphql.is_some() => {
+                    RequestHandler::introspect(app).await;
+                    return false;
+                }
+                KeyCode::Char('S') if app.graphql.is_some() => {
+                    if app.graphql_schema.is_some() {
+                        app.show_schema = true;
+                        app.schema_path.clear();
+                        app.schema_selected_index = 0;
+                    } else {
+                        app.status_message = Some("No schema yet, press 'I' to fetch it".to_string());
+                    }
+                    return false;
+                }
+                _ => {}
+            }
+        }
+
+        // Per-request options
+        if matches!(app.active_field, Field::Url | Field::SendButton) {
+            match key.code {
+                KeyCode::Char('r') => {
+                    let current = app.redirect_override_text();
+                    app.open_prompt(PromptKind::RedirectPolicy, current);
+                    return false;
+                }
+                KeyCode::Char('v') => {
+                    let current = app.request_options.http_version
+                        .map(|v| v.as_str().to_string())
+                        .unwrap_or_default();
+                    app.open_prompt(PromptKind::HttpVersion, current);
+                    return false;
+                }
+                KeyCode::Char('p') => {
+                    app.request_options.bypass_proxy = !app.request_options.bypass_proxy;
+                    app.status_message = Some(if app.request_options.bypass_proxy {
+                        "Proxy bypassed for this request".to_string()
+                    } else {
+                        "Using configured proxy".to_string()
+                    });
+                    return false;
+                }
+                _ => {}
+            }
+        }
+
         // Rest of the normal mode handling...
         match key.code {
             KeyCode::Tab => {
@@ -341,7 +857,7 @@ impl InputHandler {
                         app.input_mode = InputMode::Editing(app.active_field);
                         app.cursor_position = match app.active_field {
                             Field::Url => app.url.len(),
-                            Field::RequestBody => app.body.len(),
+                            Field::RequestBody => app.body_text().len(),
                             _ => 0
                         };
                     }
@@ -385,6 +901,7 @@ impl InputHandler {
                             NavItem::Collections => {
                                 app.show_collections = true;
                                 app.show_history = false;
+                                app.show_cookies = false;
                                 app.collections_focus = CollectionsFocus::List;
                                 app.collection_selected_index = 0;
                                 app.input_mode = InputMode::Normal;
@@ -393,20 +910,27 @@ impl InputHandler {
                             NavItem::History => {
                                 app.show_history = true;
                                 app.show_collections = false;
+                                app.show_cookies = false;
                                 app.history_selected_index = 0;
+                                app.refresh_history_view();
                                 app.input_mode = InputMode::Normal;
                                 app.active_field = Field::History;
                             }
+                            NavItem::Cookies => {
+                                app.show_cookies = true;
+                                app.show_collections = false;
+                                app.show_history = false;
+                                app.cookies_selected_index = 0;
+                                app.input_mode = InputMode::Normal;
+                                app.active_field = Field::Cookies;
+                            }
                             NavItem::Quit => return true,
                             _ => {}
                         }
                     }
                     Field::History => {
-                        if let Some(entry) = app.history.get_entries().get(app.history_selected_index) {
-                            app.url = entry.request.url.clone();
-                            app.method = HttpMethod::from_str(&entry.request.method).unwrap_or(HttpMethod::GET);
-                            app.headers = entry.request.headers.clone();
-                            app.body = entry.request.body.clone().unwrap_or_default();
+                        if let Some(entry) = app.selected_history_entry().cloned() {
+                            app.load_history_entry(&entry);
                             app.show_history = false;
                             app.active_field = Field::Url;
                         }
@@ -416,21 +940,16 @@ impl InputHandler {
                         if let Some(collection) = collections.values().nth(app.collection_selected_index) {
                             app.selected_collection = Some(collection.info.name.clone());
                             app.collection_view = CollectionView::Requests;
+                            app.collection_folder.clear();
                             app.request_selected_index = 0;
                         }
                     }
                     Field::Method => {
                         if app.show_method_selector {
-                            if let Some(method) = HttpMethod::all().get(app.selector_method_index) {
-                                app.method = *method;
-                                app.show_method_selector = false;
-                            }
+                            app.choose_selected_method();
                         } else {
                             app.show_method_selector = true;
-                            app.selector_method_index = HttpMethod::all()
-                                .iter()
-                                .position(|&m| m == app.method)
-                                .unwrap_or(0);
+                            app.selector_method_index = app.method_selector_index();
                         }
                     }
                     Field::SaveButton => {
@@ -454,11 +973,10 @@ impl InputHandler {
                     }
                     Field::Method => {
                         if app.show_method_selector {
-                            let methods = HttpMethod::all();
                             if app.selector_method_index > 0 {
                                 app.selector_method_index -= 1;
                             } else {
-                                app.selector_method_index = methods.len() - 1;
+                                app.selector_method_index = HttpMethod::all().len();
                             }
                         }
                     }
@@ -493,8 +1011,8 @@ impl InputHandler {
                     }
                     Field::Method => {
                         if app.show_method_selector {
-                            let methods = HttpMethod::all();
-                            app.selector_method_index = (app.selector_method_index + 1) % methods.len();
+                            let entries = HttpMethod::all().len() + 1;
+                            app.selector_method_index = (app.selector_method_index + 1) % entries;
                         }
                     }
                     Field::NavPanel => {
@@ -503,7 +1021,7 @@ impl InputHandler {
                         app.nav_selected = items[(current_idx + 1) % items.len()];
                     }
                     Field::History => {
-                        let len = app.history.get_entries().len();
+                        let len = app.history_view.len();
                         if len > 0 {
                             app.history_selected_index = (app.history_selected_index + 1) % len;
                         }
@@ -522,7 +1040,349 @@ impl InputHandler {
         }
     }
 
+    async fn handle_prompt(app: &mut App, key: KeyEvent) {
+        let byte_pos = |input: &str, cursor: usize| {
+            input.char_indices().nth(cursor).map(|(i, _)| i).unwrap_or(input.len())
+        };
+        match key.code {
+            KeyCode::Esc => app.close_prompt(),
+            KeyCode::Enter => {
+                let kind = app.prompt;
+                let input = app.prompt_input.trim().to_string();
+                app.close_prompt();
+                if let Some(kind) = kind {
+                    Self::submit_prompt(app, kind, &input).await;
+                }
+            }
+            KeyCode::Char(c) => {
+                let pos = byte_pos(&app.prompt_input, app.prompt_cursor);
+                app.prompt_input.insert(pos, c);
+                app.prompt_cursor += 1;
+            }
+            KeyCode::Backspace if app.prompt_cursor > 0 => {
+                app.prompt_cursor -= 1;
+                let pos = byte_pos(&app.prompt_input, app.prompt_cursor);
+                app.prompt_input.remove(pos);
+            }
+            KeyCode::Left => {
+                app.prompt_cursor = app.prompt_cursor.saturating_sub(1);
+            }
+            KeyCode::Right => {
+                app.prompt_cursor = (app.prompt_cursor + 1).min(app.prompt_input.chars().count());
+            }
+            KeyCode::Home => app.prompt_cursor = 0,
+            KeyCode::End => app.prompt_cursor = app.prompt_input.chars().count(),
+            _ => {}
+        }
+    }
+
+    // Scrolls a message log whose offset counts entries back from the newest.
+    // Returns false for keys that aren't scroll keys.
+    fn scroll_log(scroll: &mut usize, len: usize, code: KeyCode) -> bool {
+        let max_scroll = len.saturating_sub(1);
+        *scroll = match code {
+            KeyCode::Up => (*scroll + 1).min(max_scroll),
+            KeyCode::Down => scroll.saturating_sub(1),
+            KeyCode::PageUp => (*scroll + 20).min(max_scroll),
+            KeyCode::PageDown => scroll.saturating_sub(20),
+            KeyCode::Home => max_scroll,
+            KeyCode::End => 0,
+            _ => return false,
+        };
+        true
+    }
+
+    async fn submit_prompt(app: &mut App, kind: PromptKind, input: &str) {
+        match kind {
+            PromptKind::SaveResponse if input.is_empty() => {}
+            PromptKind::SaveResponse => {
+                app.status_message = Some(match app.save_response_body(input) {
+                    Ok(len) => format!("Saved {} bytes to {}", len, input),
+                    Err(e) => format!("Save failed: {}", e),
+                });
+            }
+            PromptKind::RedirectPolicy => {
+                app.status_message = Some(match app.set_redirect_override(input) {
+                    Ok(()) => {
+                        let (follow, max) = app.redirect_policy();
+                        if follow {
+                            format!("Following up to {} redirects", max)
+                        } else {
+                            "Not following redirects".to_string()
+                        }
+                    }
+                    Err(e) => format!("Invalid redirect setting: {}", e),
+                });
+            }
+            PromptKind::HttpVersion => {
+                app.status_message = Some(match app.set_http_version_override(input) {
+                    Ok(()) => format!("HTTP version: {}", app.http_version().as_str()),
+                    Err(e) => format!("Invalid HTTP version: {}", e),
+                });
+            }
+            PromptKind::CustomMethod => {
+                match HttpMethod::from_str(input) {
+                    Some(method) => app.method = method,
+                    None => app.status_message = Some(format!("Invalid method: {:?}", input)),
+                }
+            }
+            PromptKind::ProtoFiles => {
+                app.grpc_target.proto_files = input.split(',')
+                    .map(str::trim)
+                    .filter(|path| !path.is_empty())
+                    .map(String::from)
+                    .collect();
+                app.grpc_pool = None;
+                if app.grpc_target.proto_files.is_empty() {
+                    app.status_message = Some("Using server reflection".to_string());
+                    return;
+                }
+                match grpc::load_proto_files(&app.grpc_target.proto_files) {
+                    Ok(pool) => {
+                        app.set_grpc_pool(pool);
+                        app.open_grpc_methods();
+                    }
+                    Err(e) => app.status_message = Some(format!("Loading .proto files failed: {:#}", e)),
+                }
+            }
+            PromptKind::ImportCollection if input.is_empty() => {}
+            PromptKind::ImportCollection => {
+                app.status_message = Some(match app.import_collection(input) {
+                    Ok((name, count)) => format!("Imported {} requests into {}", count, name),
+                    Err(e) => format!("Import failed: {:#}", e),
+                });
+            }
+            PromptKind::ExportHar if input.is_empty() => {}
+            PromptKind::ExportHar => {
+                let entries = app.history_selection();
+                app.status_message = Some(match har::export_file(&user_path(input), &entries) {
+                    Ok(()) => {
+                        app.history_marked.clear();
+                        format!("Exported {} requests to {}", entries.len(), input)
+                    }
+                    Err(e) => format!("Export failed: {:#}", e),
+                });
+            }
+            PromptKind::ImportHar if input.is_empty() => {}
+            PromptKind::ImportHar => {
+                app.status_message = Some(match har::import_file(&user_path(input)) {
+                    Ok(entries) => {
+                        let count = entries.len();
+                        match app.history.import_entries(entries) {
+                            Ok(()) => {
+                                app.refresh_history_view();
+                                format!("Imported {} requests into history", count)
+                            }
+                            Err(e) => format!("Import failed: {:?}", e),
+                        }
+                    }
+                    Err(e) => format!("Import failed: {:#}", e),
+                });
+            }
+            PromptKind::RunCollection if input.is_empty() => {}
+            PromptKind::RunCollection => {
+                let Some(collection) = app.selected_collection.clone() else { return };
+                let entries = RequestHandler::run_collection(app, &collection).await;
+                app.status_message = Some(match har::export_file(&user_path(input), &entries) {
+                    Ok(()) => format!("Ran {} requests, saved to {}", entries.len(), input),
+                    Err(e) => format!("Export failed: {:#}", e),
+                });
+            }
+            PromptKind::CollectionVariable => {
+                let Some(collection) = app.selected_collection.clone() else { return };
+                let (name, value) = input.split_once('=').unwrap_or((input, ""));
+                let name = name.trim();
+                if name.is_empty() {
+                    return;
+                }
+                app.status_message = Some(match app.collection_manager.set_variable(&collection, name, value.trim()) {
+                    Ok(()) if value.trim().is_empty() => format!("Removed variable {}", name),
+                    Ok(()) => format!("{} = {}", name, value.trim()),
+                    Err(e) => format!("Setting variable failed: {}", e),
+                });
+            }
+            PromptKind::OperationName => {
+                if let Some(gql) = &mut app.graphql {
+                    gql.operation_name = input.trim().to_string();
+                }
+            }
+            PromptKind::EditCookie => {
+                if let Some(cookie) = app.selected_stored_cookie() {
+                    app.cookie_jar.set_value(&cookie, input.to_string());
+                }
+            }
+        }
+    }
+
+    fn handle_search_input(app: &mut App, key: KeyEvent) {
+        let search = &mut app.response_search;
+        let byte_pos = |query: &str, cursor: usize| {
+            query.char_indices().nth(cursor).map(|(i, _)| i).unwrap_or(query.len())
+        };
+        match key.code {
+            KeyCode::Enter => {
+                app.input_mode = InputMode::Normal;
+                return;
+            }
+            KeyCode::Esc => {
+                search.clear();
+                app.input_mode = InputMode::Normal;
+                return;
+            }
+            KeyCode::Char(c) => {
+                let pos = byte_pos(&search.query, search.cursor);
+                search.query.insert(pos, c);
+                search.cursor += 1;
+            }
+            KeyCode::Backspace if search.cursor > 0 => {
+                search.cursor -= 1;
+                let pos = byte_pos(&search.query, search.cursor);
+                search.query.remove(pos);
+            }
+            KeyCode::Left => {
+                search.cursor = search.cursor.saturating_sub(1);
+                return;
+            }
+            KeyCode::Right => {
+                search.cursor = (search.cursor + 1).min(search.query.chars().count());
+                return;
+            }
+            _ => return,
+        }
+        // Query changed, search again as you type
+        app.refresh_search();
+    }
+
+    fn handle_filter_input(app: &mut App, key: KeyEvent) {
+        let byte_pos = |filter: &str, cursor: usize| {
+            filter.char_indices().nth(cursor).map(|(i, _)| i).unwrap_or(filter.len())
+        };
+        match key.code {
+            KeyCode::Enter => {
+                app.input_mode = InputMode::Normal;
+                app.persist_response_filter();
+                return;
+            }
+            KeyCode::Esc => {
+                app.response_filter.clear();
+                app.response_filter_cursor = 0;
+                app.input_mode = InputMode::Normal;
+                app.persist_response_filter();
+            }
+            KeyCode::Char(c) => {
+                let pos = byte_pos(&app.response_filter, app.response_filter_cursor);
+                app.response_filter.insert(pos, c);
+                app.response_filter_cursor += 1;
+            }
+            KeyCode::Backspace if app.response_filter_cursor > 0 => {
+                app.response_filter_cursor -= 1;
+                let pos = byte_pos(&app.response_filter, app.response_filter_cursor);
+                app.response_filter.remove(pos);
+            }
+            KeyCode::Left => {
+                app.response_filter_cursor = app.response_filter_cursor.saturating_sub(1);
+                return;
+            }
+            KeyCode::Right => {
+                app.response_filter_cursor = (app.response_filter_cursor + 1)
+                    .min(app.response_filter.chars().count());
+                return;
+            }
+            _ => return,
+        }
+        // Filter changed, re-render as you type
+        app.refresh_filter();
+    }
+
+    fn handle_history_filter_input(app: &mut App, key: KeyEvent) {
+        let filter = &mut app.history_filter;
+        let byte_pos = |query: &str, cursor: usize| {
+            query.char_indices().nth(cursor).map(|(i, _)| i).unwrap_or(query.len())
+        };
+        match key.code {
+            KeyCode::Enter => {
+                app.input_mode = InputMode::Normal;
+                return;
+            }
+            KeyCode::Esc => {
+                filter.clear();
+                app.input_mode = InputMode::Normal;
+            }
+            KeyCode::Char(c) => {
+                let pos = byte_pos(&filter.query, filter.cursor);
+                filter.query.insert(pos, c);
+                filter.cursor += 1;
+            }
+            KeyCode::Backspace if filter.cursor > 0 => {
+                filter.cursor -= 1;
+                let pos = byte_pos(&filter.query, filter.cursor);
+                filter.query.remove(pos);
+            }
+            KeyCode::Left => {
+                filter.cursor = filter.cursor.saturating_sub(1);
+                return;
+            }
+            KeyCode::Right => {
+                filter.cursor = (filter.cursor + 1).min(filter.query.chars().count());
+                return;
+            }
+            _ => return,
+        }
+        // Filter changed, narrow the list as you type
+        app.history_filter.update();
+        app.history_selected_index = 0;
+        app.refresh_history_view();
+    }
+
     fn handle_editing_mode(app: &mut App, field: Field, key: KeyEvent) -> bool {
+        if field == Field::ResponseSearch {
+            Self::handle_search_input(app, key);
+            return false;
+        }
+        if field == Field::ResponseFilter {
+            Self::handle_filter_input(app, key);
+            return false;
+        }
+        if field == Field::HistoryFilter {
+            Self::handle_history_filter_input(app, key);
+            return false;
+        }
+
+        // GraphQL field completion: Tab offers the fields that fit the cursor
+        // position, inserting a single match right away
+        if field == Field::RequestBody && !app.completions.is_empty() {
+            match key.code {
+                KeyCode::Up => {
+                    app.completion_index = app.completion_index.saturating_sub(1);
+                    return false;
+                }
+                KeyCode::Down => {
+                    app.completion_index = (app.completion_index + 1).min(app.completions.len() - 1);
+                    return false;
+                }
+                KeyCode::Enter | KeyCode::Tab => {
+                    app.accept_completion();
+                    return false;
+                }
+                KeyCode::Esc => {
+                    app.completions.clear();
+                    return false;
+                }
+                _ => app.completions.clear(),
+            }
+        }
+        if field == Field::RequestBody && key.code == KeyCode::Tab && app.graphql_schema.is_some() {
+            app.refresh_completions();
+            match app.completions.len() {
+                0 => {}
+                1 => {
+                    app.accept_completion();
+                    return false;
+                }
+                _ => return false,
+            }
+        }
+
         // Handle selection with Shift + Arrow keys
         if field == Field::Url && key.modifiers.contains(KeyModifiers::SHIFT) {
             match key.code {
@@ -619,7 +1479,8 @@ impl InputHandler {
             KeyCode::Enter => {
                 match field {
                     Field::RequestBody => {
-                        app.body.insert(app.cursor_position, '\n');
+                        let cursor = app.cursor_position;
+                        app.body_text_mut().insert(cursor, '\n');
                         app.cursor_position += 1;
                         app.headers.insert(
                             "Content-Length".to_string(),
@@ -636,7 +1497,8 @@ impl InputHandler {
             }
             KeyCode::Tab => {
                 if field == Field::RequestBody {
-                    app.body.insert_str(app.cursor_position, "    ");
+                    let cursor = app.cursor_position;
+                    app.body_text_mut().insert_str(cursor, "    ");
                     app.cursor_position += 4;
                     false
                 } else {
@@ -651,7 +1513,8 @@ impl InputHandler {
                         app.cursor_position += 1;
                     }
                     Field::RequestBody => {
-                        app.body.insert(app.cursor_position, c);
+                        let cursor = app.cursor_position;
+                        app.body_text_mut().insert(cursor, c);
                         app.cursor_position += 1;
                     }
                     _ => {}
@@ -669,7 +1532,8 @@ impl InputHandler {
                     Field::RequestBody => {
                         if app.cursor_position > 0 {
                             app.cursor_position -= 1;
-                            app.body.remove(app.cursor_position);
+                            let cursor = app.cursor_position;
+                            app.body_text_mut().remove(cursor);
                         }
                     }
                     _ => {}
@@ -679,7 +1543,7 @@ impl InputHandler {
             KeyCode::Right => {
                 let max_pos = match field {
                     Field::Url => app.url.len(),
-                    Field::RequestBody => app.body.len(),
+                    Field::RequestBody => app.body_text().len(),
                     _ => app.cursor_position,
                 };
                 app.cursor_position = (app.cursor_position + 1).min(max_pos);
@@ -0,0 +1,299 @@
+use std::collections::HashSet;
+use serde_json::Value;
+use crate::utils::format::TokenKind;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum RowKind {
+    Open,
+    Close,
+    Scalar,
+}
+
+// One visible line of the tree.
+#[derive(Debug, Clone)]
+pub struct JsonRow<'a> {
+    pub depth: usize,
+    // JSONPath of the node, e.g. `$.items[3].name`
+    pub path: String,
+    pub key: Option<String>,
+    pub value: &'a Value,
+    pub kind: RowKind,
+    pub collapsed: bool,
+    pub trailing_comma: bool,
+}
+
+// Expand/collapse and selection state for the JSON tree view. Collapsed nodes
+// are tracked by path so the state survives re-renders of the same response.
+#[derive(Debug, Clone, Default)]
+pub struct JsonTree {
+    pub collapsed: HashSet<String>,
+    pub selected: usize,
+}
+
+impl JsonTree {
+    pub fn reset(&mut self) {
+        self.collapsed.clear();
+        self.selected = 0;
+    }
+
+    pub fn rows<'a>(&self, root: &'a Value) -> Vec<JsonRow<'a>> {
+        let mut rows = Vec::new();
+        self.push_rows(root, None, "$".to_string(), 0, false, &mut rows);
+        rows
+    }
+
+    fn push_rows<'a>(
+        &self,
+        value: &'a Value,
+        key: Option<String>,
+        path: String,
+        depth: usize,
+        trailing_comma: bool,
+        rows: &mut Vec<JsonRow<'a>>,
+    ) {
+        let children: Vec<(String, String, &Value)> = match value {
+            Value::Object(map) => map
+                .iter()
+                .map(|(k, v)| (serde_json::to_string(k).unwrap_or_default(), child_path(&path, k), v))
+                .collect(),
+            Value::Array(items) => items
+                .iter()
+                .enumerate()
+                .map(|(i, v)| (String::new(), format!("{}[{}]", path, i), v))
+                .collect(),
+            _ => {
+                rows.push(JsonRow {
+                    depth,
+                    path,
+                    key,
+                    value,
+                    kind: RowKind::Scalar,
+                    collapsed: false,
+                    trailing_comma,
+                });
+                return;
+            }
+        };
+
+        let collapsed = self.collapsed.contains(&path);
+        rows.push(JsonRow {
+            depth,
+            path: path.clone(),
+            key,
+            value,
+            kind: RowKind::Open,
+            collapsed,
+            trailing_comma: collapsed && trailing_comma,
+        });
+        if collapsed {
+            return;
+        }
+
+        let count = children.len();
+        let is_object = value.is_object();
+        for (i, (child_key, child_path, child)) in children.into_iter().enumerate() {
+            let child_key = is_object.then_some(child_key);
+            self.push_rows(child, child_key, child_path, depth + 1, i + 1 < count, rows);
+        }
+        rows.push(JsonRow {
+            depth,
+            path,
+            key: None,
+            value,
+            kind: RowKind::Close,
+            collapsed: false,
+            trailing_comma,
+        });
+    }
+
+    pub fn toggle(&mut self, path: &str) {
+        if !self.collapsed.remove(path) {
+            self.collapsed.insert(path.to_string());
+        }
+    }
+
+    pub fn expand_all(&mut self) {
+        self.collapsed.clear();
+    }
+
+    // Collapses every container below the root.
+    pub fn collapse_all(&mut self, root: &Value) {
+        fn collect(value: &Value, path: String, out: &mut HashSet<String>) {
+            match value {
+                Value::Object(map) => {
+                    for (k, v) in map {
+                        let p = child_path(&path, k);
+                        if v.is_object() || v.is_array() {
+                            out.insert(p.clone());
+                        }
+                        collect(v, p, out);
+                    }
+                }
+                Value::Array(items) => {
+                    for (i, v) in items.iter().enumerate() {
+                        let p = format!("{}[{}]", path, i);
+                        if v.is_object() || v.is_array() {
+                            out.insert(p.clone());
+                        }
+                        collect(v, p, out);
+                    }
+                }
+                _ => {}
+            }
+        }
+        self.collapsed.clear();
+        collect(root, "$".to_string(), &mut self.collapsed);
+        self.selected = 0;
+    }
+}
+
+fn child_path(parent: &str, key: &str) -> String {
+    let is_identifier = !key.is_empty()
+        && !key.starts_with(|c: char| c.is_ascii_digit())
+        && key.chars().all(|c| c.is_alphanumeric() || c == '_');
+    if is_identifier {
+        format!("{}.{}", parent, key)
+    } else {
+        format!("{}['{}']", parent, key.replace('\\', "\\\\").replace('\'', "\\'"))
+    }
+}
+
+impl JsonRow<'_> {
+    // The row as styled text segments. Concatenated, they form the line that
+    // search matches against, so rendering and search always agree.
+    pub fn segments(&self) -> Vec<(TokenKind, String)> {
+        let mut segments = vec![(TokenKind::Plain, "  ".repeat(self.depth))];
+
+        let marker = match (self.kind, self.collapsed) {
+            (RowKind::Open, true) => "▸ ",
+            (RowKind::Open, false) => "▾ ",
+            _ => "  ",
+        };
+        segments.push((TokenKind::Marker, marker.to_string()));
+
+        if let Some(key) = &self.key {
+            segments.push((TokenKind::Key, key.clone()));
+            segments.push((TokenKind::Punctuation, ": ".to_string()));
+        }
+
+        match self.kind {
+            RowKind::Scalar => segments.push(scalar_segment(self.value)),
+            RowKind::Open => {
+                let (open, close) = brackets(self.value);
+                if self.collapsed {
+                    segments.push((TokenKind::Punctuation, format!("{}…{}", open, close)));
+                    segments.push((TokenKind::Summary, format!(" {}", child_summary(self.value))));
+                } else {
+                    segments.push((TokenKind::Punctuation, open.to_string()));
+                }
+            }
+            RowKind::Close => segments.push((TokenKind::Punctuation, brackets(self.value).1.to_string())),
+        }
+
+        if self.trailing_comma {
+            segments.push((TokenKind::Punctuation, ",".to_string()));
+        }
+        segments
+    }
+
+    pub fn text(&self) -> String {
+        self.segments().into_iter().map(|(_, s)| s).collect()
+    }
+}
+
+fn brackets(value: &Value) -> (char, char) {
+    if value.is_array() { ('[', ']') } else { ('{', '}') }
+}
+
+fn child_summary(value: &Value) -> String {
+    match value {
+        Value::Object(map) if map.len() == 1 => "1 key".to_string(),
+        Value::Object(map) => format!("{} keys", map.len()),
+        Value::Array(items) if items.len() == 1 => "1 item".to_string(),
+        Value::Array(items) => format!("{} items", items.len()),
+        _ => String::new(),
+    }
+}
+
+fn scalar_segment(value: &Value) -> (TokenKind, String) {
+    let kind = match value {
+        Value::String(_) => TokenKind::String,
+        Value::Number(_) => TokenKind::Number,
+        Value::Bool(_) => TokenKind::Bool,
+        _ => TokenKind::Null,
+    };
+    // serde_json escapes quotes and control characters properly
+    (kind, serde_json::to_string(value).unwrap_or_default())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn texts(tree: &JsonTree, root: &Value) -> Vec<String> {
+        tree.rows(root).iter().map(JsonRow::text).collect()
+    }
+
+    #[test]
+    fn rows_of_an_expanded_tree() {
+        let root = json!({"a": 1, "b": [true, null]});
+        assert_eq!(texts(&JsonTree::default(), &root), vec![
+            "▾ {",
+            "    \"a\": 1,",
+            "  ▾ \"b\": [",
+            "      true,",
+            "      null",
+            "    ]",
+            "  }",
+        ]);
+    }
+
+    #[test]
+    fn paths_of_keys_and_items() {
+        let root = json!({"items": [{"name": "x"}], "odd key": 1, "2nd": 2, "it's": 3});
+        let paths: Vec<String> = JsonTree::default().rows(&root).into_iter()
+            .filter(|row| row.kind != RowKind::Close)
+            .map(|row| row.path)
+            .collect();
+        assert_eq!(paths, vec![
+            "$", "$['2nd']", "$['it\\'s']", "$.items", "$.items[0]", "$.items[0].name", "$['odd key']",
+        ]);
+    }
+
+    #[test]
+    fn collapsing_hides_children() {
+        let root = json!({"a": {"x": 1, "y": 2}, "b": [1]});
+        let mut tree = JsonTree::default();
+        tree.toggle("$.a");
+        assert_eq!(texts(&tree, &root), vec![
+            "▾ {",
+            "  ▸ \"a\": {…} 2 keys,",
+            "  ▾ \"b\": [",
+            "      1",
+            "    ]",
+            "  }",
+        ]);
+        tree.toggle("$.a");
+        assert_eq!(texts(&tree, &root).len(), 9);
+    }
+
+    #[test]
+    fn collapse_and_expand_all() {
+        let root = json!({"a": {"x": [1]}, "b": [[1], 2], "c": 3});
+        let mut tree = JsonTree { selected: 4, ..Default::default() };
+        tree.collapse_all(&root);
+        assert_eq!(tree.selected, 0);
+        let expected: HashSet<String> = ["$.a", "$.a.x", "$.b", "$.b[0]"].into_iter().map(String::from).collect();
+        assert_eq!(tree.collapsed, expected);
+        assert_eq!(texts(&tree, &root), vec![
+            "▾ {",
+            "  ▸ \"a\": {…} 1 key,",
+            "  ▸ \"b\": […] 2 items,",
+            "    \"c\": 3",
+            "  }",
+        ]);
+        tree.expand_all();
+        assert!(tree.collapsed.is_empty());
+    }
+}
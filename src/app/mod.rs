@@ -1,11 +1,24 @@
 pub mod state;
 pub mod input;
 pub mod actions;
+pub mod search;
+pub mod history_filter;
+pub mod json_tree;
+pub mod background_log;
+pub mod websocket;
+pub mod event_stream;
+pub mod grpc;
 
 pub use state::{
     App, Field, InputMode, NavItem, HttpMethod,
     HeaderEditState, CollectionView,
-    CollectionsFocus
+    CollectionsFocus, PromptKind, ResponseTab, GraphQlPane, Store
 };
 pub use input::InputHandler;
-pub use actions::RequestHandler; 
\ No newline at end of file
+pub use actions::RequestHandler;
+pub use search::ResponseSearch;
+pub use history_filter::HistoryFilter;
+pub use json_tree::JsonTree;
+pub use websocket::WebSocketSession;
+pub use event_stream::EventStream;
+pub use grpc::GrpcStream; 
\ No newline at end of file
@@ -0,0 +1,177 @@
+use regex::{Regex, RegexBuilder};
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct SearchMatch {
+    pub line: usize,
+    // Byte offsets within the line
+    pub start: usize,
+    pub end: usize,
+}
+
+// Incremental search over the rendered response body lines.
+#[derive(Debug, Clone, Default)]
+pub struct ResponseSearch {
+    pub query: String,
+    pub cursor: usize,
+    pub matches: Vec<SearchMatch>,
+    pub current: usize,
+    pub error: Option<String>,
+}
+
+impl ResponseSearch {
+    pub fn is_active(&self) -> bool {
+        !self.query.is_empty()
+    }
+
+    pub fn clear(&mut self) {
+        *self = Self::default();
+    }
+
+    // The query is treated as a regex; lowercase queries match case-insensitively
+    // (vim's smartcase). An invalid regex falls back to a literal search.
+    fn compile(&mut self) -> Option<Regex> {
+        let case_insensitive = !self.query.chars().any(|c| c.is_uppercase());
+        match RegexBuilder::new(&self.query).case_insensitive(case_insensitive).build() {
+            Ok(re) => {
+                self.error = None;
+                Some(re)
+            }
+            Err(_) => {
+                self.error = Some("invalid regex, matching literally".to_string());
+                RegexBuilder::new(&regex::escape(&self.query))
+                    .case_insensitive(case_insensitive)
+                    .build()
+                    .ok()
+            }
+        }
+    }
+
+    pub fn update(&mut self, lines: &[String]) {
+        self.matches.clear();
+        self.current = 0;
+        if self.query.is_empty() {
+            self.error = None;
+            return;
+        }
+        let re = match self.compile() {
+            Some(re) => re,
+            None => return,
+        };
+        for (line, text) in lines.iter().enumerate() {
+            for m in re.find_iter(text) {
+                // Skip empty matches (e.g. "a*"), they can't be highlighted
+                if m.start() < m.end() {
+                    self.matches.push(SearchMatch { line, start: m.start(), end: m.end() });
+                }
+            }
+        }
+    }
+
+    // Moves to the first match at or after `line`, wrapping around.
+    pub fn seek_from(&mut self, line: usize) -> Option<&SearchMatch> {
+        if self.matches.is_empty() {
+            return None;
+        }
+        self.current = self.matches.iter().position(|m| m.line >= line).unwrap_or(0);
+        self.matches.get(self.current)
+    }
+
+    pub fn next_match(&mut self) -> Option<&SearchMatch> {
+        if self.matches.is_empty() {
+            return None;
+        }
+        self.current = (self.current + 1) % self.matches.len();
+        self.matches.get(self.current)
+    }
+
+    pub fn previous_match(&mut self) -> Option<&SearchMatch> {
+        if self.matches.is_empty() {
+            return None;
+        }
+        self.current = self.current.checked_sub(1).unwrap_or(self.matches.len() - 1);
+        self.matches.get(self.current)
+    }
+
+    // Matches on the given line, with a flag marking the current one.
+    pub fn matches_on_line(&self, line: usize) -> Vec<(usize, usize, bool)> {
+        let first = self.matches.partition_point(|m| m.line < line);
+        self.matches[first..]
+            .iter()
+            .enumerate()
+            .take_while(|(_, m)| m.line == line)
+            .map(|(i, m)| (m.start, m.end, first + i == self.current))
+            .collect()
+    }
+
+    // "match 3/17", "no matches" or an empty string when no search is active.
+    pub fn summary(&self) -> String {
+        if !self.is_active() {
+            String::new()
+        } else if self.matches.is_empty() {
+            "no matches".to_string()
+        } else {
+            format!("match {}/{}", self.current + 1, self.matches.len())
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn search(query: &str, lines: &[&str]) -> ResponseSearch {
+        let lines: Vec<String> = lines.iter().map(|line| line.to_string()).collect();
+        let mut search = ResponseSearch { query: query.to_string(), ..Default::default() };
+        search.update(&lines);
+        search
+    }
+
+    fn spans(search: &ResponseSearch) -> Vec<(usize, usize, usize)> {
+        search.matches.iter().map(|m| (m.line, m.start, m.end)).collect()
+    }
+
+    #[test]
+    fn lowercase_queries_ignore_case() {
+        let search = search("name", &["\"Name\": 1", "\"NAME\": 2", "none"]);
+        assert_eq!(spans(&search), vec![(0, 1, 5), (1, 1, 5)]);
+    }
+
+    #[test]
+    fn uppercase_in_the_query_matches_case() {
+        let search = search("Name", &["\"Name\": 1", "\"name\": 2"]);
+        assert_eq!(spans(&search), vec![(0, 1, 5)]);
+    }
+
+    #[test]
+    fn several_matches_on_one_line() {
+        let mut search = search("id", &["{\"id\": 1, \"parent_id\": 2}", "", "id"]);
+        assert_eq!(spans(&search), vec![(0, 2, 4), (0, 18, 20), (2, 0, 2)]);
+        assert_eq!(search.matches_on_line(0), vec![(2, 4, true), (18, 20, false)]);
+        search.next_match();
+        assert_eq!(search.matches_on_line(0), vec![(2, 4, false), (18, 20, true)]);
+        assert!(search.matches_on_line(1).is_empty());
+        assert_eq!(search.summary(), "match 2/3");
+    }
+
+    #[test]
+    fn moving_between_matches_wraps() {
+        let mut search = search("a", &["a", "b", "a a"]);
+        assert_eq!(search.seek_from(1).map(|m| m.line), Some(2));
+        assert_eq!(search.current, 1);
+        search.next_match();
+        search.next_match();
+        assert_eq!(search.current, 0);
+        search.previous_match();
+        assert_eq!(search.current, 2);
+    }
+
+    #[test]
+    fn invalid_regex_matches_literally() {
+        let literal = search("a(b", &["xa(by"]);
+        assert!(literal.error.is_some());
+        assert_eq!(spans(&literal), vec![(0, 1, 4)]);
+        // Empty matches aren't kept
+        assert!(search("x*", &["abc"]).matches.is_empty());
+        assert_eq!(search("zzz", &["abc"]).summary(), "no matches");
+    }
+}
@@ -1,9 +1,27 @@
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
 use ratatui::style::Color;
 use crossterm::event::KeyEvent;
 use log::debug;
-use crate::data::{AppConfig, History, CollectionManager};
-use crate::models::{ResponseMetadata, CollectionItem};
+use crate::data::{AppConfig, ConfigError, History, CollectionManager, CookieJar, Workspace};
+use crate::data::{collections, cookies, history, storage::{self, DamagedFile}};
+use crate::data::history::HistoryEntry;
+use crate::models::{ResponseMetadata, CollectionItem, SavedRequest, SavedWebSocket, Cookie, Timings};
+use crate::models::collection::{next_request_id, GraphQlBody, GrpcTarget, HttpVersion, OpenApiLink, Request, RequestOptions};
+use crate::utils::graphql::{self, GraphQlSchema};
+use crate::utils::jsonpath;
+use crate::utils::openapi::{self, ContractCheck};
+use crate::utils::har;
+use crate::utils::http_file;
+use crate::utils::format::wrap_points;
+use crate::utils::http::{format_response_body, hex_dump};
+use super::search::ResponseSearch;
+use super::history_filter::HistoryFilter;
+use super::json_tree::JsonTree;
+use super::websocket::WebSocketSession;
+use super::event_stream::EventStream;
+use super::grpc::{self, GrpcStream};
 
 #[derive(Debug, Clone, Copy, PartialEq)]
 pub enum InputMode {
@@ -19,9 +37,14 @@ pub enum Field {
     Headers,        // 4. Headers box
     RequestBody,    // 5. Request body box
     Method,         // 6. Method box
-    NavPanel,       // 7. Left nav
+    ResponseBody,   // 7. Response body
+    NavPanel,       // 8. Left nav
     Collections,
     History,
+    Cookies,
+    ResponseSearch,
+    ResponseFilter,
+    HistoryFilter,
 }
 
 impl Field {
@@ -32,10 +55,15 @@ impl Field {
             Field::SaveButton => Field::Headers,
             Field::Headers => Field::RequestBody,
             Field::RequestBody => Field::Method,
-            Field::Method => Field::NavPanel,
+            Field::Method => Field::ResponseBody,
+            Field::ResponseBody => Field::NavPanel,
             Field::NavPanel => Field::Url,
             Field::Collections => Field::Collections,
             Field::History => Field::History,
+            Field::Cookies => Field::Cookies,
+            Field::ResponseSearch => Field::ResponseSearch,
+            Field::ResponseFilter => Field::ResponseFilter,
+            Field::HistoryFilter => Field::HistoryFilter,
         }
     }
 
@@ -47,33 +75,69 @@ impl Field {
             Field::Headers => Field::SaveButton,
             Field::RequestBody => Field::Headers,
             Field::Method => Field::RequestBody,
-            Field::NavPanel => Field::Method,
+            Field::ResponseBody => Field::Method,
+            Field::NavPanel => Field::ResponseBody,
             Field::Collections => Field::Collections,
             Field::History => Field::History,
+            Field::Cookies => Field::Cookies,
+            Field::ResponseSearch => Field::ResponseSearch,
+            Field::ResponseFilter => Field::ResponseFilter,
+            Field::HistoryFilter => Field::HistoryFilter,
         }
     }
 }
 
-#[derive(Debug, Clone, Copy, PartialEq)]
+#[derive(Debug, Clone, PartialEq)]
 pub enum HttpMethod {
     GET,
     POST,
     PUT,
     DELETE,
     PATCH,
+    HEAD,
+    OPTIONS,
+    TRACE,
+    CONNECT,
+    // WebDAV (RFC 4918, RFC 3253, RFC 5323)
+    PROPFIND,
+    PROPPATCH,
+    MKCOL,
+    COPY,
+    MOVE,
+    LOCK,
+    UNLOCK,
+    REPORT,
+    SEARCH,
+    // Any other method token, sent as typed
+    Custom(String),
 }
 
 impl HttpMethod {
-    pub fn as_str(&self) -> &'static str {
+    pub fn as_str(&self) -> &str {
         match self {
             HttpMethod::GET => "GET",
             HttpMethod::POST => "POST",
             HttpMethod::PUT => "PUT",
             HttpMethod::DELETE => "DELETE",
             HttpMethod::PATCH => "PATCH",
+            HttpMethod::HEAD => "HEAD",
+            HttpMethod::OPTIONS => "OPTIONS",
+            HttpMethod::TRACE => "TRACE",
+            HttpMethod::CONNECT => "CONNECT",
+            HttpMethod::PROPFIND => "PROPFIND",
+            HttpMethod::PROPPATCH => "PROPPATCH",
+            HttpMethod::MKCOL => "MKCOL",
+            HttpMethod::COPY => "COPY",
+            HttpMethod::MOVE => "MOVE",
+            HttpMethod::LOCK => "LOCK",
+            HttpMethod::UNLOCK => "UNLOCK",
+            HttpMethod::REPORT => "REPORT",
+            HttpMethod::SEARCH => "SEARCH",
+            HttpMethod::Custom(method) => method,
         }
     }
 
+    // The methods offered in the selector, which adds a "custom…" entry after them
     pub fn all() -> Vec<HttpMethod> {
         vec![
             HttpMethod::GET,
@@ -81,18 +145,36 @@ impl HttpMethod {
             HttpMethod::PUT,
             HttpMethod::DELETE,
             HttpMethod::PATCH,
+            HttpMethod::HEAD,
+            HttpMethod::OPTIONS,
+            HttpMethod::TRACE,
+            HttpMethod::CONNECT,
+            HttpMethod::PROPFIND,
+            HttpMethod::PROPPATCH,
+            HttpMethod::MKCOL,
+            HttpMethod::COPY,
+            HttpMethod::MOVE,
+            HttpMethod::LOCK,
+            HttpMethod::UNLOCK,
+            HttpMethod::REPORT,
+            HttpMethod::SEARCH,
         ]
     }
 
+    // Known methods map to their variant and any other valid token to
+    // `Custom`. Methods are case-sensitive, so `get` is a custom method.
     pub fn from_str(s: &str) -> Option<Self> {
-        match s {
-            "GET" => Some(HttpMethod::GET),
-            "POST" => Some(HttpMethod::POST),
-            "PUT" => Some(HttpMethod::PUT),
-            "DELETE" => Some(HttpMethod::DELETE),
-            "PATCH" => Some(HttpMethod::PATCH),
-            _ => None,
+        let s = s.trim();
+        if let Some(method) = Self::all().into_iter().find(|m| m.as_str() == s) {
+            return Some(method);
         }
+        Self::is_token(s).then(|| HttpMethod::Custom(s.to_string()))
+    }
+
+    // RFC 9110 token: one or more visible characters other than delimiters
+    pub fn is_token(s: &str) -> bool {
+        !s.is_empty()
+            && s.bytes().all(|b| b.is_ascii_alphanumeric() || b"!#$%&'*+-.^_`|~".contains(&b))
     }
 
     pub fn color(&self) -> Color {
@@ -102,15 +184,26 @@ impl HttpMethod {
             HttpMethod::PUT => Color::Magenta,
             HttpMethod::PATCH => Color::Magenta,
             HttpMethod::DELETE => Color::Yellow,
+            HttpMethod::HEAD | HttpMethod::OPTIONS => Color::Blue,
+            HttpMethod::TRACE | HttpMethod::CONNECT => Color::Gray,
+            HttpMethod::Custom(_) => Color::White,
+            _ => Color::LightBlue,
         }
     }
 }
 
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum GraphQlPane {
+    Query,
+    Variables,
+}
+
 #[derive(Debug, Clone, Copy, PartialEq)]
 pub enum NavItem {
     Collections,
     Environments,
     History,
+    Cookies,
     Quit,
 }
 
@@ -120,6 +213,7 @@ impl NavItem {
             NavItem::Collections,
             NavItem::Environments,
             NavItem::History,
+            NavItem::Cookies,
             NavItem::Quit,
         ]
     }
@@ -129,6 +223,7 @@ impl NavItem {
             NavItem::Collections => "Collections",
             NavItem::Environments => "Environments",
             NavItem::History => "History",
+            NavItem::Cookies => "Cookies",
             NavItem::Quit => "Quit",
         }
     }
@@ -153,6 +248,88 @@ pub enum CollectionsFocus {
     NewButton,
 }
 
+// Tabs of the panel next to the response body
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ResponseTab {
+    Headers,
+    Cookies,
+    Redirects,
+    Timing,
+    Contract,
+}
+
+impl ResponseTab {
+    pub fn all() -> Vec<ResponseTab> {
+        vec![ResponseTab::Headers, ResponseTab::Cookies, ResponseTab::Redirects, ResponseTab::Timing, ResponseTab::Contract]
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ResponseTab::Headers => "Headers",
+            ResponseTab::Cookies => "Cookies",
+            ResponseTab::Redirects => "Redirects",
+            ResponseTab::Timing => "Timing",
+            ResponseTab::Contract => "Contract",
+        }
+    }
+
+    pub fn next(&self) -> Self {
+        let tabs = Self::all();
+        let idx = tabs.iter().position(|t| t == self).unwrap_or(0);
+        tabs[(idx + 1) % tabs.len()]
+    }
+
+    pub fn previous(&self) -> Self {
+        let tabs = Self::all();
+        let idx = tabs.iter().position(|t| t == self).unwrap_or(0);
+        tabs[(idx + tabs.len() - 1) % tabs.len()]
+    }
+}
+
+// Files raquet keeps its state in
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Store {
+    Config,
+    Collections,
+    History,
+    Cookies,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum PromptKind {
+    SaveResponse,
+    EditCookie,
+    RedirectPolicy,
+    HttpVersion,
+    CustomMethod,
+    OperationName,
+    ProtoFiles,
+    ImportCollection,
+    CollectionVariable,
+    ExportHar,
+    ImportHar,
+    RunCollection,
+}
+
+impl PromptKind {
+    pub fn title(&self) -> &'static str {
+        match self {
+            PromptKind::SaveResponse => "Save response body to",
+            PromptKind::EditCookie => "Cookie value",
+            PromptKind::RedirectPolicy => "Redirects: on, off or max hops (empty = default)",
+            PromptKind::HttpVersion => "HTTP version: auto, 1.1, 2 or h2c (empty = default)",
+            PromptKind::CustomMethod => "Custom method",
+            PromptKind::OperationName => "GraphQL operation name (empty = none)",
+            PromptKind::ProtoFiles => ".proto files, comma-separated (empty = server reflection)",
+            PromptKind::ImportCollection => "File to import: OpenAPI 3 / Swagger 2 (YAML or JSON), HAR, or .http / .rest",
+            PromptKind::CollectionVariable => "Collection variable: name=value (empty value removes it)",
+            PromptKind::ExportHar => "Export as HAR to",
+            PromptKind::ImportHar => "HAR file to import into history",
+            PromptKind::RunCollection => "Run every request and save the results as HAR to",
+        }
+    }
+}
+
 #[derive(Clone)]
 pub struct App {
     // Core state
@@ -160,18 +337,42 @@ pub struct App {
     pub method: HttpMethod,
     pub headers: HashMap<String, String>,
     pub body: String,
+    pub request_options: RequestOptions,
+    // OpenAPI operation of the loaded request, for checking its responses
+    pub openapi_link: Option<OpenApiLink>,
     pub input_mode: InputMode,
     pub active_field: Field,
     pub cursor_position: usize,
 
     // Response state
     pub response: Option<String>,
+    pub response_bytes: Option<Vec<u8>>,
     pub response_metadata: Option<ResponseMetadata>,
-    pub response_scroll: u16,
+    pub response_scroll: usize,
+    pub response_search: ResponseSearch,
+    pub response_view_height: usize,
+    pub response_view_width: usize,
+    // Body lines as displayed and, when wrapped, the row each one starts on
+    response_lines: Vec<String>,
+    response_rows: Vec<usize>,
+    pub response_filter: String,
+    pub response_filter_cursor: usize,
+    pub response_filter_error: Option<String>,
+    pub response_json: Option<serde_json::Value>,
+    pub json_tree: JsonTree,
+    pub response_tab: ResponseTab,
+    // Last response checked against `openapi_link`, or why it couldn't be
+    pub contract_check: Option<Result<ContractCheck, String>>,
 
     // UI state
     pub show_history: bool,
+    // Entries marked for export, by id
+    pub history_marked: HashSet<u64>,
+    // Position in `history_view`
     pub history_selected_index: usize,
+    pub history_filter: HistoryFilter,
+    // Indices of the entries the filter lets through, newest first
+    pub history_view: Vec<usize>,
     pub show_method_selector: bool,
     pub selector_method_index: usize,
     pub nav_selected: NavItem,
@@ -186,6 +387,9 @@ pub struct App {
     pub config: AppConfig,
     pub history: History,
     pub collection_manager: CollectionManager,
+    // Project `.raquet/` found from the current directory
+    pub workspace: Option<Workspace>,
+    pub cookie_jar: Arc<CookieJar>,
 
     // Collection state
     pub show_collections: bool,
@@ -195,6 +399,10 @@ pub struct App {
     pub collection_view: CollectionView,
     pub selected_collection: Option<String>,
     pub selected_request: Option<String>,
+    // `SavedRequest::id` of the loaded request; its name may not be unique
+    pub selected_request_id: Option<u64>,
+    // Folder names from the top of the selected collection to the open folder
+    pub collection_folder: Vec<String>,
     pub collection_selected_index: usize,
     pub request_selected_index: usize,
     pub header_key_cursor: usize,
@@ -203,28 +411,117 @@ pub struct App {
     pub save_dialog_visible: bool,
     pub save_dialog_selected_index: usize,
 
+    // Cookie manager state
+    pub show_cookies: bool,
+    pub cookies_selected_index: usize,
+
+    // Connection opened for a ws:// or wss:// URL
+    pub websocket: Option<WebSocketSession>,
+    // Lines scrolled back from the newest message in the log
+    pub websocket_scroll: usize,
+
+    // GraphQL body mode: the body is built from a query and variables, edited
+    // in two panes of the body editor
+    pub graphql: Option<GraphQlBody>,
+    pub graphql_pane: GraphQlPane,
+    pub graphql_schema: Option<GraphQlSchema>,
+    // Field completions offered while editing the query
+    pub completions: Vec<String>,
+    pub completion_index: usize,
+    // Schema explorer: the types followed so far, empty for the type list
+    pub show_schema: bool,
+    pub schema_path: Vec<String>,
+    pub schema_selected_index: usize,
+
+    // Server-Sent Events response still being read
+    pub event_stream: Option<EventStream>,
+    pub event_stream_scroll: usize,
+
+    // gRPC mode (grpc:// and grpcs:// URLs): the method to call and the
+    // service definitions loaded from .proto files or server reflection
+    pub grpc_target: GrpcTarget,
+    pub grpc_pool: Option<prost_reflect::DescriptorPool>,
+    // Server the definitions were loaded for
+    pub grpc_pool_server: String,
+    pub show_grpc_methods: bool,
+    pub grpc_method_index: usize,
+    // Server-streaming call whose messages are still arriving
+    pub grpc_stream: Option<GrpcStream>,
+    pub grpc_stream_scroll: usize,
+
     pub selection_start: Option<usize>,
+
+    // Single-line text prompt (file paths etc.)
+    pub prompt: Option<PromptKind>,
+    pub prompt_input: String,
+    pub prompt_cursor: usize,
+    pub status_message: Option<String>,
+    // Files that couldn't be read at startup, each waiting for the user to
+    // restore a backup, set it aside or leave it
+    pub damaged_files: Vec<(Store, DamagedFile)>,
 }
 
 impl App {
     pub fn new() -> Self {
-        let config = AppConfig::load().unwrap_or_default();
+        let workspace = Workspace::discover();
+        let mut damaged_files = Vec::new();
+        let config = match AppConfig::load(workspace.as_ref()) {
+            Ok(config) => config,
+            Err(ConfigError::Damaged(file)) => {
+                damaged_files.push((Store::Config, file));
+                AppConfig::default()
+            }
+            Err(_) => AppConfig::default(),
+        };
         debug!("Loaded config with headers: {:?}", config.app.default_headers);
-        
+        let mut collection_manager = match config.app.collections_dir.trim() {
+            "" => CollectionManager::new(),
+            dir => CollectionManager::in_directory(user_path(dir)),
+        };
+        if let Some(workspace) = &workspace {
+            collection_manager = collection_manager.with_workspace(workspace.clone());
+        }
+        let history = History::new(config.app.history_size).unwrap_or_else(|e| {
+            debug!("History won't be saved: {:?}", e);
+            History::in_memory(config.app.history_size)
+        });
+        damaged_files.extend(collection_manager.damaged().map(|file| (Store::Collections, file.clone())));
+        damaged_files.extend(history.damaged().map(|file| (Store::History, file.clone())));
+        let cookie_jar = Arc::new(CookieJar::new());
+        damaged_files.extend(cookie_jar.damaged().map(|file| (Store::Cookies, file)));
+
         let mut app = Self {
             config,
             url: String::new(),
             method: HttpMethod::GET,
             headers: HashMap::new(),
             body: String::new(),
+            request_options: RequestOptions::default(),
+            openapi_link: None,
             input_mode: InputMode::Normal,
             active_field: Field::Url,
             cursor_position: 0,
             response: None,
+            response_bytes: None,
             response_metadata: None,
             response_scroll: 0,
+            response_search: ResponseSearch::default(),
+            response_view_height: 0,
+            response_view_width: 0,
+            response_lines: Vec::new(),
+            response_rows: Vec::new(),
+            response_filter: String::new(),
+            response_filter_cursor: 0,
+            response_filter_error: None,
+            response_json: None,
+            json_tree: JsonTree::default(),
+            response_tab: ResponseTab::Headers,
+            contract_check: None,
             show_history: false,
             history_selected_index: 0,
+            history_marked: HashSet::new(),
+            history_filter: HistoryFilter::default(),
+            history_view: Vec::new(),
             show_method_selector: false,
             selector_method_index: 0,
             nav_selected: NavItem::Collections,
@@ -234,8 +531,10 @@ impl App {
             header_edit_value: String::new(),
             header_enabled: HashMap::new(),
             headers_scroll: 0,
-            history: History::new(100).unwrap(),
-            collection_manager: CollectionManager::new(),
+            history,
+            collection_manager,
+            workspace,
+            cookie_jar,
             show_collections: false,
             collections_focus: CollectionsFocus::List,
             show_collection_selector: false,
@@ -243,13 +542,41 @@ impl App {
             collection_view: CollectionView::List,
             selected_collection: None,
             selected_request: None,
+            selected_request_id: None,
+            collection_folder: Vec::new(),
             collection_selected_index: 0,
             request_selected_index: 0,
             header_key_cursor: 0,
             header_value_cursor: 0,
             save_dialog_visible: false,
             save_dialog_selected_index: 0,
+            show_cookies: false,
+            cookies_selected_index: 0,
+            websocket: None,
+            websocket_scroll: 0,
+            graphql: None,
+            graphql_pane: GraphQlPane::Query,
+            graphql_schema: None,
+            completions: Vec::new(),
+            completion_index: 0,
+            show_schema: false,
+            schema_path: Vec::new(),
+            schema_selected_index: 0,
+            event_stream: None,
+            event_stream_scroll: 0,
+            grpc_target: GrpcTarget::default(),
+            grpc_pool: None,
+            grpc_pool_server: String::new(),
+            show_grpc_methods: false,
+            grpc_method_index: 0,
+            grpc_stream: None,
+            grpc_stream_scroll: 0,
             selection_start: None,
+            prompt: None,
+            prompt_input: String::new(),
+            prompt_cursor: 0,
+            status_message: None,
+            damaged_files,
         };
         
         // Initialize headers from config
@@ -264,6 +591,79 @@ impl App {
         app
     }
 
+    // Puts back the newest readable backup of the first damaged file
+    pub fn restore_damaged_file(&mut self) {
+        let Some((store, file)) = self.damaged_files.first().cloned() else { return };
+        let valid = match store {
+            Store::Config => AppConfig::is_valid_file,
+            Store::Collections => collections::is_valid_file,
+            Store::History => history::is_valid_file,
+            Store::Cookies => cookies::is_valid_file,
+        };
+        self.status_message = Some(match storage::restore_backup(&file.path, valid) {
+            Ok(Some(backup)) => {
+                self.damaged_files.remove(0);
+                self.reload_store(store);
+                format!("Restored {} from {}", file.path.display(), backup.display())
+            }
+            Ok(None) => format!("No readable backup of {}", file.path.display()),
+            Err(e) => format!("Restore failed: {}", e),
+        });
+    }
+
+    // Renames the first damaged file out of the way and starts without it
+    pub fn set_aside_damaged_file(&mut self) {
+        let Some((store, file)) = self.damaged_files.first().cloned() else { return };
+        self.status_message = Some(match storage::set_aside(&file.path) {
+            Ok(target) => {
+                self.damaged_files.remove(0);
+                self.reload_store(store);
+                format!("Moved {} to {}", file.path.display(), target.display())
+            }
+            Err(e) => format!("Could not move {}: {}", file.path.display(), e),
+        });
+    }
+
+    // Leaves the first damaged file alone; its store isn't saved this session
+    pub fn keep_damaged_file(&mut self) {
+        if !self.damaged_files.is_empty() {
+            let (_, file) = self.damaged_files.remove(0);
+            self.status_message = Some(format!("{} left as it is, changes to it won't be saved", file.path.display()));
+        }
+    }
+
+    fn reload_store(&mut self, store: Store) {
+        let damaged = match store {
+            Store::Config => match AppConfig::load(self.workspace.as_ref()) {
+                Ok(config) => {
+                    self.config = config;
+                    None
+                }
+                Err(ConfigError::Damaged(file)) => Some(file),
+                Err(_) => None,
+            },
+            Store::Collections => {
+                self.collection_manager.reload_collections().ok();
+                self.collection_manager.damaged().cloned()
+            }
+            Store::History => {
+                self.history.reload();
+                // Reloaded entries have new ids
+                self.history_marked.clear();
+                self.refresh_history_view();
+                self.history.damaged().cloned()
+            }
+            Store::Cookies => {
+                self.cookie_jar.reload();
+                self.cookie_jar.damaged()
+            }
+        };
+        // Another file of the same store may be damaged too
+        if let Some(file) = damaged {
+            self.damaged_files.insert(0, (store, file));
+        }
+    }
+
     pub fn get_ordered_headers(&self) -> Vec<(String, String)> {
         let mut headers: Vec<_> = self.headers.iter()
             .map(|(k, v)| (k.clone(), v.clone()))
@@ -277,18 +677,20 @@ impl App {
     }
 
     pub async fn send_request(&mut self) {
-        crate::app::actions::RequestHandler::send_request(self).await
+        crate::app::actions::RequestHandler::send_request(self).await;
+        self.refresh_filter();
     }
 
     pub fn is_request_in_collection(&self) -> bool {
         if let Some(collection_name) = &self.selected_collection {
             if let Some(collection) = self.collection_manager.get_collection(collection_name) {
                 for item in &collection.requests {
-                    if let CollectionItem::Request(req) = item {
-                        if req.request.method == self.method.as_str() &&
-                            req.request.url == self.url {
-                            return true;
-                        }
+                    match item {
+                        CollectionItem::Request(req) if !self.is_websocket()
+                            && req.request.method == self.method.as_str()
+                            && req.request.url == self.url => return true,
+                        CollectionItem::WebSocket(ws) if ws.url == self.url => return true,
+                        _ => {}
                     }
                 }
             }
@@ -297,21 +699,746 @@ impl App {
     }
 
     pub fn save_to_collection(&mut self, collection_name: &str) -> anyhow::Result<()> {
-        let request_name = format!("{} {}", self.method.as_str(), self.url);
-        let headers: Vec<(String, String)> = self.headers.iter()
-            .map(|(k, v)| (k.clone(), v.clone()))
+        let source = self.collection_manager.get_collection(collection_name).and_then(|c| c.source.clone());
+        if let Some(source) = source {
+            return self.save_to_source(collection_name, &source);
+        }
+        if self.is_websocket() {
+            return self.collection_manager.add_item(collection_name, CollectionItem::WebSocket(SavedWebSocket {
+                name: format!("WS {}", self.url),
+                url: self.url.clone(),
+                headers: self.headers.clone(),
+                message: (!self.body.is_empty()).then(|| self.body.clone()),
+            }));
+        }
+        let request_name = if self.is_grpc() {
+            format!("gRPC {}", self.grpc_target.method)
+        } else {
+            format!("{} {}", self.method.as_str(), self.url)
+        };
+        let filter = self.response_filter.trim();
+
+        self.collection_manager.add_request(collection_name, SavedRequest {
+            id: next_request_id(),
+            name: request_name,
+            request: Request {
+                method: self.method.as_str().to_string(),
+                url: self.url.clone(),
+                headers: self.headers.clone(),
+                body: self.graphql.is_none().then(|| self.body.clone()),
+                options: self.request_options.clone(),
+                graphql: self.graphql.clone(),
+                grpc: self.is_grpc().then(|| self.grpc_target.clone()),
+            },
+            response_filter: (!filter.is_empty()).then(|| filter.to_string()),
+            openapi: self.openapi_link.clone(),
+        })
+    }
+
+    // Writes the request to the collection's `.http` file, over the request it
+    // was opened from or as a new one at the end
+    fn save_to_source(&mut self, collection_name: &str, source: &str) -> anyhow::Result<()> {
+        if self.is_websocket() || self.is_grpc() || self.graphql.is_some() {
+            anyhow::bail!("only plain HTTP requests can be saved to {}", source);
+        }
+        let collection = self.collection_manager.get_collection(collection_name).cloned()
+            .ok_or_else(|| anyhow::anyhow!("Collection not found: {}", collection_name))?;
+        let original = self.selected_request.clone()
+            .filter(|_| self.selected_collection.as_deref() == Some(collection_name));
+        let request = Request {
+            method: self.method.as_str().to_string(),
+            url: self.url.clone(),
+            // The file gets what's sent, not disabled headers or placeholders
+            headers: crate::app::actions::RequestHandler::plain_headers(self),
+            body: (!self.body.is_empty()).then(|| self.body.clone()),
+            options: RequestOptions::default(),
+            graphql: None,
+            grpc: None,
+        };
+        http_file::save_request(Path::new(source), &collection, original.as_deref(), &request)?;
+        self.refresh_collection_source(collection_name)?;
+
+        // Reading the file again gave its requests new ids. A new request is
+        // the file's last one; saving again updates it
+        let requests = self.collection_manager.get_collection(collection_name)
+            .map(|c| c.requests.as_slice())
+            .unwrap_or_default();
+        let saved = match &original {
+            Some(name) => requests.iter().find_map(|item| match item {
+                CollectionItem::Request(saved) if saved.name == *name => Some(saved),
+                _ => None,
+            }),
+            None => match requests.last() {
+                Some(CollectionItem::Request(saved)) => Some(saved),
+                _ => None,
+            },
+        };
+        self.selected_collection = Some(collection_name.to_string());
+        self.selected_request = saved.map(|saved| saved.name.clone());
+        self.selected_request_id = saved.map(|saved| saved.id);
+        self.status_message = Some(format!("Saved to {}", source));
+        Ok(())
+    }
+
+    // Reads a collection's `.http` file again, if it has one
+    pub fn refresh_collection_source(&mut self, collection_name: &str) -> anyhow::Result<()> {
+        let source = self.collection_manager.get_collection(collection_name).and_then(|c| c.source.clone());
+        if let Some(source) = source {
+            let file = http_file::read_file(Path::new(&source))?;
+            self.collection_manager.update_from_source(collection_name, file.items(), file.variables)?;
+        }
+        Ok(())
+    }
+
+    // Items of the open folder of the selected collection
+    pub fn collection_items(&self) -> Vec<CollectionItem> {
+        self.selected_collection.as_ref()
+            .and_then(|name| self.collection_manager.get_collection(name))
+            .and_then(|c| c.items_at(&self.collection_folder))
+            .map(<[CollectionItem]>::to_vec)
+            .unwrap_or_default()
+    }
+
+    pub fn load_saved_request(&mut self, collection_name: &str, saved: &SavedRequest) {
+        // Collection variables are filled in; unset ones stay as `{{name}}`
+        let collection = self.collection_manager.get_collection(collection_name).cloned();
+        let resolve = |text: &str| match &collection {
+            Some(collection) => collection.resolve(text),
+            None => text.to_string(),
+        };
+        self.url = resolve(&saved.request.url);
+        self.method = HttpMethod::from_str(&saved.request.method).unwrap_or(HttpMethod::GET);
+        self.headers = saved.request.headers.iter()
+            .map(|(k, v)| (k.clone(), resolve(v)))
             .collect();
+        self.header_enabled = self.headers.keys().map(|k| (k.clone(), true)).collect();
+        self.body = saved.request.body.as_deref().map(resolve).unwrap_or_default();
+        self.request_options = saved.request.options.clone();
+        self.graphql = saved.request.graphql.clone();
+        self.graphql_pane = GraphQlPane::Query;
+        self.grpc_target = saved.request.grpc.clone().unwrap_or_default();
+        // Definitions are loaded again for the saved request's server or files
+        self.grpc_pool = None;
+        self.openapi_link = saved.openapi.clone();
+        self.selected_collection = Some(collection_name.to_string());
+        self.selected_request = Some(saved.name.clone());
+        self.selected_request_id = Some(saved.id);
+        self.response_filter = saved.response_filter.clone().unwrap_or_default();
+        self.response_filter_cursor = self.response_filter.chars().count();
+        self.refresh_filter();
+    }
+
+    // Puts a history entry back in the editor, with the response it got shown
+    // as it was recorded. Nothing is sent.
+    pub fn load_history_entry(&mut self, entry: &HistoryEntry) {
+        let request = &entry.request;
+        self.url = request.url.clone();
+        self.method = HttpMethod::from_str(&request.method).unwrap_or(HttpMethod::GET);
+        self.headers = request.headers.clone();
+        self.headers.extend(request.disabled_headers.clone());
+        self.header_enabled = request.headers.keys().map(|k| (k.clone(), true))
+            .chain(request.disabled_headers.keys().map(|k| (k.clone(), false)))
+            .collect();
+        self.selected_header_index = 0;
+        self.body = request.body.clone().unwrap_or_default();
+        self.request_options = RequestOptions::default();
+        self.graphql = None;
+        self.grpc_target = GrpcTarget::default();
+        self.grpc_pool = None;
+        self.openapi_link = None;
+        // Saving it makes a new request rather than overwriting the one last opened
+        self.selected_collection = None;
+        self.selected_request = None;
+        self.selected_request_id = None;
+        self.response_filter.clear();
+        self.response_filter_cursor = 0;
+
+        if let Some(stream) = self.event_stream.take() {
+            stream.log.cancel();
+        }
+        if let Some(stream) = self.grpc_stream.take() {
+            stream.log.cancel();
+        }
+        self.contract_check = None;
+        self.response_scroll = 0;
+        self.response_search.clear();
+        match &entry.response {
+            Some(response) => {
+                let status = response.status.unwrap_or(0);
+                let set_cookies = response.headers.iter()
+                    .filter(|(name, _)| name.eq_ignore_ascii_case("set-cookie"))
+                    .map(|(_, value)| value.clone())
+                    .collect();
+                self.response_metadata = Some(ResponseMetadata {
+                    status,
+                    status_text: response.status_text.clone().unwrap_or_else(|| status.to_string()),
+                    time_ms: response.time_ms,
+                    size_bytes: response.size_bytes,
+                    is_binary: response.is_binary(),
+                    response_headers: response.headers.iter().cloned().collect(),
+                    set_cookies,
+                    url: request.url.clone(),
+                    redirects: Vec::new(),
+                    http_version: response.http_version.clone().unwrap_or_default(),
+                    proxy: None,
+                    timings: response.timings.clone().unwrap_or_else(|| Timings {
+                        ttfb_ms: response.time_ms as f64,
+                        ..Timings::default()
+                    }),
+                    timestamp: entry.timestamp,
+                    from_history: true,
+                });
+                self.response_bytes = response.body_bytes();
+                self.response = (!response.is_binary()).then(|| response.body.clone());
+                // Entries recorded before binary bodies were kept
+                if response.body.is_empty() && response.size_bytes > 0 {
+                    self.status_message = Some("The binary response body isn't kept in history".to_string());
+                }
+            }
+            None => {
+                self.response_metadata = None;
+                self.response_bytes = None;
+                self.response = Some("No response was recorded for this request".to_string());
+            }
+        }
+        self.refresh_filter();
+    }
+
+    pub fn load_saved_websocket(&mut self, collection_name: &str, saved: &SavedWebSocket) {
+        self.url = saved.url.clone();
+        self.method = HttpMethod::GET;
+        self.headers = saved.headers.clone();
+        self.header_enabled = self.headers.keys().map(|k| (k.clone(), true)).collect();
+        self.body = saved.message.clone().unwrap_or_default();
+        self.request_options = RequestOptions::default();
+        self.openapi_link = None;
+        self.selected_collection = Some(collection_name.to_string());
+        self.selected_request = Some(saved.name.clone());
+        self.selected_request_id = None;
+        self.response_filter.clear();
+        self.response_filter_cursor = 0;
+    }
+
+    // The text the body editor works on: the raw body, or the focused
+    // GraphQL pane
+    pub fn body_text(&self) -> &str {
+        match (&self.graphql, self.graphql_pane) {
+            (Some(gql), GraphQlPane::Query) => &gql.query,
+            (Some(gql), GraphQlPane::Variables) => &gql.variables,
+            (None, _) => &self.body,
+        }
+    }
+
+    pub fn body_text_mut(&mut self) -> &mut String {
+        match (&mut self.graphql, self.graphql_pane) {
+            (Some(gql), GraphQlPane::Query) => &mut gql.query,
+            (Some(gql), GraphQlPane::Variables) => &mut gql.variables,
+            (None, _) => &mut self.body,
+        }
+    }
+
+    // Body to send: the raw body, or the GraphQL JSON payload
+    pub fn request_body(&self) -> anyhow::Result<String> {
+        match &self.graphql {
+            Some(gql) => graphql::payload(&gql.query, &gql.variables, &gql.operation_name),
+            None => Ok(self.body.clone()),
+        }
+    }
+
+    // Switches between a raw body and GraphQL mode. GraphQL goes over POST.
+    pub fn toggle_graphql(&mut self) {
+        self.completions.clear();
+        if self.graphql.take().is_none() {
+            self.graphql = Some(GraphQlBody::default());
+            self.graphql_pane = GraphQlPane::Query;
+            if self.method == HttpMethod::GET {
+                self.method = HttpMethod::POST;
+            }
+        }
+    }
+
+    // Completions for the identifier before the cursor in the query pane
+    pub fn refresh_completions(&mut self) {
+        self.completion_index = 0;
+        self.completions = match (&self.graphql_schema, &self.graphql, self.graphql_pane) {
+            (Some(schema), Some(gql), GraphQlPane::Query) => schema.completions(&gql.query, self.cursor_position),
+            _ => Vec::new(),
+        };
+    }
+
+    // Replaces the identifier before the cursor with the chosen completion
+    pub fn accept_completion(&mut self) {
+        let Some(choice) = self.completions.get(self.completion_index).cloned() else { return };
+        self.completions.clear();
+        let cursor = self.cursor_position;
+        let text = self.body_text_mut();
+        let start = text[..cursor]
+            .rfind(|c: char| !(c.is_alphanumeric() || c == '_'))
+            .map(|i| i + text[i..].chars().next().map_or(1, char::len_utf8))
+            .unwrap_or(0);
+        text.replace_range(start..cursor, &choice);
+        self.cursor_position = start + choice.len();
+    }
+
+    // Type shown in the schema explorer, None for the list of types
+    pub fn schema_type(&self) -> Option<&graphql::SchemaType> {
+        let schema = self.graphql_schema.as_ref()?;
+        schema.types.get(self.schema_path.last()?)
+    }
+
+    pub fn schema_entry_count(&self) -> usize {
+        match (self.schema_type(), &self.graphql_schema) {
+            (Some(t), _) => t.fields.len(),
+            (None, Some(schema)) => schema.browsable_types().len(),
+            (None, None) => 0,
+        }
+    }
+
+    // Opens the selected type, or the type of the selected field
+    pub fn schema_open_selected(&mut self) {
+        let Some(schema) = &self.graphql_schema else { return };
+        let next = match self.schema_type() {
+            Some(t) => t.fields.get(self.schema_selected_index).map(|f| f.type_name.clone()),
+            None => schema.browsable_types().get(self.schema_selected_index).map(|t| t.name.clone()),
+        };
+        if let Some(next) = next.filter(|name| schema.types.contains_key(name)) {
+            self.schema_path.push(next);
+            self.schema_selected_index = 0;
+        }
+    }
+
+    // ws:// and wss:// URLs open a WebSocket instead of sending a request
+    pub fn is_websocket(&self) -> bool {
+        let url = self.url.trim_start().to_ascii_lowercase();
+        url.starts_with("ws://") || url.starts_with("wss://")
+    }
+
+    pub fn is_grpc(&self) -> bool {
+        grpc::is_grpc_url(&self.url)
+    }
 
-        self.collection_manager.save_request(
-            collection_name,
-            &request_name,
-            self.method.as_str(),
-            &self.url,
-            &headers,
-            &self.body,
+    // Names of the methods in the loaded service definitions
+    pub fn grpc_methods(&self) -> Vec<String> {
+        self.grpc_pool.as_ref().map(grpc::method_names).unwrap_or_default()
+    }
+
+    pub fn grpc_method(&self) -> Option<prost_reflect::MethodDescriptor> {
+        grpc::find_method(self.grpc_pool.as_ref()?, &self.grpc_target.method)
+    }
+
+    pub fn set_grpc_pool(&mut self, pool: prost_reflect::DescriptorPool) {
+        self.grpc_pool_server = grpc::server(&self.url);
+        self.grpc_pool = Some(pool);
+    }
+
+    // Drops the definitions once the URL points at another server, so they're
+    // loaded again from that one
+    pub fn drop_stale_grpc_pool(&mut self) {
+        if self.grpc_pool.is_some() && self.grpc_pool_server != grpc::server(&self.url) {
+            self.grpc_pool = None;
+        }
+    }
+
+    pub fn open_grpc_methods(&mut self) {
+        let methods = self.grpc_methods();
+        if methods.is_empty() {
+            self.status_message = Some("No gRPC methods loaded".to_string());
+            return;
+        }
+        self.grpc_method_index = methods.iter().position(|m| *m == self.grpc_target.method).unwrap_or(0);
+        self.show_grpc_methods = true;
+    }
+
+    // Picks the method under the cursor; an empty body is filled with the
+    // method's request message.
+    pub fn choose_grpc_method(&mut self) {
+        self.show_grpc_methods = false;
+        let Some(name) = self.grpc_methods().get(self.grpc_method_index).cloned() else { return };
+        self.grpc_target.method = name;
+        if self.body.trim().is_empty() {
+            if let Some(method) = self.grpc_method() {
+                self.body = grpc::request_template(&method);
+            }
+        }
+    }
+
+    // The session for the current URL, while it's open
+    pub fn open_websocket(&self) -> Option<&WebSocketSession> {
+        self.websocket.as_ref().filter(|ws| ws.log.is_open() && ws.url == self.url.trim())
+    }
+
+    // Remembers the current filter on the loaded saved request, if any.
+    pub fn persist_response_filter(&mut self) {
+        if let (Some(collection), Some(request)) = (&self.selected_collection, self.selected_request_id) {
+            let filter = self.response_filter.trim();
+            let filter = (!filter.is_empty()).then(|| filter.to_string());
+            if let Err(e) = self.collection_manager.set_request_filter(collection, request, filter) {
+                debug!("Failed to save response filter: {}", e);
+            }
+        }
+    }
+
+    // Cookies set by the current response, parsed against the request URL.
+    // Entries that can't be parsed are returned as None alongside the raw value.
+    pub fn response_cookies(&self) -> Vec<(String, Option<Cookie>)> {
+        let url = self.response_metadata.as_ref()
+            .and_then(|m| reqwest::Url::parse(&m.url).ok());
+        self.response_metadata
+            .iter()
+            .flat_map(|m| m.set_cookies.iter())
+            .map(|raw| (raw.clone(), url.as_ref().and_then(|u| Cookie::parse(raw, u))))
+            .collect()
+    }
+
+    // Redirect settings for the next send: the request's overrides, else the config.
+    pub fn redirect_policy(&self) -> (bool, usize) {
+        (
+            self.request_options.follow_redirects.unwrap_or(self.config.app.follow_redirects),
+            self.request_options.max_redirects.unwrap_or(self.config.app.max_redirects),
         )
     }
 
+    // Current redirect override as typed into the prompt
+    pub fn redirect_override_text(&self) -> String {
+        match (self.request_options.follow_redirects, self.request_options.max_redirects) {
+            (Some(false), _) => "off".to_string(),
+            (_, Some(max)) => max.to_string(),
+            (Some(true), None) => "on".to_string(),
+            (None, None) => String::new(),
+        }
+    }
+
+    // Parses the redirect prompt: "on", "off", a hop limit, or empty to use the config.
+    pub fn set_redirect_override(&mut self, input: &str) -> anyhow::Result<()> {
+        let (follow, max) = match input.to_ascii_lowercase().as_str() {
+            "" | "default" => (None, None),
+            "on" | "yes" | "follow" => (Some(true), None),
+            "off" | "no" | "none" => (Some(false), None),
+            n => match n.parse::<usize>() {
+                Ok(0) => (Some(false), None),
+                Ok(max) => (Some(true), Some(max)),
+                Err(_) => anyhow::bail!("expected on, off or a number of hops"),
+            },
+        };
+        self.request_options.follow_redirects = follow;
+        self.request_options.max_redirects = max;
+        Ok(())
+    }
+
+    pub fn http_version(&self) -> HttpVersion {
+        self.request_options.http_version.unwrap_or(self.config.app.http_version)
+    }
+
+    pub fn set_http_version_override(&mut self, input: &str) -> anyhow::Result<()> {
+        self.request_options.http_version = match input {
+            "" | "default" => None,
+            s => Some(HttpVersion::parse(s).ok_or_else(|| anyhow::anyhow!("expected auto, 1.1, 2 or h2c"))?),
+        };
+        Ok(())
+    }
+
+    pub fn selected_stored_cookie(&self) -> Option<Cookie> {
+        self.cookie_jar.cookies().into_iter().nth(self.cookies_selected_index)
+    }
+
+    // Applies the method highlighted in the selector. The entry after the
+    // standard methods asks for a custom one instead.
+    pub fn choose_selected_method(&mut self) {
+        self.show_method_selector = false;
+        match HttpMethod::all().get(self.selector_method_index) {
+            Some(method) => self.method = method.clone(),
+            None => {
+                let current = match &self.method {
+                    HttpMethod::Custom(method) => method.clone(),
+                    _ => String::new(),
+                };
+                self.open_prompt(PromptKind::CustomMethod, current);
+            }
+        }
+    }
+
+    // Selector index of the current method, with custom methods on the last entry
+    pub fn method_selector_index(&self) -> usize {
+        let methods = HttpMethod::all();
+        methods.iter().position(|m| *m == self.method).unwrap_or(methods.len())
+    }
+
+    pub fn open_prompt(&mut self, kind: PromptKind, initial: String) {
+        self.prompt_cursor = initial.chars().count();
+        self.prompt_input = initial;
+        self.prompt = Some(kind);
+    }
+
+    pub fn close_prompt(&mut self) {
+        self.prompt = None;
+        self.prompt_input.clear();
+        self.prompt_cursor = 0;
+    }
+
+    pub fn response_content_type(&self) -> &str {
+        // gRPC responses are shown as the JSON the message was converted to
+        if self.response_metadata.as_ref().is_some_and(|m| grpc::is_grpc_url(&m.url)) {
+            return "application/json";
+        }
+        self.response_metadata.as_ref()
+            .and_then(|m| m.response_headers.get("content-type"))
+            .map(|s| s.as_str())
+            .unwrap_or("")
+    }
+
+    fn response_is_binary(&self) -> bool {
+        self.response_metadata.as_ref().map(|m| m.is_binary).unwrap_or(false)
+    }
+
+    // The response body as displayed: JSON tree rows, pretty-printed text, or
+    // the hex dump for binary bodies. Search operates on these lines.
+    pub fn response_lines(&self) -> &[String] {
+        &self.response_lines
+    }
+
+    // Formats the body again. Called when the response, the filter or the
+    // expanded tree nodes change, not on every frame.
+    pub fn refresh_response_lines(&mut self) {
+        self.response_lines = match &self.response_bytes {
+            Some(bytes) if self.response_is_binary() => hex_dump(bytes, 0, usize::MAX),
+            _ => match &self.response_json {
+                Some(json) => self.json_tree.rows(json).iter().map(|row| row.text()).collect(),
+                None => {
+                    let body = self.response.as_deref().unwrap_or("");
+                    format_response_body(self.response_content_type(), body)
+                        .lines()
+                        .map(|l| l.to_string())
+                        .collect()
+                }
+            },
+        };
+        self.refresh_response_rows();
+    }
+
+    // Only plain text is wrapped; tree rows and the hex dump are one row each.
+    fn refresh_response_rows(&mut self) {
+        self.response_rows.clear();
+        if self.response_json.is_some() || self.response_is_binary() || self.response_view_width == 0 {
+            return;
+        }
+        let mut row = 0;
+        for line in &self.response_lines {
+            self.response_rows.push(row);
+            row += wrap_points(line, self.response_view_width).len();
+        }
+        self.response_rows.push(row);
+    }
+
+    pub fn set_response_view_width(&mut self, width: usize) {
+        if width != self.response_view_width {
+            self.response_view_width = width;
+            self.refresh_response_rows();
+        }
+    }
+
+    // Screen row (before scrolling) that a body line starts on
+    pub fn response_row_of_line(&self, line: usize) -> usize {
+        self.response_rows.get(line).copied().unwrap_or(line)
+    }
+
+    // Body line shown on a screen row (before scrolling)
+    pub fn response_line_at_row(&self, row: usize) -> usize {
+        if self.response_rows.is_empty() {
+            return row;
+        }
+        self.response_rows.partition_point(|&start| start <= row).saturating_sub(1)
+    }
+
+    pub fn response_is_json(&self) -> bool {
+        crate::utils::format::is_json(self.response_content_type())
+    }
+
+    // Re-parses the JSON response and re-applies the filter. Called when a new
+    // response arrives or the filter changes; the result feeds the tree view.
+    pub fn refresh_filter(&mut self) {
+        let parsed: Option<serde_json::Value> = if self.response_is_json() {
+            self.response.as_deref().and_then(|body| serde_json::from_str(body).ok())
+        } else {
+            None
+        };
+
+        self.response_filter_error = None;
+        self.response_json = match parsed {
+            Some(json) if !self.response_filter.trim().is_empty() => {
+                match jsonpath::filter(&json, &self.response_filter) {
+                    Ok(filtered) => Some(filtered),
+                    Err(e) => {
+                        self.response_filter_error = Some(e.to_string());
+                        Some(json)
+                    }
+                }
+            }
+            other => other,
+        };
+        self.json_tree.reset();
+        self.response_scroll = 0;
+        self.refresh_response_lines();
+        self.refresh_search();
+    }
+
+    pub fn json_row_count(&self) -> usize {
+        self.response_json.as_ref().map(|json| self.json_tree.rows(json).len()).unwrap_or(0)
+    }
+
+    // Path and value of the selected tree node.
+    pub fn selected_json_node(&self) -> Option<(String, serde_json::Value)> {
+        let json = self.response_json.as_ref()?;
+        let rows = self.json_tree.rows(json);
+        let row = rows.get(self.json_tree.selected)?;
+        Some((row.path.clone(), row.value.clone()))
+    }
+
+    pub fn select_json_row(&mut self, row: usize) {
+        self.json_tree.selected = row.min(self.json_row_count().saturating_sub(1));
+        self.scroll_to_line(self.json_tree.selected);
+    }
+
+    pub fn toggle_selected_json_node(&mut self, expand: Option<bool>) {
+        let Some(json) = &self.response_json else { return };
+        let rows = self.json_tree.rows(json);
+        let Some(row) = rows.get(self.json_tree.selected) else { return };
+        if !(row.value.is_object() || row.value.is_array()) {
+            return;
+        }
+        let path = row.path.clone();
+        let collapsed = self.json_tree.collapsed.contains(&path);
+        if expand.is_none() || expand == Some(collapsed) {
+            self.json_tree.toggle(&path);
+        }
+        // Selection may have been on a closing bracket that is now hidden
+        if let Some(pos) = self.json_tree.rows(json).iter().position(|r| r.path == path) {
+            self.json_tree.selected = pos;
+        }
+        self.refresh_response_lines();
+        self.refresh_search();
+    }
+
+    // Number of screen rows the body takes up, which is what it scrolls by
+    pub fn response_row_count(&self) -> usize {
+        self.response_rows.last().copied().unwrap_or(self.response_lines.len())
+    }
+
+    // Recomputes search matches and scrolls to the first one at or below the
+    // current scroll position.
+    pub fn refresh_search(&mut self) {
+        self.response_search.update(&self.response_lines);
+        let first_line = self.response_line_at_row(self.response_scroll);
+        if let Some(line) = self.response_search.seek_from(first_line).map(|m| m.line) {
+            self.scroll_to_line(line);
+        }
+    }
+
+    // Adjusts the scroll only when the line is outside the visible window.
+    pub fn scroll_to_line(&mut self, line: usize) {
+        let height = self.response_view_height.max(1);
+        let row = self.response_row_of_line(line);
+        if row < self.response_scroll || row >= self.response_scroll + height {
+            self.response_scroll = row.saturating_sub(height / 3);
+        }
+    }
+
+    pub fn save_response_body(&mut self, path: &str) -> anyhow::Result<usize> {
+        let bytes = match &self.response_bytes {
+            Some(bytes) => bytes.as_slice(),
+            None => return Err(anyhow::anyhow!("No response body to save")),
+        };
+        std::fs::write(user_path(path), bytes)?;
+        Ok(bytes.len())
+    }
+
+    // Imports an OpenAPI / Swagger spec or a HAR file as a new collection,
+    // returning its name and request count. Existing collections are never
+    // replaced; the name gets a number instead.
+    pub fn import_collection(&mut self, path: &str) -> anyhow::Result<(String, usize)> {
+        let path = user_path(path);
+        let har = std::fs::read_to_string(&path).ok()
+            .and_then(|text| serde_json::from_str::<serde_json::Value>(&text).ok())
+            .filter(har::is_har);
+        let mut collection = match har {
+            _ if http_file::is_http_file(&path) => http_file::import_file(&path)?,
+            Some(har) => {
+                let name = path.file_stem().map(|stem| stem.to_string_lossy().into_owned()).unwrap_or_default();
+                har::to_collection(&name, &har::from_har(&har)?)
+            }
+            None => openapi::import_file(&path)?,
+        };
+        let title = collection.info.name.clone();
+        let mut n = 2;
+        while self.collection_manager.get_collection(&collection.info.name).is_some() {
+            collection.info.name = format!("{} ({})", title, n);
+            n += 1;
+        }
+        self.collection_manager.save_collection(&collection)?;
+        Ok((collection.info.name, openapi::request_count(&collection.requests)))
+    }
+
+    // Lists the entries matching the history filter again
+    pub fn refresh_history_view(&mut self) {
+        let entries = self.history.get_entries();
+        self.history_view = (0..entries.len()).rev()
+            .filter(|&i| self.history_filter.matches(&entries[i]))
+            .collect();
+        self.history_selected_index = self.history_selected_index.min(self.history_view.len().saturating_sub(1));
+    }
+
+    // Moves the selection to the entry with this id, if it's shown
+    pub fn select_history_entry(&mut self, id: Option<u64>) {
+        let entries = self.history.get_entries();
+        if let Some(position) = self.history_view.iter().position(|&i| Some(entries[i].id) == id) {
+            self.history_selected_index = position;
+        }
+    }
+
+    pub fn selected_history_entry(&self) -> Option<&HistoryEntry> {
+        self.history_view.get(self.history_selected_index)
+            .and_then(|&i| self.history.get_entries().get(i))
+    }
+
+    // Marked history entries, or the selected one when none are marked
+    pub fn history_selection(&self) -> Vec<HistoryEntry> {
+        if self.history_marked.is_empty() {
+            return self.selected_history_entry().cloned().into_iter().collect();
+        }
+        self.history.get_entries().iter()
+            .filter(|entry| self.history_marked.contains(&entry.id))
+            .cloned()
+            .collect()
+    }
+
+    pub fn toggle_history_mark(&mut self) {
+        if let Some(id) = self.selected_history_entry().map(|entry| entry.id) {
+            if !self.history_marked.remove(&id) {
+                self.history_marked.insert(id);
+            }
+        }
+    }
+
+    // Deletes the marked entries, or the selected one when none are marked.
+    // With `all`, every entry the filter shows goes.
+    pub fn delete_history_entries(&mut self, all: bool) {
+        let ids: HashSet<u64> = if all {
+            let entries = self.history.get_entries();
+            self.history_view.iter().map(|&i| entries[i].id).collect()
+        } else {
+            self.history_selection().iter().map(|entry| entry.id).collect()
+        };
+        if ids.is_empty() {
+            return;
+        }
+        self.status_message = Some(match self.history.remove_entries(&ids) {
+            Ok(1) => "Deleted 1 request from history".to_string(),
+            Ok(count) => format!("Deleted {} requests from history", count),
+            Err(e) => format!("Deleting failed: {:?}", e),
+        });
+        self.history_marked.clear();
+        self.refresh_history_view();
+    }
+
     pub fn update_request_body(&mut self, new_body: String) {
         self.body = new_body;
         // Update Content-Length when body changes
@@ -320,4 +1447,12 @@ impl App {
             self.body.len().to_string()
         );
     }
-} 
\ No newline at end of file
+} 
+
+// Expands a leading `~/` to the home directory
+pub fn user_path(path: &str) -> PathBuf {
+    match path.strip_prefix("~/") {
+        Some(rest) => dirs::home_dir().unwrap_or_default().join(rest),
+        None => PathBuf::from(path),
+    }
+}
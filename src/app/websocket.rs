@@ -0,0 +1,152 @@
+use std::collections::HashMap;
+use std::time::Duration;
+use anyhow::{anyhow, Context, Result};
+use futures_util::{SinkExt, StreamExt};
+use log::{debug, error};
+use tokio::sync::mpsc;
+use tokio_tungstenite::tungstenite::client::IntoClientRequest;
+use tokio_tungstenite::tungstenite::http::{HeaderName, HeaderValue};
+use tokio_tungstenite::tungstenite::protocol::frame::coding::CloseCode;
+use tokio_tungstenite::tungstenite::protocol::CloseFrame;
+use tokio_tungstenite::tungstenite::Message;
+use tokio_tungstenite::Connector;
+use crate::app::background_log::BackgroundLog;
+use crate::models::websocket::{WsDirection, WsKind, WsMessage};
+
+// Headers the handshake sets itself
+const HANDSHAKE_HEADERS: &[&str] = &[
+    "host", "connection", "upgrade", "content-length",
+    "sec-websocket-key", "sec-websocket-version", "sec-websocket-extensions",
+];
+
+// Response to the opening handshake
+pub struct Handshake {
+    pub status: u16,
+    pub status_text: String,
+    pub headers: HashMap<String, String>,
+}
+
+// An open WebSocket connection. Frames are read and written by a background
+// task; the UI sends it messages over a channel and reads the shared log,
+// which is redrawn on every tick.
+#[derive(Clone)]
+pub struct WebSocketSession {
+    pub url: String,
+    pub log: BackgroundLog<WsMessage>,
+    outgoing: mpsc::UnboundedSender<Message>,
+}
+
+impl WebSocketSession {
+    pub async fn connect(url: &str, headers: &HashMap<String, String>, timeout: Duration) -> Result<(Self, Handshake)> {
+        let mut request = url.into_client_request().context("invalid WebSocket URL")?;
+        for (name, value) in headers {
+            if HANDSHAKE_HEADERS.contains(&name.to_ascii_lowercase().as_str()) {
+                continue;
+            }
+            request.headers_mut().insert(
+                HeaderName::from_bytes(name.as_bytes()).with_context(|| format!("invalid header name: {}", name))?,
+                HeaderValue::from_str(value).with_context(|| format!("invalid value for header {}", name))?,
+            );
+        }
+
+        // Certificates aren't verified for HTTP requests either
+        let tls = tokio_native_tls::native_tls::TlsConnector::builder()
+            .danger_accept_invalid_certs(true)
+            .danger_accept_invalid_hostnames(true)
+            .build()?;
+        let (stream, response) = tokio::time::timeout(
+            timeout,
+            tokio_tungstenite::connect_async_tls_with_config(request, None, false, Some(Connector::NativeTls(tls))),
+        )
+        .await
+        .map_err(|_| anyhow!("handshake timed out"))??;
+
+        let handshake = Handshake {
+            status: response.status().as_u16(),
+            status_text: response.status().to_string(),
+            headers: response.headers().iter()
+                .map(|(k, v)| (k.to_string(), v.to_str().unwrap_or("").to_string()))
+                .collect(),
+        };
+
+        let (outgoing, mut commands) = mpsc::unbounded_channel::<Message>();
+        let initial = vec![WsMessage::info(format!("Connected to {}", url))];
+        let log = BackgroundLog::spawn(initial, |log| async move {
+            let (mut sink, mut stream) = stream.split();
+            let push = |message: WsMessage| log.push(message);
+            loop {
+                tokio::select! {
+                    command = commands.recv() => {
+                        let Some(message) = command else { break };
+                        let entry = log_entry(WsDirection::Sent, &message);
+                        if let Err(e) = sink.send(message).await {
+                            error!("WebSocket send failed: {}", e);
+                            push(WsMessage::info(format!("Send failed: {}", e)));
+                            break;
+                        }
+                        if let Some(entry) = entry {
+                            push(entry);
+                        }
+                    }
+                    incoming = stream.next() => match incoming {
+                        Some(Ok(message)) => {
+                            if let Some(entry) = log_entry(WsDirection::Received, &message) {
+                                push(entry);
+                            }
+                        }
+                        Some(Err(e)) => {
+                            debug!("WebSocket closed with error: {}", e);
+                            push(WsMessage::info(format!("Connection error: {}", e)));
+                            break;
+                        }
+                        None => break,
+                    }
+                }
+            }
+            push(WsMessage::info("Disconnected"));
+        });
+
+        Ok((WebSocketSession { url: url.to_string(), log, outgoing }, handshake))
+    }
+
+    pub fn send_text(&self, text: &str) -> Result<()> {
+        self.send(Message::Text(text.to_string()))
+    }
+
+    pub fn send_binary(&self, data: Vec<u8>) -> Result<()> {
+        self.send(Message::Binary(data))
+    }
+
+    pub fn ping(&self) -> Result<()> {
+        self.send(Message::Ping(b"raquet".to_vec()))
+    }
+
+    pub fn close(&self) -> Result<()> {
+        self.send(Message::Close(Some(CloseFrame {
+            code: CloseCode::Normal,
+            reason: "".into(),
+        })))
+    }
+
+    fn send(&self, message: Message) -> Result<()> {
+        if !self.log.is_open() {
+            return Err(anyhow!("not connected"));
+        }
+        self.outgoing.send(message).map_err(|_| anyhow!("not connected"))
+    }
+}
+
+fn log_entry(direction: WsDirection, message: &Message) -> Option<WsMessage> {
+    let (kind, data) = match message {
+        Message::Text(text) => (WsKind::Text, text.as_bytes().to_vec()),
+        Message::Binary(data) => (WsKind::Binary, data.clone()),
+        Message::Ping(data) => (WsKind::Ping, data.clone()),
+        Message::Pong(data) => (WsKind::Pong, data.clone()),
+        Message::Close(frame) => (WsKind::Close, match frame {
+            Some(frame) => format!("{} {}", u16::from(frame.code), frame.reason).trim_end().as_bytes().to_vec(),
+            None => Vec::new(),
+        }),
+        Message::Frame(_) => return None,
+    };
+    Some(WsMessage::new(direction, kind, data))
+}
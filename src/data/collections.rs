@@ -1,83 +1,240 @@
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap, HashSet};
 use std::fs;
-use std::path::PathBuf;
+use std::path::{Component, Path, PathBuf};
 use anyhow::{Result, Context};
 use directories::UserDirs;
-use std::fs::File;
-use std::io::{BufReader, BufWriter};
 use chrono::{DateTime, Utc};
+use log::warn;
+use serde_json::{json, Value};
+use super::migrations::{self, Schema};
+use super::storage::{self, DamagedFile};
+use super::Workspace;
 use crate::models::collection::{
-    Collection, CollectionInfo, CollectionItem, 
-    SavedRequest, Request, create_default_collection
+    Collection, CollectionInfo, CollectionItem, Folder, OpenApiLink,
+    SavedRequest, SavedWebSocket, Request, RequestOptions, create_default_collection, next_request_id
 };
 
 #[derive(Clone)]
 pub struct CollectionManager {
     collections_file: PathBuf,
     collections: HashMap<String, Collection>,
+    // Set when collections are kept one directory each instead of in
+    // `collections_file`
+    collections_dir: Option<PathBuf>,
+    // Collections of the project workspace, stored apart from the global ones
+    workspace: Option<Workspace>,
+    workspace_collections: HashSet<String>,
+    // Global collections hidden by a workspace collection of the same name
+    shadowed: HashMap<String, Collection>,
+    // Set when a file couldn't be read; nothing is saved until it's dealt with
+    damaged: Option<DamagedFile>,
 }
 
+// Layout of collections.json: version 0 was a map by name, 1 a bare list
+pub const VERSION: u32 = 2;
+
+static SCHEMA: Schema<Value> = Schema {
+    current: VERSION,
+    version: migrations::json_version,
+    steps: &[(0, map_to_list), (1, add_version)],
+};
+
+#[derive(Serialize)]
+struct CollectionsFile<'a> {
+    version: u32,
+    collections: Vec<&'a Collection>,
+}
+
+// Layout of a collection directory, recorded in its `collection.yaml`
+const DIRECTORY_VERSION: u32 = 1;
+
+// The `collection.yaml` of a collection directory
+#[derive(Serialize, Deserialize)]
+struct CollectionFile {
+    #[serde(default = "directory_version")]
+    version: u32,
+    #[serde(flatten)]
+    info: CollectionInfo,
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    variables: BTreeMap<String, String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    source: Option<String>,
+    // File names of the top-level requests and folders, in order
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    order: Vec<String>,
+}
+
+// A folder's `folder.yaml`: its name, which the directory's may only
+// approximate, and the file names of the requests and folders in it, in
+// order. Moving or deleting one only rewrites this list.
+#[derive(Serialize, Deserialize, Default)]
+struct FolderFile {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    name: Option<String>,
+    #[serde(default)]
+    order: Vec<String>,
+}
+
+// A request's file
+#[derive(Serialize, Deserialize)]
+#[serde(untagged)]
+enum FileItem {
+    Request(Box<SavedRequest>),
+    WebSocket(SavedWebSocket),
+}
+
+const COLLECTION_FILE: &str = "collection.yaml";
+const FOLDER_FILE: &str = "folder.yaml";
+
 impl CollectionManager {
     pub fn new() -> Self {
         let collections_file = dirs::home_dir()
             .map(|h| h.join(".raquet").join("collections.json"))
             .unwrap_or_default();
+        Self::in_file(collections_file)
+    }
 
-        if !collections_file.exists() {
+    // Keeps collections in `collections_file`, which is created with the
+    // default collection when it doesn't exist
+    pub fn in_file(collections_file: PathBuf) -> Self {
+        let mut manager = CollectionManager {
+            collections: HashMap::new(),
+            collections_file,
+            collections_dir: None,
+            workspace: None,
+            workspace_collections: HashSet::new(),
+            shadowed: HashMap::new(),
+            damaged: None,
+        };
+        if !manager.collections_file.exists() {
             // Create directory if it doesn't exist
-            if let Some(parent) = collections_file.parent() {
+            if let Some(parent) = manager.collections_file.parent() {
                 std::fs::create_dir_all(parent).ok();
             }
 
             // Create default collection
             let default_collection = create_default_collection();
-            let collections = HashMap::from([
-                (default_collection.info.name.clone(), default_collection)
-            ]);
+            manager.collections.insert(default_collection.info.name.clone(), default_collection);
+            manager.save_all_collections().ok();
+        } else if let Err(e) = manager.load_collections() {
+            warn!("Failed to load collections: {:#}", e);
+        }
+        manager
+    }
 
-            // Save to file
-            if let Ok(json) = serde_json::to_string_pretty(&collections) {
-                std::fs::write(&collections_file, json).ok();
-            }
+    // Keeps collections in `dir`, a directory per collection with a YAML file
+    // per request and a subdirectory per folder, to be committed with code
+    pub fn in_directory(dir: PathBuf) -> Self {
+        let mut manager = CollectionManager {
+            collections_file: PathBuf::new(),
+            collections: HashMap::new(),
+            collections_dir: Some(dir),
+            workspace: None,
+            workspace_collections: HashSet::new(),
+            shadowed: HashMap::new(),
+            damaged: None,
+        };
+        if let Err(e) = manager.load_collections() {
+            warn!("Failed to load collections: {:#}", e);
+        }
+        manager
+    }
 
-            CollectionManager {
-                collections,
-                collections_file,
-            }
-        } else {
-            let mut manager = CollectionManager {
-                collections: HashMap::new(),
-                collections_file,
-            };
-            manager.load_collections().ok();
-            manager
+    // Adds the collections in a project workspace's directory. They hide
+    // global ones of the same name, and new collections are stored there.
+    pub fn with_workspace(mut self, workspace: Workspace) -> Self {
+        self.workspace = Some(workspace);
+        if let Err(e) = self.load_workspace() {
+            warn!("Failed to load workspace collections: {:#}", e);
         }
+        self
+    }
+
+    pub fn in_workspace(&self, collection_name: &str) -> bool {
+        self.workspace_collections.contains(collection_name)
+    }
+
+    pub fn damaged(&self) -> Option<&DamagedFile> {
+        self.damaged.as_ref()
     }
 
     fn load_collections(&mut self) -> Result<()> {
-        if self.collections_file.exists() {
-            let file = File::open(&self.collections_file)?;
-            let reader = BufReader::new(file);
-            let collections: Vec<Collection> = serde_json::from_reader(reader).unwrap_or_default();
-            
-            // Convert to HashMap maintaining the order from the file
-            self.collections = collections.into_iter()
-                .map(|c| (c.info.name.clone(), c))
-                .collect();
+        self.damaged = None;
+        let loaded = match &self.collections_dir {
+            Some(dir) => load_directory(dir, None).map(|collections| (collections, VERSION)),
+            None if self.collections_file.exists() => read_json(&self.collections_file),
+            None => Ok((Vec::new(), VERSION)),
+        };
+        let (mut collections, from) = loaded.map_err(|damaged| self.mark_damaged(damaged))?;
+        if self.collections_dir.is_none() {
+            for collection in &mut collections {
+                resolve_specs(&mut collection.requests, &self.json_dir());
+            }
+        }
+        self.collections = collections.into_iter()
+            .map(|c| (c.info.name.clone(), c))
+            .collect();
+        self.load_workspace()?;
+
+        // Written in an older layout: keep the original and save it anew
+        if from < VERSION {
+            storage::keep_original(&self.collections_file, from)?;
+            self.save_all_collections()?;
         }
         Ok(())
     }
 
+    fn load_workspace(&mut self) -> Result<()> {
+        self.workspace_collections.clear();
+        self.shadowed.clear();
+        if let Some(workspace) = &self.workspace {
+            let collections = load_directory(&workspace.collections_dir(), Some(&workspace.root))
+                .map_err(|damaged| self.mark_damaged(damaged))?;
+            for collection in collections {
+                let name = collection.info.name.clone();
+                if let Some(global) = self.collections.insert(name.clone(), collection) {
+                    self.shadowed.insert(name.clone(), global);
+                }
+                self.workspace_collections.insert(name);
+            }
+        }
+        Ok(())
+    }
+
+    // Spec paths in collections.json are relative to the directory it's in
+    fn json_dir(&self) -> PathBuf {
+        self.collections_file.parent().map(Path::to_path_buf).unwrap_or_default()
+    }
+
+    fn mark_damaged(&mut self, damaged: DamagedFile) -> anyhow::Error {
+        self.damaged = Some(damaged.clone());
+        damaged.into()
+    }
+
     fn save_all_collections(&self) -> Result<()> {
+        if let Some(damaged) = &self.damaged {
+            return Err(damaged.clone().into());
+        }
+        let (workspace, global): (Vec<&Collection>, Vec<&Collection>) = self.collections.values()
+            .partition(|c| self.workspace_collections.contains(&c.info.name));
+        let global = global.into_iter().chain(self.shadowed.values());
+        if let Some(project) = &self.workspace {
+            save_directory(&project.collections_dir(), workspace.into_iter(), Some(&project.root))?;
+        }
+        if let Some(dir) = &self.collections_dir {
+            return save_directory(dir, global, None);
+        }
         // Sort collections by created_at in reverse order (newest first)
-        let mut collections: Vec<_> = self.collections.values().collect();
+        let mut collections: Vec<Collection> = global
+            .map(|collection| with_relative_specs(collection, &self.json_dir()))
+            .collect();
         collections.sort_by(|a, b| b.info.created_at.cmp(&a.info.created_at));
-        
-        let file = File::create(&self.collections_file)?;
-        let writer = BufWriter::new(file);
-        serde_json::to_writer_pretty(writer, &collections)?;
-        Ok(())
+        let collections = collections.iter().collect();
+
+        let json = serde_json::to_string_pretty(&CollectionsFile { version: VERSION, collections })?;
+        storage::save_with_backups(&self.collections_file, json.as_bytes())
+            .with_context(|| format!("could not write {}", self.collections_file.display()))
     }
 
     pub fn save_collection(&mut self, collection: &Collection) -> Result<()> {
@@ -88,9 +245,15 @@ impl CollectionManager {
                 created_at: Utc::now(),
             },
             requests: collection.requests.clone(),
+            variables: collection.variables.clone(),
+            source: collection.source.clone(),
         };
         
-        self.collections.insert(collection_with_timestamp.info.name.clone(), collection_with_timestamp);
+        let name = collection_with_timestamp.info.name.clone();
+        if self.workspace.is_some() && !self.collections.contains_key(&name) {
+            self.workspace_collections.insert(name.clone());
+        }
+        self.collections.insert(name, collection_with_timestamp);
         self.save_all_collections()
     }
 
@@ -104,22 +267,36 @@ impl CollectionManager {
 
     pub fn save_request(&mut self, collection_name: &str, request_name: &str, method: &str, 
                        url: &str, headers: &[(String, String)], body: &str) -> Result<()> {
-        if let Some(collection) = self.collections.get_mut(collection_name) {
-            // Convert headers Vec to HashMap
-            let headers: HashMap<String, String> = headers
-                .iter()
-                .map(|(k, v)| (k.clone(), v.clone()))
-                .collect();
+        // Convert headers Vec to HashMap
+        let headers: HashMap<String, String> = headers
+            .iter()
+            .map(|(k, v)| (k.clone(), v.clone()))
+            .collect();
 
-            collection.requests.push(CollectionItem::Request(SavedRequest {
-                name: request_name.to_string(),
-                request: Request {
-                    method: method.to_string(),
-                    url: url.to_string(),
-                    headers,
-                    body: Some(body.to_string()),
-                }
-            }));
+        self.add_request(collection_name, SavedRequest {
+            id: next_request_id(),
+            name: request_name.to_string(),
+            request: Request {
+                method: method.to_string(),
+                url: url.to_string(),
+                headers,
+                body: Some(body.to_string()),
+                options: RequestOptions::default(),
+                graphql: None,
+                grpc: None,
+            },
+            response_filter: None,
+            openapi: None,
+        })
+    }
+
+    pub fn add_request(&mut self, collection_name: &str, request: SavedRequest) -> Result<()> {
+        self.add_item(collection_name, CollectionItem::Request(Box::new(request)))
+    }
+
+    pub fn add_item(&mut self, collection_name: &str, item: CollectionItem) -> Result<()> {
+        if let Some(collection) = self.collections.get_mut(collection_name) {
+            collection.requests.push(item);
             self.save_all_collections()?;
             Ok(())
         } else {
@@ -127,19 +304,512 @@ impl CollectionManager {
         }
     }
 
+    pub fn set_request_filter(&mut self, collection_name: &str, request_id: u64,
+                              filter: Option<String>) -> Result<()> {
+        let request = self.collections.get_mut(collection_name)
+            .and_then(|c| c.find_request_mut(request_id))
+            .ok_or_else(|| anyhow::anyhow!("Request not found in {}", collection_name))?;
+        if request.response_filter != filter {
+            request.response_filter = filter;
+            self.save_all_collections()?;
+        }
+        Ok(())
+    }
+
+    // Sets a collection variable; an empty value removes it.
+    pub fn set_variable(&mut self, collection_name: &str, name: &str, value: &str) -> Result<()> {
+        let collection = self.collections.get_mut(collection_name)
+            .ok_or_else(|| anyhow::anyhow!("Collection not found: {}", collection_name))?;
+        if value.is_empty() {
+            collection.variables.remove(name);
+        } else {
+            collection.variables.insert(name.to_string(), value.to_string());
+        }
+        self.save_all_collections()
+    }
+
+    // Takes the requests and variables read from a collection's source file.
+    // Variables set here that the file doesn't define are kept.
+    pub fn update_from_source(&mut self, collection_name: &str, requests: Vec<CollectionItem>,
+                              variables: BTreeMap<String, String>) -> Result<()> {
+        let collection = self.collections.get_mut(collection_name)
+            .ok_or_else(|| anyhow::anyhow!("Collection not found: {}", collection_name))?;
+        collection.requests = requests;
+        collection.variables.extend(variables);
+        self.save_all_collections()
+    }
+
     pub fn reload_collections(&mut self) -> Result<()> {
         self.load_collections()
     }
 
     pub fn delete_collection(&mut self, name: &str) {
         self.collections.remove(name);
+        if self.workspace_collections.remove(name) {
+            if let Some(global) = self.shadowed.remove(name) {
+                self.collections.insert(name.to_string(), global);
+            }
+        }
         self.save_all_collections().ok();
     }
 }
 
+// Parses collections.json of any version. Also returns the version it had.
+pub fn parse_file(contents: &[u8]) -> std::result::Result<(Vec<Collection>, u32), String> {
+    let document: Value = serde_json::from_slice(contents).map_err(|e| e.to_string())?;
+    let (mut document, from) = SCHEMA.upgrade(document)?;
+    let collections = serde_json::from_value(document["collections"].take()).map_err(|e| e.to_string())?;
+    Ok((collections, from))
+}
+
+// Whether a backup of collections.json can be restored
+pub fn is_valid_file(contents: &[u8]) -> bool {
+    parse_file(contents).is_ok()
+}
+
+fn map_to_list(document: Value) -> Value {
+    match document {
+        Value::Object(by_name) => Value::Array(by_name.into_iter().map(|(_, collection)| collection).collect()),
+        document => document,
+    }
+}
+
+fn add_version(document: Value) -> Value {
+    json!({ "version": 2, "collections": document })
+}
+
+fn directory_version() -> u32 {
+    DIRECTORY_VERSION
+}
+
+fn read_json(path: &Path) -> std::result::Result<(Vec<Collection>, u32), DamagedFile> {
+    let contents = fs::read(path).map_err(|e| DamagedFile::new(path, e))?;
+    parse_file(&contents).map_err(|e| DamagedFile::new(path, e))
+}
+
+fn read_yaml<T: serde::de::DeserializeOwned>(path: &Path) -> std::result::Result<T, DamagedFile> {
+    let text = fs::read_to_string(path).map_err(|e| DamagedFile::new(path, e))?;
+    serde_yaml::from_str(&text).map_err(|e| DamagedFile::new(path, e))
+}
+
+// Spec paths are relative to `spec_base`, or to each collection's directory
+fn load_directory(dir: &Path, spec_base: Option<&Path>) -> std::result::Result<Vec<Collection>, DamagedFile> {
+    let mut collections = Vec::new();
+    if !dir.exists() {
+        return Ok(collections);
+    }
+    for entry in fs::read_dir(dir).map_err(|e| DamagedFile::new(dir, e))? {
+        let path = entry.map_err(|e| DamagedFile::new(dir, e))?.path();
+        let meta = path.join(COLLECTION_FILE);
+        if !meta.is_file() {
+            continue;
+        }
+        let file: CollectionFile = read_yaml(&meta)?;
+        if file.version > DIRECTORY_VERSION {
+            return Err(DamagedFile::new(&meta, format!(
+                "written by a newer raquet (format version {}, this one reads up to {})",
+                file.version, DIRECTORY_VERSION
+            )));
+        }
+        let mut requests = load_items(&path, &file.order)?;
+        resolve_specs(&mut requests, spec_base.unwrap_or(&path));
+        collections.push(Collection {
+            info: file.info,
+            requests,
+            variables: file.variables,
+            source: file.source,
+        });
+    }
+    Ok(collections)
+}
+
+// Items in the order their parent lists them, then any it doesn't list by
+// file name
+fn load_items(dir: &Path, order: &[String]) -> std::result::Result<Vec<CollectionItem>, DamagedFile> {
+    let mut paths: Vec<PathBuf> = fs::read_dir(dir).map_err(|e| DamagedFile::new(dir, e))?
+        .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+        .collect();
+    paths.sort();
+
+    let mut items = Vec::new();
+    for path in paths {
+        let file_name = path.file_name().map(|name| name.to_string_lossy().into_owned()).unwrap_or_default();
+        let item = if path.is_dir() {
+            let folder = path.join(FOLDER_FILE);
+            let folder: FolderFile = if folder.is_file() { read_yaml(&folder)? } else { FolderFile::default() };
+            // Folders written before names were kept are named by their directory
+            CollectionItem::Folder(Folder {
+                name: folder.name.unwrap_or_else(|| file_name.clone()),
+                item: load_items(&path, &folder.order)?,
+            })
+        } else if file_name != COLLECTION_FILE && file_name != FOLDER_FILE && file_name.ends_with(".yaml") {
+            match read_yaml(&path)? {
+                FileItem::Request(request) => CollectionItem::Request(request),
+                FileItem::WebSocket(websocket) => CollectionItem::WebSocket(websocket),
+            }
+        } else {
+            continue;
+        };
+        let position = order.iter().position(|name| *name == file_name).unwrap_or(usize::MAX);
+        items.push((position, item));
+    }
+    items.sort_by_key(|(position, _)| *position);
+    Ok(items.into_iter().map(|(_, item)| item).collect())
+}
+
+// Writes only the files whose contents changed, and removes the ones of
+// deleted requests and collections, so a commit shows just what was edited
+fn save_directory<'a>(dir: &Path, collections: impl Iterator<Item = &'a Collection>,
+                      spec_base: Option<&Path>) -> Result<()> {
+    let mut files: HashMap<PathBuf, String> = HashMap::new();
+    let mut used = HashSet::new();
+    fs::create_dir_all(dir).with_context(|| format!("could not create {}", dir.display()))?;
+    for collection in collections {
+        let collection_dir = dir.join(unique_file_name(&collection.info.name, "", &mut used));
+        let collection = with_relative_specs(collection, spec_base.unwrap_or(&collection_dir));
+        let meta = CollectionFile {
+            version: DIRECTORY_VERSION,
+            info: collection.info.clone(),
+            variables: collection.variables.clone(),
+            source: collection.source.clone(),
+            order: item_files(&collection_dir, &collection.requests, &mut files)?,
+        };
+        files.insert(collection_dir.join(COLLECTION_FILE), serde_yaml::to_string(&meta)?);
+    }
+
+    for (path, text) in &files {
+        if fs::read_to_string(path).ok().as_ref() != Some(text) {
+            if let Some(parent) = path.parent() {
+                fs::create_dir_all(parent)?;
+            }
+            storage::write_atomic(path, text.as_bytes())
+                .with_context(|| format!("could not write {}", path.display()))?;
+        }
+    }
+
+    // Only directories holding a collection.yaml are ours to clean up. Files
+    // that aren't YAML stay, and so does a deleted collection's directory
+    // while it holds any.
+    for entry in fs::read_dir(dir)?.flatten() {
+        let path = entry.path();
+        if path.join(COLLECTION_FILE).is_file() {
+            remove_stale(&path, &files)?;
+            if fs::read_dir(&path)?.next().is_none() {
+                fs::remove_dir(&path)?;
+            }
+        }
+    }
+    Ok(())
+}
+
+// Adds the files of `items` under `dir` and returns their names in order
+fn item_files(dir: &Path, items: &[CollectionItem], files: &mut HashMap<PathBuf, String>) -> Result<Vec<String>> {
+    let mut used = HashSet::from([COLLECTION_FILE.to_string(), FOLDER_FILE.to_string()]);
+    let mut order = Vec::new();
+    for item in items {
+        let (name, item) = match item {
+            CollectionItem::Folder(folder) => {
+                let folder_name = unique_file_name(&folder.name, "", &mut used);
+                let folder_dir = dir.join(&folder_name);
+                let folder_file = FolderFile {
+                    name: Some(folder.name.clone()),
+                    order: item_files(&folder_dir, &folder.item, files)?,
+                };
+                files.insert(folder_dir.join(FOLDER_FILE), serde_yaml::to_string(&folder_file)?);
+                order.push(folder_name);
+                continue;
+            }
+            CollectionItem::Request(request) => (&request.name, FileItem::Request(request.clone())),
+            CollectionItem::WebSocket(websocket) => (&websocket.name, FileItem::WebSocket(websocket.clone())),
+        };
+        let file_name = unique_file_name(name, ".yaml", &mut used);
+        files.insert(dir.join(&file_name), serde_yaml::to_string(&item)?);
+        order.push(file_name);
+    }
+    Ok(order)
+}
+
+// Removes YAML files and directories under `dir` that weren't just written
+fn remove_stale(dir: &Path, files: &HashMap<PathBuf, String>) -> Result<()> {
+    for entry in fs::read_dir(dir)?.flatten() {
+        let path = entry.path();
+        if path.is_dir() {
+            remove_stale(&path, files)?;
+            if fs::read_dir(&path)?.next().is_none() {
+                fs::remove_dir(&path)?;
+            }
+        } else if path.extension().is_some_and(|ext| ext == "yaml") && !files.contains_key(&path) {
+            fs::remove_file(&path)?;
+        }
+    }
+    Ok(())
+}
+
+// `name` with characters that aren't allowed in file names replaced, made
+// unique among `used` ignoring case, as some file systems do
+fn unique_file_name(name: &str, extension: &str, used: &mut HashSet<String>) -> String {
+    let cleaned: String = name.chars()
+        .map(|c| if c.is_control() || r#"/\:*?"<>|"#.contains(c) { '-' } else { c })
+        .collect();
+    let cleaned = cleaned.trim_matches(|c: char| c == '-' || c == '.' || c.is_whitespace());
+    let base = if cleaned.is_empty() { "unnamed" } else { cleaned };
+    let mut file_name = format!("{}{}", base, extension);
+    let mut n = 2;
+    while !used.insert(file_name.to_lowercase()) {
+        file_name = format!("{} ({}){}", base, n, extension);
+        n += 1;
+    }
+    file_name
+}
+
+// Imported requests link to their OpenAPI spec. The path is stored relative
+// to where the collection is kept, so a workspace can be committed or moved
+// together with its specs, and is made absolute again when read.
+fn with_relative_specs(collection: &Collection, base: &Path) -> Collection {
+    let mut collection = collection.clone();
+    for_each_link(&mut collection.requests, &mut |link| {
+        let spec = Path::new(&link.spec);
+        if spec.is_absolute() && base.is_absolute() {
+            link.spec = relative_path(spec, base).to_string_lossy().into_owned();
+        }
+    });
+    collection
+}
+
+fn resolve_specs(items: &mut [CollectionItem], base: &Path) {
+    for_each_link(items, &mut |link| {
+        if Path::new(&link.spec).is_relative() {
+            link.spec = normalize(&base.join(&link.spec)).to_string_lossy().into_owned();
+        }
+    });
+}
+
+fn for_each_link(items: &mut [CollectionItem], f: &mut impl FnMut(&mut OpenApiLink)) {
+    for item in items {
+        match item {
+            CollectionItem::Folder(folder) => for_each_link(&mut folder.item, f),
+            CollectionItem::Request(request) => {
+                if let Some(link) = &mut request.openapi {
+                    f(link);
+                }
+            }
+            CollectionItem::WebSocket(_) => {}
+        }
+    }
+}
+
+// `path` as seen from `base`, both absolute, e.g. `../specs/api.yaml`
+fn relative_path(path: &Path, base: &Path) -> PathBuf {
+    let (path, base) = (normalize(path), normalize(base));
+    let path: Vec<Component> = path.components().collect();
+    let base: Vec<Component> = base.components().collect();
+    let common = path.iter().zip(&base).take_while(|(a, b)| a == b).count();
+    // Nothing in common but the root, as for another Windows drive
+    if common == 0 {
+        return path.iter().collect();
+    }
+    let mut relative: PathBuf = base[common..].iter().map(|_| Component::ParentDir).collect();
+    relative.extend(&path[common..]);
+    relative
+}
+
+// Folds `.` and `..` without touching the file system
+fn normalize(path: &Path) -> PathBuf {
+    let mut normal = PathBuf::new();
+    for component in path.components() {
+        match component {
+            Component::CurDir => {}
+            Component::ParentDir if matches!(normal.components().next_back(), Some(Component::Normal(_))) => {
+                normal.pop();
+            }
+            component => normal.push(component),
+        }
+    }
+    normal
+}
+
 fn get_collections_dir() -> Result<PathBuf> {
     Ok(UserDirs::new()
         .context("Could not find user directory")?
         .home_dir()
         .join(".raquet"))
-} 
\ No newline at end of file
+} 
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn temp_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("raquet-collections-{}-{}", name, std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    fn request(name: &str) -> SavedRequest {
+        SavedRequest {
+            id: next_request_id(),
+            name: name.to_string(),
+            request: Request {
+                method: "GET".to_string(),
+                url: "{{baseUrl}}/pets/1".to_string(),
+                headers: HashMap::new(),
+                body: None,
+                options: RequestOptions::default(),
+                graphql: None,
+                grpc: None,
+            },
+            response_filter: None,
+            openapi: None,
+        }
+    }
+
+    fn linked_request(spec: &Path) -> CollectionItem {
+        CollectionItem::Request(Box::new(SavedRequest {
+            openapi: Some(OpenApiLink {
+                spec: spec.to_string_lossy().into_owned(),
+                method: "get".to_string(),
+                path: "/pets/{petId}".to_string(),
+            }),
+            ..request("Get a pet")
+        }))
+    }
+
+    fn folder(name: &str, item: Vec<CollectionItem>) -> CollectionItem {
+        CollectionItem::Folder(Folder { name: name.to_string(), item })
+    }
+
+    // Names of the items, folders with their contents in brackets
+    fn outline(items: &[CollectionItem]) -> String {
+        items.iter()
+            .map(|item| match item {
+                CollectionItem::Request(request) => request.name.clone(),
+                CollectionItem::WebSocket(websocket) => websocket.name.clone(),
+                CollectionItem::Folder(folder) => format!("{} [{}]", folder.name, outline(&folder.item)),
+            })
+            .collect::<Vec<_>>()
+            .join(", ")
+    }
+
+    fn collection(name: &str, requests: Vec<CollectionItem>) -> Collection {
+        Collection {
+            info: CollectionInfo { name: name.to_string(), description: String::new(), created_at: Utc::now() },
+            requests,
+            variables: BTreeMap::new(),
+            source: None,
+        }
+    }
+
+    fn spec_of(item: &CollectionItem) -> &str {
+        match item {
+            CollectionItem::Request(request) => &request.openapi.as_ref().unwrap().spec,
+            _ => panic!("not a request"),
+        }
+    }
+
+    #[test]
+    fn relative_paths() {
+        let relative = |path: &str, base: &str| relative_path(Path::new(path), Path::new(base));
+        assert_eq!(relative("/home/me/.raquet/specs/api.yaml", "/home/me/.raquet"), PathBuf::from("specs/api.yaml"));
+        assert_eq!(relative("/home/me/specs/api.yaml", "/home/me/.raquet"), PathBuf::from("../specs/api.yaml"));
+        assert_eq!(relative("/srv/api.yaml", "/home/me/./.raquet/../.raquet"), PathBuf::from("../../../srv/api.yaml"));
+        assert_eq!(normalize(Path::new("/a/b/../c/./d")), PathBuf::from("/a/c/d"));
+    }
+
+    #[test]
+    fn spec_paths_are_saved_relative_to_collections_json() {
+        let dir = temp_dir("json-specs");
+        let file = dir.join("collections.json");
+        let inside = dir.join("specs").join("pets.yaml");
+        let outside = std::env::temp_dir().join("elsewhere").join("pets.yaml");
+        let mut manager = CollectionManager::in_file(file.clone());
+        manager.save_collection(&collection("Pets", vec![linked_request(&inside), linked_request(&outside)])).unwrap();
+
+        let (saved, _) = parse_file(&fs::read(&file).unwrap()).unwrap();
+        let pets = saved.iter().find(|c| c.info.name == "Pets").unwrap();
+        assert_eq!(spec_of(&pets.requests[0]), Path::new("specs").join("pets.yaml").to_string_lossy());
+        assert!(spec_of(&pets.requests[1]).starts_with(".."));
+
+        // Absolute again once loaded, wherever the directory is now
+        let moved = temp_dir("json-specs-moved");
+        fs::rename(&file, moved.join("collections.json")).unwrap();
+        let manager = CollectionManager::in_file(moved.join("collections.json"));
+        let pets = manager.get_collection("Pets").unwrap();
+        assert_eq!(Path::new(spec_of(&pets.requests[0])), moved.join("specs").join("pets.yaml"));
+        assert_eq!(Path::new(spec_of(&pets.requests[1])), normalize(&outside));
+        fs::remove_dir_all(&dir).unwrap();
+        fs::remove_dir_all(&moved).unwrap();
+    }
+
+    #[test]
+    fn workspace_spec_paths_are_relative_to_the_project() {
+        let root = temp_dir("workspace-specs");
+        let workspace = Workspace { root: root.clone(), dir: root.join(".raquet") };
+        let spec = root.join("api").join("openapi.yaml");
+        let mut manager = CollectionManager::in_directory(root.join("global")).with_workspace(workspace.clone());
+        manager.save_collection(&collection("Pets", vec![CollectionItem::Folder(Folder {
+            name: "pets".to_string(),
+            item: vec![linked_request(&spec)],
+        })])).unwrap();
+        assert!(manager.in_workspace("Pets"));
+
+        let file = workspace.collections_dir().join("Pets").join("pets").join("Get a pet.yaml");
+        let FileItem::Request(saved) = read_yaml(&file).unwrap() else { panic!("not a request") };
+        assert_eq!(saved.openapi.unwrap().spec, Path::new("api").join("openapi.yaml").to_string_lossy());
+
+        let manager = CollectionManager::in_directory(root.join("global")).with_workspace(workspace);
+        let CollectionItem::Folder(folder) = &manager.get_collection("Pets").unwrap().requests[0] else { panic!("not a folder") };
+        assert_eq!(Path::new(spec_of(&folder.item[0])), spec);
+        fs::remove_dir_all(&root).unwrap();
+    }
+
+    #[test]
+    fn folders_keep_their_names_and_order() {
+        let dir = temp_dir("folders");
+        let items = vec![
+            CollectionItem::Request(Box::new(request("zeta"))),
+            folder("Orders / v2", vec![
+                folder("Drafts?", vec![CollectionItem::Request(Box::new(request("List")))]),
+                CollectionItem::Request(Box::new(request("List"))),
+            ]),
+            CollectionItem::Request(Box::new(request("Ping"))),
+            folder("orders - v2", vec![]),
+            CollectionItem::Request(Box::new(request("ping"))),
+        ];
+        let expected = "zeta, Orders / v2 [Drafts? [List], List], Ping, orders - v2 [], ping";
+        let mut manager = CollectionManager::in_directory(dir.clone());
+        manager.save_collection(&collection("Shop", items)).unwrap();
+
+        let shop = dir.join("Shop");
+        let names = |dir: &Path| {
+            let mut names: Vec<_> = fs::read_dir(dir).unwrap()
+                .map(|entry| entry.unwrap().file_name().to_string_lossy().into_owned())
+                .collect();
+            names.sort();
+            names
+        };
+        assert_eq!(names(&shop), [
+            "Orders - v2", "Ping.yaml", "collection.yaml", "orders - v2 (2)", "ping (2).yaml", "zeta.yaml",
+        ]);
+        assert_eq!(names(&shop.join("Orders - v2")), ["Drafts", "List.yaml", "folder.yaml"]);
+
+        let loaded = CollectionManager::in_directory(dir.clone());
+        assert_eq!(outline(&loaded.get_collection("Shop").unwrap().requests), expected);
+
+        // Saving what was loaded writes the same folders
+        let folder_file = shop.join("Orders - v2").join(FOLDER_FILE);
+        let before = fs::read_to_string(&folder_file).unwrap();
+        assert!(before.contains("name: Orders / v2"));
+        let mut manager = loaded;
+        manager.save_collection(&manager.get_collection("Shop").unwrap().clone()).unwrap();
+        assert_eq!(fs::read_to_string(&folder_file).unwrap(), before);
+        assert_eq!(names(&shop.join("Orders - v2")), ["Drafts", "List.yaml", "folder.yaml"]);
+        assert_eq!(outline(&CollectionManager::in_directory(dir.clone()).get_collection("Shop").unwrap().requests), expected);
+
+        // Without a name in folder.yaml the directory's is used
+        fs::write(shop.join("Orders - v2").join("Drafts").join(FOLDER_FILE), "order: []\n").unwrap();
+        let loaded = CollectionManager::in_directory(dir.clone());
+        assert_eq!(outline(&loaded.get_collection("Shop").unwrap().requests),
+                   expected.replace("Drafts?", "Drafts"));
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
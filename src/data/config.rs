@@ -4,12 +4,18 @@ use serde::{Deserialize, Serialize};
 use std::path::{Path, PathBuf};
 use std::collections::HashMap;
 use log::{debug, info, warn};
+use crate::models::collection::HttpVersion;
+use super::Workspace;
+use super::migrations::Schema;
+use super::storage::{self, DamagedFile};
 
 #[derive(Debug)]
 pub enum Error {
     Config(ConfigError),
     Io(std::io::Error),
     Toml(toml::ser::Error),
+    // A config file that can't be parsed; the defaults are used instead
+    Damaged(DamagedFile),
 }
 
 impl From<ConfigError> for Error {
@@ -30,12 +36,31 @@ impl From<toml::ser::Error> for Error {
     }
 }
 
+// Layout of config.toml: version 1 had no `version` and could have the
+// settings at the top level instead of under `[app]`
+pub const VERSION: u32 = 2;
+
+static SCHEMA: Schema<toml::Table> = Schema {
+    current: VERSION,
+    version: table_version,
+    steps: &[(1, settings_into_app)],
+};
+
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct AppConfig {
+    #[serde(default = "default_version")]
+    pub version: u32,
     #[serde(default)]
     pub app: AppSettings,
-    #[serde(default)]
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
     pub default_headers: HashMap<String, String>,
+    // The global config.toml and the workspace's as read. Settings the
+    // workspace sets are saved with their global values, so they never
+    // leak out of the project.
+    #[serde(skip)]
+    global: toml::Table,
+    #[serde(skip)]
+    overlay: toml::Table,
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
@@ -54,6 +79,49 @@ pub struct AppSettings {
     
     #[serde(default)]
     pub default_headers: HashMap<String, String>,
+
+    #[serde(default = "default_follow_redirects")]
+    pub follow_redirects: bool,
+
+    #[serde(default = "default_max_redirects")]
+    pub max_redirects: usize,
+
+    #[serde(default)]
+    pub http_version: HttpVersion,
+
+    #[serde(default)]
+    pub proxy: ProxySettings,
+
+    // Keep collections here, a directory each with a YAML file per request,
+    // instead of in ~/.raquet/collections.json
+    #[serde(default)]
+    pub collections_dir: String,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ProxySettings {
+    // http://, https://, socks5:// or socks5h:// (DNS through the proxy)
+    #[serde(default)]
+    pub url: String,
+
+    #[serde(default)]
+    pub username: String,
+
+    #[serde(default)]
+    pub password: String,
+
+    // Hosts reached directly: domains (matching subdomains too), IPs, CIDR ranges or "*",
+    // optionally with a port ("example.com:8080")
+    #[serde(default)]
+    pub no_proxy: Vec<String>,
+
+    // Fall back to HTTP_PROXY / HTTPS_PROXY / ALL_PROXY / NO_PROXY when no url is set
+    #[serde(default = "default_use_env_proxy")]
+    pub use_env: bool,
+}
+
+fn default_version() -> u32 {
+    VERSION
 }
 
 fn default_timeout() -> u64 {
@@ -68,11 +136,26 @@ fn default_history_size() -> usize {
     100
 }
 
+fn default_follow_redirects() -> bool {
+    true
+}
+
+fn default_max_redirects() -> usize {
+    10
+}
+
+fn default_use_env_proxy() -> bool {
+    true
+}
+
 impl Default for AppConfig {
     fn default() -> Self {
         Self {
+            version: VERSION,
             app: AppSettings::default(),
             default_headers: HashMap::new(),
+            global: toml::Table::new(),
+            overlay: toml::Table::new(),
         }
     }
 }
@@ -95,43 +178,79 @@ impl Default for AppSettings {
             history_size: default_history_size(),
             default_url: String::new(),
             default_headers,
+            follow_redirects: default_follow_redirects(),
+            max_redirects: default_max_redirects(),
+            http_version: HttpVersion::default(),
+            proxy: ProxySettings::default(),
+            collections_dir: String::new(),
+        }
+    }
+}
+
+impl Default for ProxySettings {
+    fn default() -> Self {
+        Self {
+            url: String::new(),
+            username: String::new(),
+            password: String::new(),
+            no_proxy: Vec::new(),
+            use_env: default_use_env_proxy(),
         }
     }
 }
 
 impl AppConfig {
-    pub fn load() -> Result<Self, Error> {
-        let config_path = get_config_path()?;
+    // The global config, with the workspace's config.toml (if any) over it
+    pub fn load(workspace: Option<&Workspace>) -> Result<Self, Error> {
+        Self::load_from(&get_config_path()?, workspace)
+    }
+
+    fn load_from(config_path: &Path, workspace: Option<&Workspace>) -> Result<Self, Error> {
         debug!("Loading config from: {:?}", config_path);
         
-        if config_path.exists() {
-            let content = std::fs::read_to_string(&config_path)?;
-            debug!("Config content:\n{}", content);
-            
-            match toml::from_str::<AppConfig>(&content) {
-                Ok(mut config) => {
-                    if !config.default_headers.is_empty() {
-                        config.app.default_headers.extend(config.default_headers.clone());
-                    }
-                    
-                    debug!("Default headers: {:?}", config.app.default_headers);
-                    if config.app.default_headers.is_empty() {
-                        warn!("No default headers found in config");
-                    }
-                    Ok(config)
-                }
-                Err(e) => {
-                    warn!("Error parsing config: {}", e);
-                    Ok(Self::default())
+        if !config_path.exists() {
+            info!("Config file not found, creating default");
+            Self::create_default_config(config_path)?;
+        }
+        let global = read_table(config_path)?;
+        let overlay = match workspace.map(Workspace::config_path).filter(|path| path.exists()) {
+            Some(path) => {
+                debug!("Loading workspace config from: {:?}", path);
+                read_table(&path)?
+            }
+            None => toml::Table::new(),
+        };
+        let mut table = global.clone();
+        merge(&mut table, overlay.clone());
+
+        match toml::Value::Table(table).try_into::<AppConfig>() {
+            Ok(mut config) => {
+                config.global = global;
+                config.overlay = overlay;
+                debug!("Default headers: {:?}", config.app.default_headers);
+                if config.app.default_headers.is_empty() {
+                    warn!("No default headers found in config");
                 }
+                Ok(config)
             }
-        } else {
-            info!("Config file not found, creating default");
-            Self::create_default_config(&config_path)?;
-            Ok(Self::default())
+            Err(e) => Err(Error::Damaged(DamagedFile::new(config_path, e))),
         }
     }
 
+    // Parses one config file, upgraded to the current layout. Also returns
+    // the version it had. The file itself is left as written, comments and all.
+    pub fn parse_table(content: &str) -> Result<(toml::Table, u32), String> {
+        let table: toml::Table = toml::from_str(content).map_err(|e| e.to_string())?;
+        let (table, from) = SCHEMA.upgrade(table)?;
+        toml::Value::Table(table.clone()).try_into::<AppConfig>().map_err(|e| e.to_string())?;
+        Ok((table, from))
+    }
+
+    // Whether a backup of a config file can be restored
+    pub fn is_valid_file(contents: &[u8]) -> bool {
+        std::str::from_utf8(contents).is_ok_and(|text| Self::parse_table(text).is_ok())
+    }
+
     fn create_default_config(path: &Path) -> Result<(), Error> {
         if let Some(parent) = path.parent() {
             std::fs::create_dir_all(parent)?;
@@ -139,18 +258,101 @@ impl AppConfig {
 
         let default_config = Self::default();
         let toml = toml::to_string_pretty(&default_config)?;
-        std::fs::write(path, toml)?;
+        storage::write_atomic(path, toml.as_bytes())?;
         Ok(())
     }
 
+    // Writes the global config only; the workspace's file is never touched
     pub fn save(&self) -> Result<(), Error> {
-        let config_path = get_config_path()?;
-        let toml = toml::to_string_pretty(&self)?;
-        std::fs::write(config_path, toml)?;
+        self.save_to(&get_config_path()?)
+    }
+
+    fn save_to(&self, config_path: &Path) -> Result<(), Error> {
+        let mut table = toml::Table::try_from(self)?;
+        unmerge(&mut table, &self.overlay, &self.global);
+        let toml = toml::to_string_pretty(&table)?;
+        storage::save_with_backups(config_path, toml.as_bytes())?;
         Ok(())
     }
 }
 
+// Each file has to be a valid config on its own
+fn read_table(path: &Path) -> Result<toml::Table, Error> {
+    let content = std::fs::read_to_string(path)?;
+    debug!("Config content:\n{}", content);
+    AppConfig::parse_table(&content)
+        .map(|(table, _)| fold_default_headers(table))
+        .map_err(|e| Error::Damaged(DamagedFile::new(path, e)))
+}
+
+// Top-level `[default_headers]` go in with `[app]`'s, over them, so a
+// workspace's are merged and left out of the global file like any setting
+fn fold_default_headers(mut table: toml::Table) -> toml::Table {
+    if let Some(toml::Value::Table(headers)) = table.remove("default_headers") {
+        if let toml::Value::Table(app) = table.entry("app").or_insert_with(|| toml::Table::new().into()) {
+            if let toml::Value::Table(existing) = app.entry("default_headers").or_insert_with(|| toml::Table::new().into()) {
+                existing.extend(headers);
+            }
+        }
+    }
+    table
+}
+
+fn table_version(table: &toml::Table) -> u32 {
+    table.get("version").and_then(toml::Value::as_integer).unwrap_or(1) as u32
+}
+
+fn settings_into_app(mut table: toml::Table) -> toml::Table {
+    let settings: Vec<String> = table.keys()
+        .filter(|key| !["app", "default_headers", "version"].contains(&key.as_str()))
+        .cloned()
+        .collect();
+    let moved: Vec<(String, toml::Value)> = settings.into_iter()
+        .filter_map(|key| table.remove(&key).map(|value| (key, value)))
+        .collect();
+    if !moved.is_empty() {
+        if let toml::Value::Table(app) = table.entry("app").or_insert_with(|| toml::Table::new().into()) {
+            // `[app]` wins where both have a setting
+            for (key, value) in moved {
+                app.entry(key).or_insert(value);
+            }
+        }
+    }
+    table.insert("version".to_string(), toml::Value::Integer(2));
+    table
+}
+
+// Values in `overlay` win; tables are merged key by key
+fn merge(base: &mut toml::Table, overlay: toml::Table) {
+    for (key, value) in overlay {
+        match (base.get_mut(&key), value) {
+            (Some(toml::Value::Table(base)), toml::Value::Table(overlay)) => merge(base, overlay),
+            (Some(existing), value) => *existing = value,
+            (None, value) => {
+                base.insert(key, value);
+            }
+        }
+    }
+}
+
+// Undoes `merge` for the keys `overlay` set, restoring what `global` had
+fn unmerge(table: &mut toml::Table, overlay: &toml::Table, global: &toml::Table) {
+    let empty = toml::Table::new();
+    for (key, value) in overlay {
+        match (table.get_mut(key), value, global.get(key)) {
+            (Some(toml::Value::Table(table)), toml::Value::Table(overlay), None | Some(toml::Value::Table(_))) => {
+                unmerge(table, overlay, global.get(key).and_then(toml::Value::as_table).unwrap_or(&empty))
+            }
+            (_, _, Some(global)) => {
+                table.insert(key.clone(), global.clone());
+            }
+            (_, _, None) => {
+                table.remove(key);
+            }
+        }
+    }
+}
+
 fn get_config_path() -> Result<PathBuf, Error> {
     UserDirs::new()
         .ok_or_else(|| {
@@ -159,4 +361,95 @@ fn get_config_path() -> Result<PathBuf, Error> {
             ))
         })
         .map(|dirs| dirs.home_dir().join(".raquet").join("config.toml"))
-} 
\ No newline at end of file
+} 
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn temp_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("raquet-config-{}-{}", name, std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    const GLOBAL: &str = r#"
+version = 2
+
+[app]
+timeout_seconds = 10
+history_size = 50
+
+[app.proxy]
+url = "http://proxy.local:3128"
+
+[default_headers]
+X-Global = "1"
+"#;
+
+    const WORKSPACE: &str = r#"
+[app]
+timeout_seconds = 99
+
+[app.proxy]
+no_proxy = ["localhost"]
+
+[default_headers]
+X-Project = "2"
+X-Global = "project"
+"#;
+
+    // A global config at `dir/config.toml` and a workspace in `dir/project`
+    fn files(dir: &Path) -> (PathBuf, Workspace) {
+        let workspace = Workspace { root: dir.join("project"), dir: dir.join("project").join(".raquet") };
+        std::fs::create_dir_all(&workspace.dir).unwrap();
+        std::fs::write(dir.join("config.toml"), GLOBAL).unwrap();
+        std::fs::write(workspace.config_path(), WORKSPACE).unwrap();
+        (dir.join("config.toml"), workspace)
+    }
+
+    #[test]
+    fn workspace_settings_win() {
+        let dir = temp_dir("merge");
+        let (global, workspace) = files(&dir);
+        let config = AppConfig::load_from(&global, Some(&workspace)).unwrap();
+        assert_eq!(config.app.timeout_seconds, 99);
+        assert_eq!(config.app.history_size, 50);
+        // Tables are merged key by key
+        assert_eq!(config.app.proxy.url, "http://proxy.local:3128");
+        assert_eq!(config.app.proxy.no_proxy, ["localhost"]);
+        assert_eq!(config.app.default_headers.get("X-Project").map(String::as_str), Some("2"));
+        assert_eq!(config.app.default_headers.get("X-Global").map(String::as_str), Some("project"));
+
+        let config = AppConfig::load_from(&global, None).unwrap();
+        assert_eq!(config.app.timeout_seconds, 10);
+        assert!(config.app.proxy.no_proxy.is_empty());
+        assert_eq!(config.app.default_headers.get("X-Global").map(String::as_str), Some("1"));
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn saving_leaves_workspace_settings_out() {
+        let dir = temp_dir("save");
+        let (global, workspace) = files(&dir);
+        let mut config = AppConfig::load_from(&global, Some(&workspace)).unwrap();
+        config.app.history_size = 75;
+        config.app.proxy.username = "me".to_string();
+        config.save_to(&global).unwrap();
+
+        assert_eq!(std::fs::read_to_string(workspace.config_path()).unwrap(), WORKSPACE);
+        let saved = AppConfig::load_from(&global, None).unwrap();
+        assert_eq!(saved.app.history_size, 75);
+        assert_eq!(saved.app.proxy.username, "me");
+        assert_eq!(saved.app.timeout_seconds, 10);
+        assert!(saved.app.proxy.no_proxy.is_empty());
+        assert_eq!(saved.app.default_headers.get("X-Global").map(String::as_str), Some("1"));
+        assert!(!saved.app.default_headers.contains_key("X-Project"));
+
+        // And the workspace still applies on top
+        let merged = AppConfig::load_from(&global, Some(&workspace)).unwrap();
+        assert_eq!((merged.app.timeout_seconds, merged.app.history_size), (99, 75));
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
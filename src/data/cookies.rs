@@ -0,0 +1,170 @@
+use std::cmp::Reverse;
+use std::path::PathBuf;
+use std::sync::Mutex;
+use anyhow::Result;
+use log::{debug, error};
+use reqwest::header::HeaderValue;
+use reqwest::Url;
+use crate::models::Cookie;
+use super::storage::{self, DamagedFile};
+
+// Cookies received from servers, persisted to ~/.raquet/cookies.json and sent
+// back on later requests. The jar is shared with reqwest as its cookie
+// provider, so cookies set during redirects are kept as well.
+pub struct CookieJar {
+    cookies_file: PathBuf,
+    cookies: Mutex<Vec<Cookie>>,
+    // Set when cookies.json couldn't be read; it isn't saved until it's dealt with
+    damaged: Mutex<Option<DamagedFile>>,
+}
+
+impl CookieJar {
+    pub fn new() -> Self {
+        let cookies_file = dirs::home_dir()
+            .map(|h| h.join(".raquet").join("cookies.json"))
+            .unwrap_or_default();
+
+        let jar = CookieJar {
+            cookies_file,
+            cookies: Mutex::new(Vec::new()),
+            damaged: Mutex::new(None),
+        };
+        jar.reload();
+        jar
+    }
+
+    // Reads cookies.json again. A file that can't be parsed leaves the jar
+    // empty and is kept as it is until it's restored or set aside.
+    pub fn reload(&self) {
+        let mut cookies = self.cookies.lock().unwrap();
+        let mut damaged = self.damaged.lock().unwrap();
+        cookies.clear();
+        *damaged = None;
+        if !self.cookies_file.exists() {
+            return;
+        }
+        match std::fs::read(&self.cookies_file).map_err(|e| e.to_string()).and_then(|contents| parse_file(&contents)) {
+            Ok(mut stored) => {
+                // Session cookies don't survive a restart
+                stored.retain(|c| c.expires.is_some() && !c.is_expired());
+                *cookies = stored;
+            }
+            Err(e) => *damaged = Some(DamagedFile::new(&self.cookies_file, e)),
+        }
+    }
+
+    pub fn damaged(&self) -> Option<DamagedFile> {
+        self.damaged.lock().unwrap().clone()
+    }
+
+    // All stored cookies, ordered by domain, path and name.
+    pub fn cookies(&self) -> Vec<Cookie> {
+        let mut cookies = self.cookies.lock().unwrap().clone();
+        cookies.retain(|c| !c.is_expired());
+        cookies.sort_by(|a, b| (&a.domain, &a.path, &a.name).cmp(&(&b.domain, &b.path, &b.name)));
+        cookies
+    }
+
+    // Adds or replaces a cookie. An already expired cookie deletes the stored one.
+    pub fn store(&self, cookie: Cookie) {
+        let mut cookies = self.cookies.lock().unwrap();
+        Self::upsert(&mut cookies, cookie);
+        self.save(&cookies);
+    }
+
+    pub fn set_value(&self, cookie: &Cookie, value: String) {
+        let mut cookies = self.cookies.lock().unwrap();
+        if let Some(stored) = cookies.iter_mut().find(|c| c.same_identity(cookie)) {
+            stored.value = value;
+        }
+        self.save(&cookies);
+    }
+
+    pub fn remove(&self, cookie: &Cookie) {
+        let mut cookies = self.cookies.lock().unwrap();
+        cookies.retain(|c| !c.same_identity(cookie));
+        self.save(&cookies);
+    }
+
+    pub fn clear(&self) {
+        let mut cookies = self.cookies.lock().unwrap();
+        cookies.clear();
+        self.save(&cookies);
+    }
+
+    fn upsert(cookies: &mut Vec<Cookie>, cookie: Cookie) {
+        cookies.retain(|c| !c.same_identity(&cookie));
+        if !cookie.is_expired() {
+            cookies.push(cookie);
+        }
+    }
+
+    fn save(&self, cookies: &[Cookie]) {
+        if let Err(e) = self.write_file(cookies) {
+            error!("Failed to save cookies: {}", e);
+        }
+    }
+
+    fn write_file(&self, cookies: &[Cookie]) -> Result<()> {
+        if let Some(damaged) = self.damaged() {
+            return Err(damaged.into());
+        }
+        if let Some(parent) = self.cookies_file.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        let persistent: Vec<&Cookie> = cookies.iter().filter(|c| c.expires.is_some()).collect();
+        storage::save_with_backups(&self.cookies_file, serde_json::to_string_pretty(&persistent)?.as_bytes())?;
+        Ok(())
+    }
+}
+
+fn parse_file(contents: &[u8]) -> std::result::Result<Vec<Cookie>, String> {
+    serde_json::from_slice(contents).map_err(|e| e.to_string())
+}
+
+// Whether a backup of cookies.json can be restored
+pub fn is_valid_file(contents: &[u8]) -> bool {
+    parse_file(contents).is_ok()
+}
+
+impl Default for CookieJar {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl reqwest::cookie::CookieStore for CookieJar {
+    fn set_cookies(&self, cookie_headers: &mut dyn Iterator<Item = &HeaderValue>, url: &Url) {
+        let mut cookies = self.cookies.lock().unwrap();
+        let mut changed = false;
+        for header in cookie_headers {
+            match header.to_str().ok().and_then(|h| Cookie::parse(h, url)) {
+                Some(cookie) => {
+                    debug!("Storing cookie {} for {}{}", cookie.name, cookie.domain, cookie.path);
+                    Self::upsert(&mut cookies, cookie);
+                    changed = true;
+                }
+                None => debug!("Ignoring Set-Cookie from {}: {:?}", url, header),
+            }
+        }
+        if changed {
+            self.save(&cookies);
+        }
+    }
+
+    fn cookies(&self, url: &Url) -> Option<HeaderValue> {
+        let cookies = self.cookies.lock().unwrap();
+        let mut matching: Vec<&Cookie> = cookies.iter().filter(|c| c.matches(url)).collect();
+        if matching.is_empty() {
+            return None;
+        }
+        // More specific paths first
+        matching.sort_by_key(|c| Reverse(c.path.len()));
+        let header = matching
+            .iter()
+            .map(|c| format!("{}={}", c.name, c.value))
+            .collect::<Vec<_>>()
+            .join("; ");
+        HeaderValue::from_str(&header).ok()
+    }
+}
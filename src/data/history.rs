@@ -1,25 +1,48 @@
 use crate::app::HttpMethod;
+use crate::models::collection::next_request_id;
+use crate::models::Timings;
+use base64::engine::general_purpose::STANDARD as BASE64;
+use base64::Engine as _;
 use chrono::{DateTime, Utc};
 use directories::UserDirs;
 use serde::{Deserialize, Serialize};
 use std::{
-    fs::{self, File},
-    io::{self, BufReader, BufWriter},
+    collections::HashSet,
+    fs,
+    io,
     path::PathBuf,
 };
+use log::error;
+use serde_json::{json, Value};
+use super::migrations::{self, Schema};
+use super::storage::{self, DamagedFile};
 
-#[derive(Debug, Serialize, Deserialize, PartialEq, Clone)]
+#[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct HistoryEntry {
+    // Tells entries apart while raquet runs, as imported ones may share a
+    // timestamp. Not saved; every load hands out new ones.
+    #[serde(skip, default = "next_request_id")]
+    pub id: u64,
     pub timestamp: DateTime<Utc>,
     pub request: RequestData,
     pub response: Option<ResponseData>,
 }
 
+// The same exchange, whatever id it was given
+impl PartialEq for HistoryEntry {
+    fn eq(&self, other: &Self) -> bool {
+        self.timestamp == other.timestamp && self.request == other.request && self.response == other.response
+    }
+}
+
 #[derive(Debug, Serialize, Deserialize, PartialEq, Clone)]
 pub struct RequestData {
     pub url: String,
     pub method: String,
     pub headers: std::collections::HashMap<String, String>,
+    // Headers switched off in the editor: not sent, but restored with the request
+    #[serde(default, skip_serializing_if = "std::collections::HashMap::is_empty")]
+    pub disabled_headers: std::collections::HashMap<String, String>,
     pub body: Option<String>,
 }
 
@@ -27,10 +50,43 @@ pub struct RequestData {
 pub struct ResponseData {
     pub status: Option<u16>,
     pub status_text: Option<String>,
-    pub headers: std::collections::HashMap<String, String>,
+    // In the order received; repeated headers like Set-Cookie once per value
+    pub headers: Vec<(String, String)>,
     pub body: String,
+    // "base64" when `body` is a binary body encoded as base64, as in HAR
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub encoding: Option<String>,
     pub time_ms: u128,
     pub size_bytes: usize,
+    // Negotiated protocol, e.g. "HTTP/2.0"
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub http_version: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub timings: Option<Timings>,
+}
+
+impl ResponseData {
+    pub fn is_binary(&self) -> bool {
+        self.encoding.as_deref() == Some("base64")
+    }
+
+    // The body as received, None for a base64 body that doesn't decode
+    pub fn body_bytes(&self) -> Option<Vec<u8>> {
+        if self.is_binary() {
+            BASE64.decode(&self.body).ok()
+        } else {
+            Some(self.body.clone().into_bytes())
+        }
+    }
+}
+
+// Body text, or base64 and its encoding for a binary body
+pub fn encode_body(bytes: &[u8], is_binary: bool) -> (String, Option<String>) {
+    if is_binary {
+        (BASE64.encode(bytes), Some("base64".to_string()))
+    } else {
+        (String::from_utf8_lossy(bytes).into_owned(), None)
+    }
 }
 
 #[derive(Debug)]
@@ -38,6 +94,8 @@ pub enum HistoryError {
     Io(io::Error),
     Json(serde_json::Error),
     NoUserDir,
+    // history.json couldn't be read and isn't written over
+    Damaged(DamagedFile),
 }
 
 impl From<io::Error> for HistoryError {
@@ -52,29 +110,76 @@ impl From<serde_json::Error> for HistoryError {
     }
 }
 
+// Layout of history.json: version 1 was a bare list, 2 kept response
+// headers in a map
+pub const VERSION: u32 = 3;
+
+static SCHEMA: Schema<Value> = Schema {
+    current: VERSION,
+    version: migrations::json_version,
+    steps: &[(1, add_version), (2, headers_as_list)],
+};
+
+#[derive(Serialize)]
+struct HistoryFile<'a> {
+    version: u32,
+    entries: &'a [HistoryEntry],
+}
+
 #[derive(Clone)]
 pub struct History {
     entries: Vec<HistoryEntry>,
     max_entries: usize,
     file_path: PathBuf,
+    damaged: Option<DamagedFile>,
 }
 
 impl History {
     pub fn new(max_entries: usize) -> Result<Self, HistoryError> {
-        let file_path = get_history_path()?;
-        let entries = if file_path.exists() {
-            let file = File::open(&file_path)?;
-            let reader = BufReader::new(file);
-            serde_json::from_reader(reader).unwrap_or_default()
-        } else {
-            Vec::new()
+        let mut history = Self {
+            file_path: get_history_path()?,
+            ..Self::in_memory(max_entries)
         };
+        history.reload();
+        Ok(history)
+    }
 
-        Ok(Self {
-            entries,
+    // History that's never written, for when there's nowhere to keep it
+    pub fn in_memory(max_entries: usize) -> Self {
+        Self {
+            entries: Vec::new(),
             max_entries,
-            file_path,
-        })
+            file_path: PathBuf::new(),
+            damaged: None,
+        }
+    }
+
+    // Reads history.json again. A file that can't be parsed leaves history
+    // empty and is kept as it is until it's restored or set aside.
+    pub fn reload(&mut self) {
+        self.entries.clear();
+        self.damaged = None;
+        if !self.file_path.exists() {
+            return;
+        }
+        match fs::read(&self.file_path).map_err(|e| e.to_string()).and_then(|contents| parse_file(&contents)) {
+            Ok((entries, from)) => {
+                self.entries = entries;
+                // Written in an older layout: keep the original and save it anew
+                if from < VERSION {
+                    let upgraded = storage::keep_original(&self.file_path, from).map_err(HistoryError::from)
+                        .and_then(|_| self.save());
+                    if let Err(e) = upgraded {
+                        error!("Failed to upgrade history: {:?}", e);
+                    }
+                }
+            }
+            Err(e) => self.damaged = Some(DamagedFile::new(&self.file_path, e)),
+        }
+    }
+
+    pub fn damaged(&self) -> Option<&DamagedFile> {
+        self.damaged.as_ref()
     }
 
     pub fn add_entry(
@@ -82,15 +187,18 @@ impl History {
         url: String,
         method: HttpMethod,
         headers: std::collections::HashMap<String, String>,
+        disabled_headers: std::collections::HashMap<String, String>,
         body: Option<String>,
         response: Option<ResponseData>,
     ) -> Result<(), HistoryError> {
         let entry = HistoryEntry {
+            id: next_request_id(),
             timestamp: Utc::now(),
             request: RequestData {
                 url,
                 method: method.as_str().to_string(),
                 headers,
+                disabled_headers,
                 body,
             },
             response,
@@ -106,25 +214,111 @@ impl History {
         self.save()
     }
 
+    // Adds entries recorded elsewhere, keeping the list in time order
+    pub fn import_entries(&mut self, entries: Vec<HistoryEntry>) -> Result<(), HistoryError> {
+        self.entries.extend(entries);
+        self.entries.sort_by_key(|entry| entry.timestamp);
+        let excess = self.entries.len().saturating_sub(self.max_entries);
+        self.entries.drain(..excess);
+        self.save()
+    }
+
+    // Removes the entries with these ids, returning how many went
+    pub fn remove_entries(&mut self, ids: &HashSet<u64>) -> Result<usize, HistoryError> {
+        let before = self.entries.len();
+        self.entries.retain(|entry| !ids.contains(&entry.id));
+        self.save()?;
+        Ok(before - self.entries.len())
+    }
+
     pub fn get_entries(&self) -> &[HistoryEntry] {
         &self.entries
     }
 
     fn save(&self) -> Result<(), HistoryError> {
-        // Create directory if it doesn't exist
-        if let Some(parent) = self.file_path.parent() {
-            fs::create_dir_all(parent)?;
+        if let Some(damaged) = &self.damaged {
+            return Err(HistoryError::Damaged(damaged.clone()));
         }
-
-        let file = File::create(&self.file_path)?;
-        let writer = BufWriter::new(file);
-        serde_json::to_writer_pretty(writer, &self.entries)?;
+        if self.file_path.as_os_str().is_empty() {
+            return Ok(());
+        }
+        let json = serde_json::to_vec_pretty(&HistoryFile { version: VERSION, entries: &self.entries })?;
+        storage::save_with_backups(&self.file_path, &json)?;
         Ok(())
     }
 }
 
+// Parses history.json of any version. Also returns the version it had.
+pub fn parse_file(contents: &[u8]) -> Result<(Vec<HistoryEntry>, u32), String> {
+    let document: Value = serde_json::from_slice(contents).map_err(|e| e.to_string())?;
+    let (mut document, from) = SCHEMA.upgrade(document)?;
+    let entries = serde_json::from_value(document["entries"].take()).map_err(|e| e.to_string())?;
+    Ok((entries, from))
+}
+
+// Whether a backup of history.json can be restored
+pub fn is_valid_file(contents: &[u8]) -> bool {
+    parse_file(contents).is_ok()
+}
+
+fn add_version(document: Value) -> Value {
+    json!({ "version": 2, "entries": document })
+}
+
+fn headers_as_list(mut document: Value) -> Value {
+    for entry in document["entries"].as_array_mut().into_iter().flatten() {
+        if let Some(Value::Object(headers)) = entry.pointer_mut("/response/headers") {
+            let list = std::mem::take(headers).into_iter().map(|(name, value)| json!([name, value])).collect();
+            entry["response"]["headers"] = Value::Array(list);
+        }
+    }
+    document["version"] = json!(3);
+    document
+}
+
 fn get_history_path() -> Result<PathBuf, HistoryError> {
     UserDirs::new()
         .ok_or(HistoryError::NoUserDir)
         .map(|dirs| dirs.home_dir().join(".raquet").join("history.json"))
-} 
\ No newline at end of file
+} 
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(url: &str) -> HistoryEntry {
+        HistoryEntry {
+            id: next_request_id(),
+            timestamp: DateTime::parse_from_rfc3339("2026-10-18T09:30:00Z").unwrap().with_timezone(&Utc),
+            request: RequestData {
+                url: url.to_string(),
+                method: "GET".to_string(),
+                headers: std::collections::HashMap::new(),
+                disabled_headers: std::collections::HashMap::new(),
+                body: None,
+            },
+            response: None,
+        }
+    }
+
+    #[test]
+    fn entries_at_the_same_time_are_removed_one_by_one() {
+        let mut history = History::in_memory(10);
+        history.import_entries(vec![entry("https://a.example/"), entry("https://b.example/"), entry("https://c.example/")]).unwrap();
+        let ids: Vec<u64> = history.get_entries().iter().map(|entry| entry.id).collect();
+        assert_eq!(ids.iter().collect::<HashSet<_>>().len(), 3);
+
+        assert_eq!(history.remove_entries(&HashSet::from([ids[1]])).unwrap(), 1);
+        let urls: Vec<&str> = history.get_entries().iter().map(|entry| entry.request.url.as_str()).collect();
+        assert_eq!(urls, ["https://a.example/", "https://c.example/"]);
+        assert_eq!(history.remove_entries(&HashSet::from([ids[1]])).unwrap(), 0);
+    }
+
+    #[test]
+    fn ids_are_not_saved() {
+        let json = serde_json::to_value(entry("https://a.example/")).unwrap();
+        assert!(json.get("id").is_none());
+        let (entries, _) = parse_file(json!({ "version": VERSION, "entries": [json.clone(), json] }).to_string().as_bytes()).unwrap();
+        assert_ne!(entries[0].id, entries[1].id);
+        assert_eq!(entries[0], entries[1]);
+    }
+}
@@ -0,0 +1,43 @@
+use serde_json::Value;
+
+// Every stored file records the version of its layout. Files written by an
+// older raquet are brought up to date step by step when they're read; ones
+// from a newer raquet are refused rather than misread and overwritten.
+// `(n, step)` upgrades a version n document to version n + 1
+pub type Step<T> = (u32, fn(T) -> T);
+
+pub struct Schema<T: 'static> {
+    pub current: u32,
+    // The version a document was written with
+    pub version: fn(&T) -> u32,
+    pub steps: &'static [Step<T>],
+}
+
+impl<T> Schema<T> {
+    // The document in the current layout, and the version it had
+    pub fn upgrade(&self, mut document: T) -> Result<(T, u32), String> {
+        let from = (self.version)(&document);
+        if from > self.current {
+            return Err(format!(
+                "written by a newer raquet (format version {}, this one reads up to {})",
+                from, self.current
+            ));
+        }
+        for (version, step) in self.steps {
+            if *version >= from {
+                document = step(document);
+            }
+        }
+        Ok((document, from))
+    }
+}
+
+// JSON files started out as a bare list (version 1) and are now an object
+// with a `version`. An object without one predates both.
+pub fn json_version(document: &Value) -> u32 {
+    match document {
+        Value::Array(_) => 1,
+        Value::Object(object) => object.get("version").and_then(Value::as_u64).unwrap_or(0) as u32,
+        _ => 0,
+    }
+}
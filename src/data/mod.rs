@@ -1,7 +1,13 @@
 mod config;
-mod history;
+pub mod history;
+pub mod cookies;
 pub mod collections;
+mod workspace;
+pub mod storage;
+pub mod migrations;
 
-pub use config::AppConfig;
+pub use config::{AppConfig, ProxySettings, Error as ConfigError};
 pub use history::History;
-pub use collections::CollectionManager; 
\ No newline at end of file
+pub use cookies::CookieJar;
+pub use collections::CollectionManager;
+pub use workspace::Workspace;
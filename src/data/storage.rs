@@ -0,0 +1,197 @@
+use std::fmt;
+use std::fs::{self, File};
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+
+// Previous versions kept next to a file, `name.1` being the newest
+const BACKUPS: usize = 3;
+
+// A file that couldn't be read. Nothing is written over it until it's
+// restored from a backup or set aside.
+#[derive(Debug, Clone)]
+pub struct DamagedFile {
+    pub path: PathBuf,
+    pub error: String,
+}
+
+impl DamagedFile {
+    pub fn new(path: &Path, error: impl fmt::Display) -> Self {
+        DamagedFile { path: path.to_path_buf(), error: error.to_string() }
+    }
+}
+
+impl fmt::Display for DamagedFile {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{} could not be read ({}), not overwriting it", self.path.display(), self.error)
+    }
+}
+
+impl std::error::Error for DamagedFile {}
+
+// Replaces `path` without ever leaving it half written: the contents go to a
+// temporary file next to it, which is synced to disk and renamed over it.
+pub fn write_atomic(path: &Path, contents: &[u8]) -> io::Result<()> {
+    let dir = path.parent().filter(|dir| !dir.as_os_str().is_empty()).unwrap_or(Path::new("."));
+    fs::create_dir_all(dir)?;
+    let temp = dir.join(format!(".{}.tmp", file_name(path)));
+    let written = File::create(&temp)
+        .and_then(|mut file| {
+            file.write_all(contents)?;
+            file.sync_all()
+        })
+        .and_then(|()| fs::rename(&temp, path));
+    if written.is_err() {
+        fs::remove_file(&temp).ok();
+    }
+    written?;
+    // The rename only survives a crash once the directory is synced too
+    #[cfg(unix)]
+    File::open(dir).and_then(|dir| dir.sync_all()).ok();
+    Ok(())
+}
+
+// `write_atomic`, first rotating the current contents into the backups
+pub fn save_with_backups(path: &Path, contents: &[u8]) -> io::Result<()> {
+    match fs::read(path) {
+        Ok(current) if current == contents => return Ok(()),
+        Ok(_) => {
+            for n in (1..BACKUPS).rev() {
+                let backup = backup_path(path, n);
+                if backup.exists() {
+                    fs::rename(&backup, backup_path(path, n + 1))?;
+                }
+            }
+            fs::copy(path, backup_path(path, 1))?;
+        }
+        Err(_) => {}
+    }
+    write_atomic(path, contents)
+}
+
+// Backups of `path`, newest first
+pub fn backups(path: &Path) -> Vec<PathBuf> {
+    (1..=BACKUPS).map(|n| backup_path(path, n)).filter(|backup| backup.exists()).collect()
+}
+
+// Puts back the newest backup that `valid` accepts; None when there's none
+pub fn restore_backup(path: &Path, valid: impl Fn(&[u8]) -> bool) -> io::Result<Option<PathBuf>> {
+    for backup in backups(path) {
+        let contents = fs::read(&backup)?;
+        if valid(&contents) {
+            write_atomic(path, &contents)?;
+            return Ok(Some(backup));
+        }
+    }
+    Ok(None)
+}
+
+// Keeps a file as it was before it's upgraded to a newer layout, as
+// `name.v<version>`
+pub fn keep_original(path: &Path, version: u32) -> io::Result<PathBuf> {
+    let target = path.with_file_name(format!("{}.v{}", file_name(path), version));
+    if !target.exists() {
+        fs::copy(path, &target)?;
+    }
+    Ok(target)
+}
+
+// Moves a damaged file out of the way, keeping it to be repaired by hand
+pub fn set_aside(path: &Path) -> io::Result<PathBuf> {
+    let mut target = path.with_file_name(format!("{}.damaged", file_name(path)));
+    let mut n = 2;
+    while target.exists() {
+        target = path.with_file_name(format!("{}.damaged{}", file_name(path), n));
+        n += 1;
+    }
+    fs::rename(path, &target)?;
+    Ok(target)
+}
+
+fn backup_path(path: &Path, n: usize) -> PathBuf {
+    path.with_file_name(format!("{}.{}", file_name(path), n))
+}
+
+fn file_name(path: &Path) -> String {
+    path.file_name().map(|name| name.to_string_lossy().into_owned()).unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn temp_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("raquet-storage-{}-{}", name, std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    fn read(path: &Path) -> String {
+        fs::read_to_string(path).unwrap()
+    }
+
+    fn names(dir: &Path) -> Vec<String> {
+        let mut names: Vec<_> = fs::read_dir(dir).unwrap()
+            .map(|entry| entry.unwrap().file_name().to_string_lossy().into_owned())
+            .collect();
+        names.sort();
+        names
+    }
+
+    #[test]
+    fn write_atomic_replaces_the_file() {
+        let dir = temp_dir("atomic");
+        let path = dir.join("nested").join("data.json");
+        write_atomic(&path, b"first").unwrap();
+        write_atomic(&path, b"second").unwrap();
+        assert_eq!(read(&path), "second");
+        // No temporary file is left behind
+        assert_eq!(names(path.parent().unwrap()), ["data.json"]);
+
+        // A failed write leaves the old contents and no temporary file
+        let blocked = dir.join("blocked");
+        fs::create_dir_all(blocked.join("inner")).unwrap();
+        assert!(write_atomic(&blocked, b"x").is_err());
+        assert!(blocked.is_dir());
+        assert_eq!(names(&dir), ["blocked", "nested"]);
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn backups_rotate() {
+        let dir = temp_dir("rotate");
+        let path = dir.join("history.json");
+        for contents in ["v1", "v2", "v3", "v4", "v5"] {
+            save_with_backups(&path, contents.as_bytes()).unwrap();
+        }
+        assert_eq!(read(&path), "v5");
+        assert_eq!(backups(&path), [dir.join("history.json.1"), dir.join("history.json.2"), dir.join("history.json.3")]);
+        let kept: Vec<_> = backups(&path).iter().map(|backup| read(backup)).collect();
+        assert_eq!(kept, ["v4", "v3", "v2"]);
+
+        // Saving the same contents doesn't push out a backup
+        save_with_backups(&path, b"v5").unwrap();
+        assert_eq!(read(&dir.join("history.json.1")), "v4");
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn restores_the_newest_valid_backup() {
+        let dir = temp_dir("restore");
+        let path = dir.join("config.toml");
+        assert_eq!(restore_backup(&path, |_| true).unwrap(), None);
+
+        for contents in ["good 1", "good 2", "bad 3", "bad 4"] {
+            save_with_backups(&path, contents.as_bytes()).unwrap();
+        }
+        let valid = |contents: &[u8]| contents.starts_with(b"good");
+        assert_eq!(restore_backup(&path, valid).unwrap(), Some(dir.join("config.toml.2")));
+        assert_eq!(read(&path), "good 2");
+        // The backups themselves are left as they were
+        assert_eq!(read(&dir.join("config.toml.1")), "bad 3");
+
+        assert_eq!(restore_backup(&path, |_| false).unwrap(), None);
+        assert_eq!(read(&path), "good 2");
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
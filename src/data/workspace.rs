@@ -0,0 +1,82 @@
+use std::path::{Path, PathBuf};
+
+// A project's own `.raquet/` directory, found in the current directory or
+// one of its parents. Its `config.toml` is laid over the global config and
+// its `collections/` are shown next to the global collections.
+#[derive(Debug, Clone)]
+pub struct Workspace {
+    // The directory holding `.raquet/`
+    pub root: PathBuf,
+    pub dir: PathBuf,
+}
+
+impl Workspace {
+    pub fn discover() -> Option<Self> {
+        Self::find(&std::env::current_dir().ok()?)
+    }
+
+    // The global `~/.raquet` isn't a workspace, even when searching from home
+    pub fn find(start: &Path) -> Option<Self> {
+        let global = dirs::home_dir()
+            .map(|home| home.join(".raquet"))
+            .and_then(|dir| dir.canonicalize().ok());
+        start.ancestors()
+            .map(|root| (root, root.join(".raquet")))
+            .filter(|(_, dir)| dir.is_dir())
+            .find(|(_, dir)| dir.canonicalize().ok() != global)
+            .map(|(root, dir)| Workspace { root: root.to_path_buf(), dir })
+    }
+
+    pub fn name(&self) -> String {
+        self.root.file_name()
+            .map(|name| name.to_string_lossy().into_owned())
+            .unwrap_or_else(|| self.root.display().to_string())
+    }
+
+    pub fn config_path(&self) -> PathBuf {
+        self.dir.join("config.toml")
+    }
+
+    pub fn collections_dir(&self) -> PathBuf {
+        self.dir.join("collections")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn temp_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("raquet-workspace-{}-{}", name, std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    #[test]
+    fn finds_the_nearest_raquet_directory() {
+        let dir = temp_dir("find");
+        let project = dir.join("project");
+        let nested = project.join("services").join("api");
+        std::fs::create_dir_all(project.join(".raquet")).unwrap();
+        std::fs::create_dir_all(nested.join("src")).unwrap();
+
+        let found = Workspace::find(&nested.join("src")).unwrap();
+        assert_eq!(found.root, project);
+        assert_eq!(found.dir, project.join(".raquet"));
+        assert_eq!(found.name(), "project");
+        assert_eq!(found.config_path(), project.join(".raquet").join("config.toml"));
+        assert_eq!(found.collections_dir(), project.join(".raquet").join("collections"));
+
+        // A closer one wins
+        std::fs::create_dir_all(nested.join(".raquet")).unwrap();
+        assert_eq!(Workspace::find(&nested.join("src")).unwrap().root, nested);
+
+        // A file named .raquet isn't a workspace
+        let other = dir.join("other");
+        std::fs::create_dir_all(&other).unwrap();
+        std::fs::write(other.join(".raquet"), "").unwrap();
+        assert!(Workspace::find(&other).map_or(true, |found| !found.root.starts_with(&dir)));
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -1,32 +1,158 @@
-use serde::{Deserialize, Serialize};
+use serde::{Deserialize, Serialize, Serializer};
 use chrono::{DateTime, Utc};
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
+use std::sync::atomic::{AtomicU64, Ordering};
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct Collection {
     pub info: CollectionInfo,
     #[serde(default)]
     pub requests: Vec<CollectionItem>,
+    // `{{name}}` placeholders filled in when one of its requests is opened
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub variables: BTreeMap<String, String>,
+    // `.http` / `.rest` file the requests are read from and saved back to
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub source: Option<String>,
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct SavedRequest {
+    // Tells requests apart while raquet runs, since names needn't be unique.
+    // Not saved; every load hands out new ones.
+    #[serde(skip, default = "next_request_id")]
+    pub id: u64,
     pub name: String,
     pub request: Request,
+    // JSONPath filter applied to the response body when this request is loaded
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub response_filter: Option<String>,
+    // Operation this request was imported from; responses are checked against it
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub openapi: Option<OpenApiLink>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct OpenApiLink {
+    // Path of the spec file. Absolute once loaded; saved relative to the
+    // collection (the project root for workspace collections)
+    pub spec: String,
+    // Lower case, as the spec's path item keys
+    pub method: String,
+    // Path template, e.g. `/pets/{petId}`
+    pub path: String,
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct Request {
     pub method: String,
     pub url: String,
-    #[serde(default)]
+    #[serde(default, serialize_with = "sorted")]
     pub headers: HashMap<String, String>,
     pub body: Option<String>,
+    #[serde(default, skip_serializing_if = "RequestOptions::is_default")]
+    pub options: RequestOptions,
+    // Set for GraphQL requests, whose body is built from it
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub graphql: Option<GraphQlBody>,
+    // Set for gRPC requests, whose body is the request message as JSON
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub grpc: Option<GrpcTarget>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Default, PartialEq)]
+pub struct GraphQlBody {
+    pub query: String,
+    // JSON object, as typed
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub variables: String,
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub operation_name: String,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Default, PartialEq)]
+pub struct GrpcTarget {
+    // "package.Service/Method"
+    pub method: String,
+    // Where the service definitions come from; empty for server reflection
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub proto_files: Vec<String>,
+}
+
+// Per-request overrides of the global settings. Unset fields fall back to
+// the `[app]` section of the config.
+#[derive(Debug, Serialize, Deserialize, Clone, Default, PartialEq)]
+pub struct RequestOptions {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub follow_redirects: Option<bool>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_redirects: Option<usize>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub http_version: Option<HttpVersion>,
+    // Connect directly even when a proxy is configured
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub bypass_proxy: bool,
+}
+
+impl RequestOptions {
+    pub fn is_default(&self) -> bool {
+        *self == RequestOptions::default()
+    }
+}
+
+// HTTP version to speak. `Http2` negotiates HTTP/2 through TLS ALPN and fails
+// the request when the server picks HTTP/1.1; `Http2PriorKnowledge` speaks HTTP/2 from the
+// first byte, which is also how h2c works over plain HTTP.
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, Default, PartialEq)]
+pub enum HttpVersion {
+    #[default]
+    #[serde(rename = "auto")]
+    Auto,
+    #[serde(rename = "http1")]
+    Http1,
+    #[serde(rename = "http2")]
+    Http2,
+    #[serde(rename = "h2c")]
+    Http2PriorKnowledge,
+}
+
+impl HttpVersion {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            HttpVersion::Auto => "auto",
+            HttpVersion::Http1 => "HTTP/1.1",
+            HttpVersion::Http2 => "HTTP/2",
+            HttpVersion::Http2PriorKnowledge => "h2c",
+        }
+    }
+
+    pub fn parse(s: &str) -> Option<Self> {
+        match s.to_ascii_lowercase().as_str() {
+            "auto" => Some(HttpVersion::Auto),
+            "1" | "1.1" | "http1" | "http/1.1" => Some(HttpVersion::Http1),
+            "2" | "h2" | "http2" | "http/2" => Some(HttpVersion::Http2),
+            "h2c" | "prior" | "prior-knowledge" => Some(HttpVersion::Http2PriorKnowledge),
+            _ => None,
+        }
+    }
+}
+
+// A WebSocket endpoint, saved with its handshake headers and the message
+// being composed
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct SavedWebSocket {
+    pub name: String,
+    pub url: String,
+    #[serde(default, serialize_with = "sorted")]
+    pub headers: HashMap<String, String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub message: Option<String>,
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub enum CollectionItem {
-    Request(SavedRequest),
+    Request(Box<SavedRequest>),
+    WebSocket(SavedWebSocket),
     Folder(Folder),
 }
 
@@ -39,6 +165,66 @@ pub struct Folder {
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct Response {}
 
+impl Collection {
+    // Finds a request by id, descending into folders.
+    pub fn find_request_mut(&mut self, id: u64) -> Option<&mut SavedRequest> {
+        fn find(items: &mut [CollectionItem], id: u64) -> Option<&mut SavedRequest> {
+            items.iter_mut().find_map(|item| match item {
+                CollectionItem::Request(req) if req.id == id => Some(req.as_mut()),
+                CollectionItem::Request(_) | CollectionItem::WebSocket(_) => None,
+                CollectionItem::Folder(folder) => find(&mut folder.item, id),
+            })
+        }
+        find(&mut self.requests, id)
+    }
+
+    // Items of the folder at `path`, a list of folder names from the top.
+    pub fn items_at(&self, path: &[String]) -> Option<&[CollectionItem]> {
+        let mut items = self.requests.as_slice();
+        for name in path {
+            items = items.iter().find_map(|item| match item {
+                CollectionItem::Folder(folder) if folder.name == *name => Some(folder.item.as_slice()),
+                _ => None,
+            })?;
+        }
+        Some(items)
+    }
+
+    // Replaces `{{name}}` with the variable's value. Unknown and empty
+    // variables are left in place to be filled in by hand.
+    pub fn resolve(&self, text: &str) -> String {
+        resolve_variables(&self.variables, text)
+    }
+}
+
+pub fn next_request_id() -> u64 {
+    static NEXT: AtomicU64 = AtomicU64::new(1);
+    NEXT.fetch_add(1, Ordering::Relaxed)
+}
+
+// Headers are written in order so saved files don't change from run to run
+fn sorted<S: Serializer>(map: &HashMap<String, String>, serializer: S) -> Result<S::Ok, S::Error> {
+    map.iter().collect::<BTreeMap<_, _>>().serialize(serializer)
+}
+
+// `Collection::resolve` over any set of variables
+pub fn resolve_variables(variables: &BTreeMap<String, String>, text: &str) -> String {
+    let mut resolved = String::with_capacity(text.len());
+    let mut rest = text;
+    while let Some(start) = rest.find("{{") {
+        let Some(len) = rest[start + 2..].find("}}") else { break };
+        let name = &rest[start + 2..start + 2 + len];
+        resolved.push_str(&rest[..start]);
+        match variables.get(name.trim()).filter(|value| !value.is_empty()) {
+            Some(value) => resolved.push_str(value),
+            None => resolved.push_str(&rest[start..start + len + 4]),
+        }
+        rest = &rest[start + len + 4..];
+    }
+    resolved.push_str(rest);
+    resolved
+}
+
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct CollectionInfo {
     pub name: String,
@@ -55,5 +241,7 @@ pub fn create_default_collection() -> Collection {
             created_at: chrono::Utc::now(),
         },
         requests: Vec::new(),
+        variables: BTreeMap::new(),
+        source: None,
     }
 } 
\ No newline at end of file
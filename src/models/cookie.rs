@@ -0,0 +1,268 @@
+use chrono::{DateTime, Duration, NaiveDateTime, Utc};
+use reqwest::Url;
+use serde::{Deserialize, Serialize};
+
+// A cookie as stored in the jar, scoped to a domain and path (RFC 6265).
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct Cookie {
+    pub name: String,
+    pub value: String,
+    // Lowercased, without a leading dot
+    pub domain: String,
+    pub path: String,
+    // Set when the cookie had no Domain attribute: only sent to the exact host
+    #[serde(default)]
+    pub host_only: bool,
+    // None for session cookies
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub expires: Option<DateTime<Utc>>,
+    #[serde(default)]
+    pub secure: bool,
+    #[serde(default)]
+    pub http_only: bool,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub same_site: Option<String>,
+}
+
+impl Cookie {
+    // Parses a `Set-Cookie` header value received from `url`. Returns None for
+    // malformed cookies, cookies whose Domain doesn't cover the host or is a
+    // public suffix of another host, and Secure cookies sent over plain http.
+    pub fn parse(set_cookie: &str, url: &Url) -> Option<Cookie> {
+        let host = url.host_str()?.to_ascii_lowercase();
+        let mut parts = set_cookie.split(';');
+        let (name, value) = parts.next()?.split_once('=')?;
+        let name = name.trim();
+        if name.is_empty() {
+            return None;
+        }
+
+        let mut cookie = Cookie {
+            name: name.to_string(),
+            value: value.trim().trim_matches('"').to_string(),
+            domain: host.clone(),
+            path: default_path(url),
+            host_only: true,
+            expires: None,
+            secure: false,
+            http_only: false,
+            same_site: None,
+        };
+
+        let mut max_age = None;
+        for attribute in parts {
+            let (key, value) = attribute.split_once('=').unwrap_or((attribute, ""));
+            let value = value.trim();
+            match key.trim().to_ascii_lowercase().as_str() {
+                "expires" => {
+                    if let Some(expires) = parse_cookie_date(value) {
+                        cookie.expires = Some(expires);
+                    }
+                }
+                "max-age" => max_age = value.parse::<i64>().ok(),
+                "domain" if !value.is_empty() => {
+                    let domain = value.trim_start_matches('.').to_ascii_lowercase();
+                    if !domain_matches(&host, &domain) {
+                        return None;
+                    }
+                    // `com` or `co.uk` would cover every site under them; only
+                    // the host itself may use one, and then it's host-only
+                    if psl::suffix_str(&domain) == Some(domain.as_str()) {
+                        if domain != host {
+                            return None;
+                        }
+                        continue;
+                    }
+                    cookie.domain = domain;
+                    cookie.host_only = false;
+                }
+                "path" if value.starts_with('/') => cookie.path = value.to_string(),
+                "secure" => cookie.secure = true,
+                "httponly" => cookie.http_only = true,
+                "samesite" if !value.is_empty() => cookie.same_site = Some(value.to_string()),
+                _ => {}
+            }
+        }
+
+        if cookie.secure && url.scheme() != "https" {
+            return None;
+        }
+
+        // Max-Age takes precedence over Expires
+        if let Some(seconds) = max_age {
+            cookie.expires = Some(if seconds <= 0 {
+                DateTime::<Utc>::MIN_UTC
+            } else {
+                Utc::now() + Duration::seconds(seconds)
+            });
+        }
+        Some(cookie)
+    }
+
+    pub fn is_expired(&self) -> bool {
+        self.expires.map(|e| e <= Utc::now()).unwrap_or(false)
+    }
+
+    // Whether the cookie should be sent with a request to `url`.
+    pub fn matches(&self, url: &Url) -> bool {
+        let host = match url.host_str() {
+            Some(host) => host.to_ascii_lowercase(),
+            None => return false,
+        };
+        let domain_ok = if self.host_only {
+            host == self.domain
+        } else {
+            domain_matches(&host, &self.domain)
+        };
+        domain_ok
+            && path_matches(url.path(), &self.path)
+            && (!self.secure || url.scheme() == "https")
+            && !self.is_expired()
+    }
+
+    // Two cookies with the same name, domain and path are the same cookie.
+    pub fn same_identity(&self, other: &Cookie) -> bool {
+        self.name == other.name && self.domain == other.domain && self.path == other.path
+    }
+
+    pub fn expires_display(&self) -> String {
+        match self.expires {
+            Some(expires) => expires.format("%Y-%m-%d %H:%M UTC").to_string(),
+            None => "session".to_string(),
+        }
+    }
+}
+
+fn domain_matches(host: &str, domain: &str) -> bool {
+    host == domain || host.ends_with(&format!(".{}", domain))
+}
+
+fn path_matches(request_path: &str, cookie_path: &str) -> bool {
+    request_path == cookie_path
+        || (request_path.starts_with(cookie_path)
+            && (cookie_path.ends_with('/') || request_path[cookie_path.len()..].starts_with('/')))
+}
+
+// The directory of the request path, used when there's no Path attribute.
+fn default_path(url: &Url) -> String {
+    let path = url.path();
+    match path.rfind('/') {
+        Some(0) | None => "/".to_string(),
+        Some(i) => path[..i].to_string(),
+    }
+}
+
+// "Wed, 21 Oct 2015 07:28:00 GMT", plus the older dashed form
+// "Wednesday, 21-Oct-15 07:28:00 GMT" still sent by some servers.
+fn parse_cookie_date(value: &str) -> Option<DateTime<Utc>> {
+    if let Ok(date) = DateTime::parse_from_rfc2822(value) {
+        return Some(date.with_timezone(&Utc));
+    }
+    let value = value.split_once(", ").map(|(_, rest)| rest).unwrap_or(value);
+    ["%d-%b-%y %H:%M:%S GMT", "%d-%b-%Y %H:%M:%S GMT", "%d %b %Y %H:%M:%S GMT"]
+        .iter()
+        .find_map(|format| NaiveDateTime::parse_from_str(value, format).ok())
+        .map(|date| date.and_utc())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    fn url(url: &str) -> Url {
+        Url::parse(url).unwrap()
+    }
+
+    #[test]
+    fn parses_attributes() {
+        let cookie = Cookie::parse(
+            "sid=\"abc\"; Path=/api; Domain=.Example.com; Secure; HttpOnly; SameSite=Lax",
+            &url("https://www.example.com/login"),
+        ).unwrap();
+        assert_eq!(cookie.name, "sid");
+        assert_eq!(cookie.value, "abc");
+        assert_eq!(cookie.domain, "example.com");
+        assert!(!cookie.host_only);
+        assert_eq!(cookie.path, "/api");
+        assert!(cookie.secure && cookie.http_only);
+        assert_eq!(cookie.same_site.as_deref(), Some("Lax"));
+        assert_eq!(cookie.expires, None);
+    }
+
+    #[test]
+    fn defaults_to_the_host_and_request_directory() {
+        let cookie = Cookie::parse("a=1", &url("http://Example.com/docs/page")).unwrap();
+        assert_eq!(cookie.domain, "example.com");
+        assert!(cookie.host_only);
+        assert_eq!(cookie.path, "/docs");
+        let cookie = Cookie::parse("a=1; Path=relative", &url("http://example.com/page")).unwrap();
+        assert_eq!(cookie.path, "/");
+    }
+
+    #[test]
+    fn rejects_malformed_cookies() {
+        let from = url("http://example.com/");
+        assert!(Cookie::parse("no-equals-sign", &from).is_none());
+        assert!(Cookie::parse("=value", &from).is_none());
+        assert!(Cookie::parse("a=1; Domain=other.com", &from).is_none());
+        assert!(Cookie::parse("a=1; Domain=ample.com", &from).is_none());
+    }
+
+    #[test]
+    fn rejects_secure_cookies_over_http() {
+        assert!(Cookie::parse("a=1; Secure", &url("http://example.com/")).is_none());
+        assert!(Cookie::parse("a=1; Secure", &url("https://example.com/")).is_some());
+    }
+
+    #[test]
+    fn rejects_public_suffix_domains() {
+        assert!(Cookie::parse("a=1; Domain=com", &url("http://example.com/")).is_none());
+        assert!(Cookie::parse("a=1; Domain=co.uk", &url("http://shop.example.co.uk/")).is_none());
+        assert!(Cookie::parse("a=1; Domain=github.io", &url("http://me.github.io/")).is_none());
+        // The suffix itself may set a cookie, but only for itself
+        let cookie = Cookie::parse("a=1; Domain=localhost", &url("http://localhost:8080/")).unwrap();
+        assert!(cookie.host_only);
+        assert!(Cookie::parse("a=1; Domain=example.co.uk", &url("http://shop.example.co.uk/")).is_some());
+    }
+
+    #[test]
+    fn max_age_overrides_expires() {
+        let from = url("http://example.com/");
+        let cookie = Cookie::parse("a=1; Max-Age=0; Expires=Wed, 21 Oct 2099 07:28:00 GMT", &from).unwrap();
+        assert!(cookie.is_expired());
+        let cookie = Cookie::parse("a=1; Expires=Wed, 21 Oct 2015 07:28:00 GMT; Max-Age=60", &from).unwrap();
+        assert!(!cookie.is_expired());
+    }
+
+    #[test]
+    fn parses_cookie_dates() {
+        let expected = Utc.with_ymd_and_hms(2015, 10, 21, 7, 28, 0).unwrap();
+        assert_eq!(parse_cookie_date("Wed, 21 Oct 2015 07:28:00 GMT"), Some(expected));
+        assert_eq!(parse_cookie_date("Wednesday, 21-Oct-15 07:28:00 GMT"), Some(expected));
+        assert_eq!(parse_cookie_date("Wed, 21-Oct-2015 07:28:00 GMT"), Some(expected));
+        assert_eq!(parse_cookie_date("21 Oct 2015 07:28:00 GMT"), Some(expected));
+        assert_eq!(parse_cookie_date("tomorrow"), None);
+    }
+
+    #[test]
+    fn matches_paths() {
+        assert!(path_matches("/api", "/api"));
+        assert!(path_matches("/api/users", "/api"));
+        assert!(path_matches("/api/users", "/api/"));
+        assert!(path_matches("/anything", "/"));
+        assert!(!path_matches("/apiv2", "/api"));
+        assert!(!path_matches("/", "/api"));
+    }
+
+    #[test]
+    fn matches_requests() {
+        let cookie = Cookie::parse("a=1; Domain=example.com; Path=/api", &url("https://example.com/")).unwrap();
+        assert!(cookie.matches(&url("http://sub.example.com/api/x")));
+        assert!(!cookie.matches(&url("http://notexample.com/api")));
+        assert!(!cookie.matches(&url("http://example.com/other")));
+        let host_only = Cookie::parse("a=1; Secure", &url("https://example.com/")).unwrap();
+        assert!(!host_only.matches(&url("https://sub.example.com/")));
+        assert!(!host_only.matches(&url("http://example.com/")));
+    }
+}
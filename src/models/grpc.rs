@@ -0,0 +1,15 @@
+use chrono::{DateTime, Utc};
+
+// One line of a server-streaming call's log: a response message as JSON, or
+// a notice such as the final status
+#[derive(Debug, Clone)]
+pub enum GrpcEntry {
+    Message { received_at: DateTime<Utc>, json: String },
+    Info { at: DateTime<Utc>, text: String },
+}
+
+impl GrpcEntry {
+    pub fn info(text: impl Into<String>) -> Self {
+        GrpcEntry::Info { at: Utc::now(), text: text.into() }
+    }
+}
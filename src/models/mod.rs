@@ -1,5 +1,10 @@
 pub mod collection;
 pub mod response;
+pub mod cookie;
+pub mod websocket;
+pub mod sse;
+pub mod grpc;
 
-pub use response::{ResponseData, ResponseMetadata};
-pub use collection::{Collection, CollectionInfo, CollectionItem, SavedRequest}; 
\ No newline at end of file
+pub use response::{ResponseData, ResponseMetadata, RedirectHop, Timings};
+pub use cookie::Cookie;
+pub use collection::{Collection, CollectionInfo, CollectionItem, SavedRequest, SavedWebSocket}; 
\ No newline at end of file
@@ -12,12 +12,60 @@ pub struct ResponseData {
     pub size_bytes: usize,
 }
 
+// Per-phase timings of a request in milliseconds, similar to curl's `-w`
+// timings. Connection phases are None when they didn't happen (an IP address
+// needs no DNS lookup, plain HTTP has no TLS handshake) or couldn't be measured.
+#[derive(Debug, Serialize, Deserialize, Clone, Default, PartialEq)]
+pub struct Timings {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub dns_ms: Option<f64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub connect_ms: Option<f64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tls_ms: Option<f64>,
+    // Waiting for the server: request sent until the response headers arrived
+    pub ttfb_ms: f64,
+    pub download_ms: f64,
+}
+
+impl Timings {
+    pub fn total_ms(&self) -> f64 {
+        self.dns_ms.unwrap_or(0.0)
+            + self.connect_ms.unwrap_or(0.0)
+            + self.tls_ms.unwrap_or(0.0)
+            + self.ttfb_ms
+            + self.download_ms
+    }
+}
+
+// One redirect followed while sending a request
+#[derive(Debug, Clone, PartialEq)]
+pub struct RedirectHop {
+    pub url: String,
+    pub status: u16,
+    // Resolved target of the `Location` header
+    pub location: String,
+}
+
 #[derive(Debug, Clone)]
 pub struct ResponseMetadata {
     pub status: u16,
     pub status_text: String,
     pub time_ms: u128,
     pub size_bytes: usize,
+    pub is_binary: bool,
     pub response_headers: HashMap<String, String>,
+    // Raw `Set-Cookie` values; the header map above keeps only one of them
+    pub set_cookies: Vec<String>,
+    // Final URL after redirects, and the hops taken to get there
+    pub url: String,
+    pub redirects: Vec<RedirectHop>,
+    // Negotiated protocol, e.g. "HTTP/2.0"
+    pub http_version: String,
+    // Proxy the request went through, without credentials
+    pub proxy: Option<String>,
+    pub timings: Timings,
     pub timestamp: DateTime<Utc>,
+    // Restored from history rather than received just now
+    pub from_history: bool,
 } 
\ No newline at end of file
@@ -0,0 +1,24 @@
+use chrono::{DateTime, Utc};
+
+// A dispatched Server-Sent Event
+#[derive(Debug, Clone, PartialEq)]
+pub struct SseEvent {
+    pub received_at: DateTime<Utc>,
+    // "message" unless the server named it
+    pub event: String,
+    pub id: Option<String>,
+    pub data: String,
+}
+
+// One line of an event stream's log: an event, or a connection notice
+#[derive(Debug, Clone)]
+pub enum StreamEntry {
+    Event(SseEvent),
+    Info { at: DateTime<Utc>, text: String },
+}
+
+impl StreamEntry {
+    pub fn info(text: impl Into<String>) -> Self {
+        StreamEntry::Info { at: Utc::now(), text: text.into() }
+    }
+}
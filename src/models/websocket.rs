@@ -0,0 +1,62 @@
+use chrono::{DateTime, Utc};
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum WsDirection {
+    Sent,
+    Received,
+    // Connection events and errors
+    Info,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum WsKind {
+    Text,
+    Binary,
+    Ping,
+    Pong,
+    Close,
+}
+
+impl WsKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            WsKind::Text => "text",
+            WsKind::Binary => "binary",
+            WsKind::Ping => "ping",
+            WsKind::Pong => "pong",
+            WsKind::Close => "close",
+        }
+    }
+}
+
+// One entry of a WebSocket session's message log
+#[derive(Debug, Clone)]
+pub struct WsMessage {
+    pub timestamp: DateTime<Utc>,
+    pub direction: WsDirection,
+    pub kind: WsKind,
+    pub data: Vec<u8>,
+}
+
+impl WsMessage {
+    pub fn new(direction: WsDirection, kind: WsKind, data: Vec<u8>) -> Self {
+        WsMessage {
+            timestamp: Utc::now(),
+            direction,
+            kind,
+            data,
+        }
+    }
+
+    pub fn info(text: impl Into<String>) -> Self {
+        Self::new(WsDirection::Info, WsKind::Text, text.into().into_bytes())
+    }
+
+    // Payload as shown in the log: text as is, binary frames as hex
+    pub fn preview(&self) -> String {
+        match self.kind {
+            WsKind::Binary => self.data.iter().map(|b| format!("{:02x}", b)).collect::<Vec<_>>().join(" "),
+            _ => String::from_utf8_lossy(&self.data).into_owned(),
+        }
+    }
+}
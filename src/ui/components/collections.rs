@@ -14,7 +14,7 @@ pub fn draw_collections(f: &mut Frame, app: &mut App, area: Rect) {
             // Collections list with help text inside
             let collections = app.collection_manager.get_collections();
             let mut items = vec![
-                ListItem::new("Press 'n' to add new collection, 'd' to delete")
+                ListItem::new("Press 'n' to add new collection, 'i' to import OpenAPI, HAR or .http, 'd' to delete")
                     .style(Style::default().fg(Color::DarkGray))
             ];
 
@@ -23,13 +23,22 @@ pub fn draw_collections(f: &mut Frame, app: &mut App, area: Rect) {
                 .values()
                 .enumerate()
                 .map(|(index, collection)| {
-                    ListItem::new(format!("{}. {}", index + 1, collection.info.name))
+                    let name = &collection.info.name;
+                    if app.collection_manager.in_workspace(name) {
+                        ListItem::new(format!("{}. {} ⌂", index + 1, name))
+                    } else {
+                        ListItem::new(format!("{}. {}", index + 1, name))
+                    }
                 }));
 
+            let mut block = Block::default()
+                .title("Collections")
+                .borders(Borders::ALL);
+            if let Some(message) = &app.status_message {
+                block = block.title_bottom(Line::from(format!(" {} ", message)).right_aligned());
+            }
             let list = List::new(items)
-                .block(Block::default()
-                    .title("Collections")
-                    .borders(Borders::ALL))
+                .block(block)
                 .highlight_style(Style::default().fg(Color::Yellow))
                 .highlight_symbol("▶ ");
 
@@ -42,7 +51,8 @@ pub fn draw_collections(f: &mut Frame, app: &mut App, area: Rect) {
         CollectionView::Requests => {
             if let Some(collection_name) = &app.selected_collection {
                 if let Some(collection) = app.collection_manager.get_collection(collection_name) {
-                    let items: Vec<ListItem> = collection.requests
+                    let items: Vec<ListItem> = collection.items_at(&app.collection_folder)
+                        .unwrap_or_default()
                         .iter()
                         .enumerate()
                         .map(|(index, item)| {
@@ -54,15 +64,39 @@ pub fn draw_collections(f: &mut Frame, app: &mut App, area: Rect) {
                                         req.name
                                     ))
                                 }
-                                CollectionItem::Folder(_) => ListItem::new("📁 Folder"),
+                                CollectionItem::WebSocket(ws) => {
+                                    ListItem::new(format!("{}. WS {}", index + 1, ws.url))
+                                }
+                                CollectionItem::Folder(folder) => {
+                                    ListItem::new(format!("📁 {} ({})", folder.name, folder.item.len()))
+                                }
                             }
                         })
                         .collect();
 
+                    let mut title = format!("Requests in {}", collection_name);
+                    for folder in &app.collection_folder {
+                        title.push_str(" › ");
+                        title.push_str(folder);
+                    }
+                    title.push_str(" ('r' to run all and save as HAR)");
+                    let variables = if collection.variables.is_empty() {
+                        " 'v' to set a {{variable}} ".to_string()
+                    } else {
+                        let variables: Vec<String> = collection.variables.iter()
+                            .map(|(name, value)| format!("{}={}", name, value))
+                            .collect();
+                        format!(" {} ('v' to set) ", variables.join(", "))
+                    };
+                    let mut block = Block::default()
+                        .title(title)
+                        .title_bottom(variables)
+                        .borders(Borders::ALL);
+                    if let Some(message) = &app.status_message {
+                        block = block.title_bottom(Line::from(format!(" {} ", message)).right_aligned());
+                    }
                     let list = List::new(items)
-                        .block(Block::default()
-                            .title(format!("Requests in {}", collection_name))
-                            .borders(Borders::ALL))
+                        .block(block)
                         .highlight_style(Style::default().fg(Color::Yellow))
                         .highlight_symbol("▶ ");
 
@@ -0,0 +1,80 @@
+use ratatui::{
+    prelude::*,
+    widgets::{Block, Borders, List, ListItem, ListState, Paragraph},
+    style::{Color, Style},
+    text::Line,
+};
+use crate::app::App;
+
+// Cookie manager: every cookie in the jar with its scope, plus the details of
+// the selected one.
+pub fn draw_cookies(f: &mut Frame, app: &mut App, area: Rect) {
+    let cookies = app.cookie_jar.cookies();
+    if app.cookies_selected_index >= cookies.len() {
+        app.cookies_selected_index = cookies.len().saturating_sub(1);
+    }
+
+    let layout = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([Constraint::Min(0), Constraint::Length(6)])
+        .split(area);
+
+    let cookies_block = Block::default()
+        .title("Cookies (↑↓ navigate, Enter/e edit value, d delete, D delete all, Esc close)")
+        .borders(Borders::ALL);
+
+    let items: Vec<ListItem> = cookies
+        .iter()
+        .enumerate()
+        .map(|(i, cookie)| {
+            let style = if i == app.cookies_selected_index {
+                Style::default().fg(Color::Yellow)
+            } else {
+                Style::default()
+            };
+            let line = Line::from(vec![
+                Span::styled(
+                    format!("{}{} ", cookie.domain, cookie.path),
+                    Style::default().fg(Color::Cyan)
+                ),
+                Span::raw(format!("{}={}", cookie.name, cookie.value)),
+            ]);
+            ListItem::new(line).style(style)
+        })
+        .collect();
+
+    let cookies_list = List::new(items)
+        .block(cookies_block)
+        .highlight_style(Style::default().fg(Color::Yellow));
+
+    let mut list_state = ListState::default()
+        .with_selected((!cookies.is_empty()).then_some(app.cookies_selected_index));
+    f.render_stateful_widget(cookies_list, layout[0], &mut list_state);
+
+    let details = match cookies.get(app.cookies_selected_index) {
+        Some(cookie) => {
+            let yes_no = |b: bool| if b { "yes" } else { "no" };
+            vec![
+                Line::from(format!("Name: {}    Value: {}", cookie.name, cookie.value)),
+                Line::from(format!(
+                    "Domain: {}{}    Path: {}",
+                    cookie.domain,
+                    if cookie.host_only { " (host only)" } else { "" },
+                    cookie.path
+                )),
+                Line::from(format!(
+                    "Expires: {}    Secure: {}    HttpOnly: {}    SameSite: {}",
+                    cookie.expires_display(),
+                    yes_no(cookie.secure),
+                    yes_no(cookie.http_only),
+                    cookie.same_site.as_deref().unwrap_or("-")
+                )),
+            ]
+        }
+        None => vec![Line::styled("The cookie jar is empty", Style::default().fg(Color::DarkGray))],
+    };
+    let details_block = Block::default()
+        .title("Details")
+        .borders(Borders::ALL);
+    f.render_widget(Paragraph::new(details).block(details_block), layout[1]);
+}
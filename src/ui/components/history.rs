@@ -1,61 +1,150 @@
 use ratatui::{
     prelude::*,
-    widgets::{Block, Borders, List, ListItem, ListState},
+    widgets::{Block, Borders, List, ListItem, ListState, Paragraph},
     style::{Color, Style},
     text::Line,
 };
-use crate::app::App;
+use chrono::{DateTime, Local, NaiveDate, Utc};
+use crate::app::{App, Field, InputMode};
 
 pub fn draw_history(f: &mut Frame, app: &mut App, area: Rect) {
-    let history_block = Block::default()
-        .title("History (↑↓ to navigate, Enter to select, Esc to close)")
+    let entries = app.history.get_entries();
+    let mut title = "History (Enter open, 'r' re-send, 's' save, Space mark, '/' filter, 'd'/'D' delete marked/all shown, 'e'/'i' HAR, Esc close)".to_string();
+    if app.history_filter.is_active() {
+        title = format!("{} ─ {} of {}", title, app.history_view.len(), entries.len());
+    }
+    if !app.history_marked.is_empty() {
+        title = format!("{} ─ {} marked", title, app.history_marked.len());
+    }
+    let mut history_block = Block::default()
+        .title(title)
         .borders(Borders::ALL);
+    if let Some(message) = &app.status_message {
+        history_block = history_block.title_bottom(Line::from(format!(" {} ", message)).right_aligned());
+    }
 
-    let items: Vec<ListItem> = app.history.get_entries()
-        .iter()
-        .map(|entry| {
-            let style = if app.history.get_entries().get(app.history_selected_index)
-                .map(|entry_ref| entry_ref == entry)
-                .unwrap_or(false)
-            {
-                Style::default().fg(Color::Yellow)
-            } else {
-                Style::default()
-            };
-
-            let status = entry.response.as_ref()
-                .and_then(|r| r.status)
-                .map(|s| s.to_string())
-                .unwrap_or_else(|| "---".to_string());
-
-            let line = Line::from(vec![
-                Span::styled(
-                    format!("{} ", entry.request.method),
-                    Style::default().fg(Color::Cyan)
-                ),
-                Span::raw(format!("{} ", entry.request.url)),
-                Span::styled(
-                    status.clone(),
-                    Style::default().fg(if status.starts_with('2') {
-                        Color::Green
-                    } else if status.starts_with('4') || status.starts_with('5') {
-                        Color::Red
-                    } else {
-                        Color::DarkGray
-                    })
-                ),
-            ]);
-
-            ListItem::new(line).style(style)
-        })
-        .collect();
+    let inner = history_block.inner(area);
+    f.render_widget(history_block, area);
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([Constraint::Length(1), Constraint::Min(0)])
+        .split(inner);
+    draw_filter_bar(f, app, chunks[0]);
+
+    // Entries are newest first, under a header for each day
+    let now = Utc::now();
+    let today = Local::now().date_naive();
+    let dim = Style::default().fg(Color::DarkGray);
+    let mut items: Vec<ListItem> = Vec::new();
+    let mut selected_row = None;
+    let mut current_day = None;
+    for (position, &index) in app.history_view.iter().enumerate() {
+        let entry = &entries[index];
+        let day = entry.timestamp.with_timezone(&Local).date_naive();
+        if current_day != Some(day) {
+            current_day = Some(day);
+            items.push(ListItem::new(Line::from(Span::styled(
+                day_label(day, today),
+                Style::default().fg(Color::Blue).add_modifier(Modifier::BOLD),
+            ))));
+        }
+        if position == app.history_selected_index {
+            selected_row = Some(items.len());
+        }
+
+        let status = entry.response.as_ref()
+            .and_then(|r| r.status)
+            .map(|s| s.to_string())
+            .unwrap_or_else(|| "---".to_string());
+        let duration = entry.response.as_ref()
+            .map(|r| format!("{}ms", r.time_ms))
+            .unwrap_or_default();
+
+        let marked = app.history_marked.contains(&entry.id);
+        let line = Line::from(vec![
+            Span::styled(if marked { "● " } else { "  " }, Style::default().fg(Color::Magenta)),
+            Span::styled(format!("{:>8}  ", relative_time(entry.timestamp, now)), dim),
+            Span::styled(
+                format!("{:<7} ", entry.request.method),
+                Style::default().fg(Color::Cyan)
+            ),
+            Span::styled(
+                format!("{:<4}", status),
+                Style::default().fg(if status.starts_with('2') {
+                    Color::Green
+                } else if status.starts_with('4') || status.starts_with('5') {
+                    Color::Red
+                } else {
+                    Color::DarkGray
+                })
+            ),
+            Span::styled(format!("{:>8}  ", duration), dim),
+            Span::raw(entry.request.url.clone()),
+        ]);
+
+        items.push(ListItem::new(line));
+    }
+
+    if items.is_empty() {
+        let message = if entries.is_empty() { "No requests yet" } else { "No requests match the filter" };
+        f.render_widget(Paragraph::new(Span::styled(message, dim)), chunks[1]);
+        return;
+    }
 
     let history_list = List::new(items)
-        .block(history_block)
         .highlight_style(Style::default().fg(Color::Yellow));
 
     let mut list_state = ListState::default()
-        .with_selected(Some(app.history_selected_index));
+        .with_selected(selected_row);
+
+    f.render_stateful_widget(history_list, chunks[1], &mut list_state);
+}
+
+fn draw_filter_bar(f: &mut Frame, app: &App, area: Rect) {
+    let filter = &app.history_filter;
+    let mut spans = vec![Span::styled("filter: ", Style::default().fg(Color::Cyan))];
+    if app.input_mode == InputMode::Editing(Field::HistoryFilter) {
+        let chars: Vec<char> = filter.query.chars().collect();
+        let pos = filter.cursor.min(chars.len());
+        spans.push(Span::raw(chars[..pos].iter().collect::<String>()));
+        if pos < chars.len() {
+            spans.push(Span::styled(chars[pos].to_string(), Style::default().bg(Color::Yellow)));
+            spans.push(Span::raw(chars[pos + 1..].iter().collect::<String>()));
+        } else {
+            spans.push(Span::styled(" ", Style::default().bg(Color::Yellow)));
+        }
+    } else if filter.is_active() {
+        spans.push(Span::raw(filter.query.clone()));
+        spans.push(Span::styled("  (Esc clear)", Style::default().fg(Color::DarkGray)));
+    } else {
+        spans.push(Span::styled(
+            "press '/' to filter, e.g. POST 5xx after:yesterday body:token /users",
+            Style::default().fg(Color::DarkGray),
+        ));
+    }
+    if let Some(error) = &filter.error {
+        spans.push(Span::styled(format!("  {}", error), Style::default().fg(Color::Red)));
+    }
+    f.render_widget(Paragraph::new(Line::from(spans)), area);
+}
+
+fn day_label(day: NaiveDate, today: NaiveDate) -> String {
+    match (today - day).num_days() {
+        0 => "Today".to_string(),
+        1 => "Yesterday".to_string(),
+        _ => day.format("%a %-d %b %Y").to_string(),
+    }
+}
 
-    f.render_stateful_widget(history_list, area, &mut list_state);
+// "just now", "5m ago", "3h ago", "2d ago", ...
+fn relative_time(timestamp: DateTime<Utc>, now: DateTime<Utc>) -> String {
+    let seconds = (now - timestamp).num_seconds().max(0);
+    match seconds {
+        0..=59 => "just now".to_string(),
+        60..=3599 => format!("{}m ago", seconds / 60),
+        3600..=86399 => format!("{}h ago", seconds / 3600),
+        _ if seconds < 30 * 86400 => format!("{}d ago", seconds / 86400),
+        _ if seconds < 365 * 86400 => format!("{}mo ago", seconds / (30 * 86400)),
+        _ => format!("{}y ago", seconds / (365 * 86400)),
+    }
 }
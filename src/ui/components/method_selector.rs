@@ -8,8 +8,52 @@ use crate::app::{App, HttpMethod};
 pub fn draw_method_selector(f: &mut Frame, app: &App, area: Rect) {
     let methods = HttpMethod::all();
     let width = 15;
-    let height = (methods.len() + 2) as u16;
+    // All methods plus the custom entry, capped to the screen
+    let height = ((methods.len() + 3) as u16).min(area.height.saturating_sub(4));
     
+    let method_area = method_box(area);
+
+    // Create popup area aligned with method box
+    let popup_area = Rect {
+        x: method_area.x,
+        y: method_area.y,
+        width: width.max(method_area.width),
+        height,
+    };
+
+    // Clear the background and draw the list
+    f.render_widget(Clear, popup_area);
+
+    let mut items: Vec<ListItem> = methods
+        .iter()
+        .map(|method| {
+            ListItem::new(method.as_str())
+                .style(Style::default().fg(method.color()))
+        })
+        .collect();
+    let custom = match &app.method {
+        HttpMethod::Custom(method) => format!("custom… ({})", method),
+        _ => "custom…".to_string(),
+    };
+    items.push(ListItem::new(custom).style(Style::default().fg(Color::DarkGray)));
+
+    let methods_list = List::new(items)
+        .block(Block::default()
+            .title("Method")
+            .borders(Borders::ALL))
+        .highlight_style(Style::default()
+            .fg(Color::Black)
+            .bg(Color::White));
+
+    f.render_stateful_widget(
+        methods_list,
+        popup_area,
+        &mut ListState::default().with_selected(Some(app.selector_method_index)),
+    );
+}
+
+// Where the method box is drawn, for popups that open from it
+fn method_box(area: Rect) -> Rect {
     // Account for main layout and request section
     let main_layout = Layout::default()
         .direction(Direction::Horizontal)
@@ -30,36 +74,45 @@ pub fn draw_method_selector(f: &mut Frame, app: &App, area: Rect) {
         .split(content_area)[0];
 
     // Get method box position (first 10% of URL bar)
-    let method_area = Layout::default()
+    Layout::default()
         .direction(Direction::Horizontal)
         .constraints([
             Constraint::Percentage(10),  // Method width
             Constraint::Min(0),
         ])
-        .split(request_area)[0];
+        .split(request_area)[0]
+}
 
-    // Create popup area aligned with method box
+// Methods of the loaded gRPC service definitions, below the method box
+pub fn draw_grpc_method_selector(f: &mut Frame, app: &App, area: Rect) {
+    let methods = app.grpc_methods();
+    let width = (methods.iter().map(|m| m.len()).max().unwrap_or(0) as u16 + 4)
+        .max(20)
+        .min(area.width.saturating_sub(22));
+    let height = ((methods.len() + 2) as u16).min(area.height.saturating_sub(4));
+    let method_area = method_box(area);
     let popup_area = Rect {
         x: method_area.x,
         y: method_area.y,
-        width: width.max(method_area.width),
+        width,
         height,
     };
-
-    // Clear the background and draw the list
     f.render_widget(Clear, popup_area);
 
     let items: Vec<ListItem> = methods
         .iter()
         .map(|method| {
-            ListItem::new(method.as_str())
-                .style(Style::default().fg(method.color()))
+            let style = if *method == app.grpc_target.method {
+                Style::default().fg(Color::LightBlue)
+            } else {
+                Style::default()
+            };
+            ListItem::new(method.as_str()).style(style)
         })
         .collect();
-
     let methods_list = List::new(items)
         .block(Block::default()
-            .title("Method")
+            .title("gRPC method (↑↓, Enter)")
             .borders(Borders::ALL))
         .highlight_style(Style::default()
             .fg(Color::Black)
@@ -68,7 +121,7 @@ pub fn draw_method_selector(f: &mut Frame, app: &App, area: Rect) {
     f.render_stateful_widget(
         methods_list,
         popup_area,
-        &mut ListState::default().with_selected(Some(app.selector_method_index)),
+        &mut ListState::default().with_selected(Some(app.grpc_method_index)),
     );
 }
 
@@ -1,19 +1,34 @@
 mod collections;
 mod history;
+mod cookies;
 mod headers;
 mod request;
 mod response;
 mod save_dialog;
 mod method_selector;
+mod prompt;
+mod schema;
+mod recovery;
 
 pub use collections::draw_collections;
 pub use history::draw_history;
+pub use cookies::draw_cookies;
 pub use headers::draw_headers;
 pub use request::{draw_request, draw_request_body};
 pub use response::{
     draw_response_headers, 
+    draw_response_cookies,
+    draw_response_redirects,
+    draw_response_timing,
+    draw_response_contract,
     draw_response_body,
+    draw_websocket_log,
+    draw_event_stream,
+    draw_grpc_stream,
     draw_response_status
 };
 pub use save_dialog::draw_save_dialog;
-pub use method_selector::draw_method_selector; 
\ No newline at end of file
+pub use method_selector::{draw_method_selector, draw_grpc_method_selector};
+pub use prompt::draw_prompt;
+pub use schema::draw_schema;
+pub use recovery::draw_recovery; 
\ No newline at end of file
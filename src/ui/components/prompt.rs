@@ -0,0 +1,38 @@
+use ratatui::{
+    prelude::*,
+    widgets::{Block, Borders, Clear, Paragraph},
+    style::{Color, Style},
+};
+use crate::app::App;
+
+pub fn draw_prompt(f: &mut Frame, app: &App, area: Rect) {
+    let kind = match app.prompt {
+        Some(kind) => kind,
+        None => return,
+    };
+
+    let width = 70.min(area.width.saturating_sub(4));
+    let height = 3;
+    let x = area.x + area.width.saturating_sub(width) / 2;
+    let y = area.y + area.height.saturating_sub(height) / 2;
+    let prompt_area = Rect::new(x, y, width, height);
+
+    let chars: Vec<char> = app.prompt_input.chars().collect();
+    let pos = app.prompt_cursor.min(chars.len());
+    let mut spans = vec![Span::raw(chars[..pos].iter().collect::<String>())];
+    if pos < chars.len() {
+        spans.push(Span::styled(chars[pos].to_string(), Style::default().bg(Color::Yellow)));
+        spans.push(Span::raw(chars[pos + 1..].iter().collect::<String>()));
+    } else {
+        spans.push(Span::styled(" ", Style::default().bg(Color::Yellow)));
+    }
+
+    let input = Paragraph::new(Line::from(spans))
+        .block(Block::default()
+            .title(format!("{} (Enter to confirm, Esc to cancel)", kind.title()))
+            .borders(Borders::ALL)
+            .border_style(Style::default().fg(Color::Yellow)));
+
+    f.render_widget(Clear, prompt_area);
+    f.render_widget(input, prompt_area);
+}
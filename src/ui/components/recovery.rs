@@ -0,0 +1,49 @@
+use ratatui::{
+    prelude::*,
+    widgets::{Block, Borders, Clear, Paragraph, Wrap},
+    style::{Color, Style},
+};
+use crate::app::{App, Store};
+use crate::data::storage;
+
+pub fn draw_recovery(f: &mut Frame, app: &App, area: Rect) {
+    let Some((store, file)) = app.damaged_files.first() else { return };
+
+    let width = 76.min(area.width.saturating_sub(4));
+    let height = (14 + file.error.lines().count() as u16).min(area.height);
+    let x = area.x + area.width.saturating_sub(width) / 2;
+    let y = area.y + area.height.saturating_sub(height) / 2;
+    let dialog_area = Rect::new(x, y, width, height);
+
+    let what = match store {
+        Store::Config => "Settings",
+        Store::Collections => "Saved collections",
+        Store::History => "History",
+        Store::Cookies => "Cookies",
+    };
+    let backups = storage::backups(&file.path).len();
+    let key = |key: &'static str| Span::styled(key, Style::default().fg(Color::Yellow).add_modifier(Modifier::BOLD));
+    let mut lines = vec![Line::from(Span::styled(file.path.display().to_string(), Style::default().add_modifier(Modifier::BOLD)))];
+    // TOML errors quote the offending line over several lines
+    lines.extend(file.error.lines().map(|line| Line::from(Span::styled(line.to_string(), Style::default().fg(Color::Red)))));
+    lines.extend([
+        Line::from(""),
+        Line::from(format!("{} couldn't be loaded. The file won't be written over until you choose:", what)),
+        Line::from(""),
+        Line::from(vec![key("r"), Span::raw(format!(" restore the newest readable backup ({} found)", backups))]),
+        Line::from(vec![key("s"), Span::raw(" set the file aside as .damaged and start without it")]),
+        Line::from(vec![key("c"), Span::raw(" continue, leaving the file as it is (changes aren't saved)")]),
+        Line::from(vec![key("q"), Span::raw(" quit and repair it by hand")]),
+    ]);
+
+    let mut block = Block::default()
+        .title(format!("Damaged file (1 of {})", app.damaged_files.len()))
+        .borders(Borders::ALL)
+        .border_style(Style::default().fg(Color::Red));
+    if let Some(message) = &app.status_message {
+        block = block.title_bottom(Line::from(format!(" {} ", message)).right_aligned());
+    }
+
+    f.render_widget(Clear, dialog_area);
+    f.render_widget(Paragraph::new(lines).wrap(Wrap { trim: false }).block(block), dialog_area);
+}
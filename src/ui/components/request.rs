@@ -1,9 +1,9 @@
 use ratatui::{
     prelude::*,
-    widgets::{Block, Borders, Paragraph},
+    widgets::{Block, Borders, Clear, List, ListItem, ListState, Paragraph},
     style::{Color, Style},
 };
-use crate::app::{App, Field, InputMode};
+use crate::app::{App, Field, GraphQlPane, InputMode};
 use super::{
     headers::draw_headers,
     save_dialog::draw_save_dialog,
@@ -74,16 +74,46 @@ fn draw_method(f: &mut Frame, app: &App, area: Rect) {
         .borders(Borders::ALL)
         .border_style(style_for_field(Field::Method, app));
 
-    let method = Paragraph::new(app.method.as_str())
+    // The method doesn't apply to WebSocket and gRPC URLs
+    let (text, color) = if app.is_websocket() {
+        ("WS", Color::LightMagenta)
+    } else if app.is_grpc() {
+        ("gRPC", Color::LightBlue)
+    } else {
+        (app.method.as_str(), app.method.color())
+    };
+    let method = Paragraph::new(text)
         .block(method_block)
-        .style(Style::default().fg(app.method.color()))
+        .style(Style::default().fg(color))
         .alignment(Alignment::Center);
     f.render_widget(method, area);
 }
 
 fn draw_url(f: &mut Frame, app: &App, area: Rect) {
+    // Per-request overrides, e.g. "URL ─ redirects off ─ HTTP/2"
+    let mut title = "URL".to_string();
+    if app.is_websocket() {
+        title.push_str(if app.open_websocket().is_some() { " ─ WebSocket connected" } else { " ─ WebSocket" });
+    }
+    if app.is_grpc() {
+        match app.grpc_target.method.as_str() {
+            "" => title.push_str(" ─ gRPC, no method"),
+            method => title.push_str(&format!(" ─ gRPC {}", method)),
+        }
+    }
+    match (app.request_options.follow_redirects, app.request_options.max_redirects) {
+        (Some(false), _) => title.push_str(" ─ redirects off"),
+        (_, Some(max)) => title.push_str(&format!(" ─ max {} redirects", max)),
+        _ => {}
+    }
+    if let Some(version) = app.request_options.http_version {
+        title.push_str(&format!(" ─ {}", version.as_str()));
+    }
+    if app.request_options.bypass_proxy {
+        title.push_str(" ─ no proxy");
+    }
     let url_block = Block::default()
-        .title("URL")
+        .title(title)
         .borders(Borders::ALL)
         .border_style(style_for_field(Field::Url, app));
 
@@ -162,41 +192,123 @@ fn draw_send_button(f: &mut Frame, app: &App, area: Rect) {
 }
 
 pub fn draw_request_body(f: &mut Frame, app: &mut App, area: Rect) {
+    if app.graphql.is_some() {
+        return draw_graphql_body(f, app, area);
+    }
+    // gRPC bodies are the request message as JSON
+    let title = match app.grpc_method().filter(|_| app.is_grpc()) {
+        Some(method) => format!("Request Message ({})", method.input().full_name()),
+        None => "Request Body".to_string(),
+    };
     let body_block = Block::default()
-        .title("Request Body")
+        .title(title)
         .borders(Borders::ALL)
         .border_style(style_for_field(Field::RequestBody, app));
 
-    let body = if app.input_mode == InputMode::Editing(Field::RequestBody) {
-        let body_chars: Vec<char> = app.body.chars().collect();
-        let pos = app.cursor_position.min(body_chars.len());
-        
-        let mut styled_spans = Vec::new();
-        
-        // Text before cursor
-        if pos > 0 {
-            styled_spans.push(Span::raw(body_chars[..pos].iter().collect::<String>()));
+    let editing = app.input_mode == InputMode::Editing(Field::RequestBody);
+    let inner_height = body_block.inner(area).height;
+    let body = editor(&app.body, editing.then_some(app.cursor_position), inner_height);
+    f.render_widget(body.block(body_block), area);
+}
+
+// Query and variables panes of a GraphQL body, with the completion popup
+// while the query is edited
+fn draw_graphql_body(f: &mut Frame, app: &App, area: Rect) {
+    let Some(gql) = &app.graphql else { return };
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([Constraint::Percentage(65), Constraint::Percentage(35)])
+        .split(area);
+
+    let mut query_title = vec![Span::raw("GraphQL Query")];
+    if !gql.operation_name.is_empty() {
+        query_title.push(Span::styled(format!(" ─ {}", gql.operation_name), Style::default().fg(Color::Cyan)));
+    }
+    if app.graphql_schema.is_some() {
+        query_title.push(Span::styled(" ─ schema", Style::default().fg(Color::DarkGray)));
+    }
+    let mut variables_title = vec![Span::raw("Variables")];
+    if !gql.variables.trim().is_empty() && serde_json::from_str::<serde_json::Value>(&gql.variables).is_err() {
+        variables_title.push(Span::styled(" ─ invalid JSON", Style::default().fg(Color::Red)));
+    }
+
+    let panes = [
+        (GraphQlPane::Query, chunks[0], query_title, &gql.query),
+        (GraphQlPane::Variables, chunks[1], variables_title, &gql.variables),
+    ];
+    for (pane, area, title, text) in panes {
+        let focused = app.active_field == Field::RequestBody && app.graphql_pane == pane;
+        let editing = focused && app.input_mode == InputMode::Editing(Field::RequestBody);
+        let mut style = Style::default();
+        if focused {
+            style = style.fg(Color::Yellow);
         }
-        
-        // Cursor
-        if pos < body_chars.len() {
-            styled_spans.push(Span::styled(
-                body_chars[pos].to_string(),
-                Style::default().bg(Color::Yellow)
-            ));
-            
-            // Text after cursor
-            if pos + 1 < body_chars.len() {
-                styled_spans.push(Span::raw(body_chars[pos + 1..].iter().collect::<String>()));
-            }
-        } else {
-            styled_spans.push(Span::styled(" ", Style::default().bg(Color::Yellow)));
+        if editing {
+            style = style.add_modifier(Modifier::BOLD);
         }
-        
-        Paragraph::new(Line::from(styled_spans))
-    } else {
-        Paragraph::new(app.body.as_str())
+        let block = Block::default()
+            .title(Line::from(title))
+            .borders(Borders::ALL)
+            .border_style(style);
+        let inner = block.inner(area);
+        f.render_widget(editor(text, editing.then_some(app.cursor_position), inner.height).block(block), area);
+
+        if editing && pane == GraphQlPane::Query && !app.completions.is_empty() {
+            draw_completions(f, app, text, inner);
+        }
+    }
+}
+
+fn draw_completions(f: &mut Frame, app: &App, text: &str, area: Rect) {
+    if area.height == 0 {
+        return;
+    }
+    let cursor = app.cursor_position.min(text.len());
+    let line = text[..cursor].matches('\n').count() as u16;
+    let column = text[..cursor].rsplit('\n').next().map(|l| l.chars().count()).unwrap_or(0) as u16;
+    let scroll = line.saturating_sub(area.height.saturating_sub(1));
+
+    let width = app.completions.iter().map(|c| c.len()).max().unwrap_or(0) as u16 + 4;
+    let height = (app.completions.len() as u16).min(8) + 2;
+    let popup = Rect {
+        x: (area.x + column).min(area.right().saturating_sub(width)),
+        y: (area.y + line - scroll + 1).min(f.size().bottom().saturating_sub(height)),
+        width: width.min(area.width),
+        height,
     };
+    let items: Vec<ListItem> = app.completions.iter().map(|c| ListItem::new(c.as_str())).collect();
+    let list = List::new(items)
+        .block(Block::default().borders(Borders::ALL).border_style(Style::default().fg(Color::Cyan)))
+        .highlight_style(Style::default().fg(Color::Black).bg(Color::Cyan));
+    f.render_widget(Clear, popup);
+    f.render_stateful_widget(list, popup, &mut ListState::default().with_selected(Some(app.completion_index)));
+}
 
-    f.render_widget(body.block(body_block), area);
+// Multi-line text with the cursor (a byte offset) highlighted, scrolled so
+// that the cursor line stays visible
+fn editor(text: &str, cursor: Option<usize>, height: u16) -> Paragraph<'static> {
+    let cursor_style = Style::default().bg(Color::Yellow);
+    let mut lines = Vec::new();
+    let mut cursor_line = 0;
+    let mut offset = 0;
+    for (i, line) in text.split('\n').enumerate() {
+        let end = offset + line.len();
+        match cursor.filter(|c| (offset..=end).contains(c) && line.is_char_boundary(c - offset)) {
+            Some(c) => {
+                cursor_line = i;
+                let (before, rest) = line.split_at(c - offset);
+                let mut rest = rest.chars();
+                let at = rest.next().map(String::from).unwrap_or_else(|| " ".to_string());
+                lines.push(Line::from(vec![
+                    Span::raw(before.to_string()),
+                    Span::styled(at, cursor_style),
+                    Span::raw(rest.as_str().to_string()),
+                ]));
+            }
+            None => lines.push(Line::raw(line.to_string())),
+        }
+        offset = end + 1;
+    }
+    let scroll = (cursor_line as u16).saturating_sub(height.saturating_sub(1));
+    Paragraph::new(lines).scroll((scroll, 0))
 }
@@ -2,90 +2,315 @@ use ratatui::{
     prelude::*,
     widgets::{Block, Borders, Paragraph},
 };
-use crate::app::App;
-use serde_json::Value;
+use crate::app::{App, Field, InputMode};
+use crate::utils::format::{formatter_for, wrap_points, TokenKind};
+use crate::utils::http::hex_dump;
 use ratatui::text::{Line as TLine, Span as TSpan};
 
 pub fn draw_response_body(f: &mut Frame, app: &mut App, area: Rect) {
-    let body_block = Block::default()
-        .title("Body")
-        .borders(Borders::ALL);
-
-    let formatted_body = if let Some(metadata) = &app.response_metadata {
-        let content_type = metadata
-            .response_headers
-            .get("content-type")
-            .map(|s| s.as_str())
-            .unwrap_or("");
-        if content_type.contains("application/json") {
-            if let Some(body) = &app.response {
-                if let Ok(json) = serde_json::from_str::<Value>(body) {
-                    serde_json::to_string_pretty(&json)
-                        .unwrap_or_else(|_| body.clone())
-                } else {
-                    body.clone()
-                }
-            } else {
-                String::new()
-            }
+    let mut title = vec![TSpan::raw("Body")];
+    let is_binary = app.response_metadata.as_ref().map(|m| m.is_binary).unwrap_or(false);
+    if let Some(formatter) = formatter_for(app.response_content_type()).filter(|_| !is_binary) {
+        title.push(TSpan::styled(format!(" ({})", formatter.name()), Style::default().fg(Color::DarkGray)));
+    }
+    let error_count = graphql_error_count(app);
+    if error_count > 0 {
+        title.push(TSpan::styled(
+            format!(" ─ {} error{}", error_count, if error_count == 1 { "" } else { "s" }),
+            Style::default().fg(Color::Red),
+        ));
+    }
+    let summary = app.response_search.summary();
+    if !summary.is_empty() {
+        title.push(TSpan::raw(" ─ "));
+        title.push(TSpan::styled(summary, Style::default().fg(Color::Yellow)));
+    }
+
+    let mut body_block = Block::default()
+        .title(TLine::from(title))
+        .borders(Borders::ALL)
+        .border_style(if app.active_field == Field::ResponseBody {
+            Style::default().fg(Color::Yellow)
         } else {
-            app.response.clone().unwrap_or_default()
+            Style::default()
+        });
+    if let Some(message) = &app.status_message {
+        body_block = body_block.title_bottom(Line::from(format!(" {} ", message)).right_aligned());
+    }
+
+    let inner = body_block.inner(area);
+    f.render_widget(body_block, area);
+
+    let searching = app.input_mode == InputMode::Editing(Field::ResponseSearch);
+    let filtering = app.input_mode == InputMode::Editing(Field::ResponseFilter);
+    let show_search = searching || app.response_search.is_active();
+    let show_filter = filtering || (app.response_is_json() && !is_binary);
+
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([
+            Constraint::Min(0),
+            Constraint::Length(show_search as u16),
+            Constraint::Length(show_filter as u16),
+        ])
+        .split(inner);
+    let (content_area, search_area) = (chunks[0], show_search.then_some(chunks[1]));
+    if show_filter {
+        draw_filter_bar(f, app, filtering, chunks[2]);
+    }
+    app.response_view_height = content_area.height as usize;
+    app.set_response_view_width(content_area.width as usize);
+
+    if let (Some(metadata), Some(bytes)) = (&app.response_metadata, &app.response_bytes) {
+        if metadata.is_binary {
+            // The first line tells what the dump is
+            app.response_view_height = content_area.height.saturating_sub(1) as usize;
+            draw_hex_view(f, app, bytes, content_area);
+            if let Some(search_area) = search_area {
+                draw_search_bar(f, app, searching, search_area);
+            }
+            return;
         }
+    }
+
+    if app.response_json.is_some() {
+        draw_json_tree(f, app, content_area);
     } else {
-        app.response.clone().unwrap_or_default()
-    };
+        draw_text(f, app, content_area);
+    }
 
-    let paragraph = if let Some(metadata) = &app.response_metadata {
-        let content_type = metadata
-            .response_headers
-            .get("content-type")
-            .map(|s| s.as_str())
-            .unwrap_or("");
-        if content_type.contains("application/json") {
-            Paragraph::new(colorize_json(&formatted_body))
-                .block(body_block)
-                .wrap(ratatui::widgets::Wrap { trim: false })
-                .scroll((app.response_scroll, 0))
-        } else {
-            Paragraph::new(formatted_body)
-                .block(body_block)
-                .wrap(ratatui::widgets::Wrap { trim: false })
-                .scroll((app.response_scroll, 0))
+    if let Some(search_area) = search_area {
+        draw_search_bar(f, app, searching, search_area);
+    }
+}
+
+// Plain and pretty-printed bodies. Lines are wrapped here rather than by the
+// Paragraph so that the scroll position counts the same rows as search jumps,
+// and only the visible lines are highlighted.
+fn draw_text(f: &mut Frame, app: &App, area: Rect) {
+    let formatter = formatter_for(app.response_content_type());
+    let height = area.height as usize;
+    let first = app.response_line_at_row(app.response_scroll);
+    let mut skip = app.response_scroll.saturating_sub(app.response_row_of_line(first));
+    let mut rows = Vec::new();
+    for (i, line) in app.response_lines().iter().enumerate().skip(first) {
+        if rows.len() >= height {
+            break;
         }
-    } else {
-        Paragraph::new(formatted_body)
-            .block(body_block)
-            .wrap(ratatui::widgets::Wrap { trim: false })
-            .scroll((app.response_scroll, 0))
-    };
+        let spans = match formatter {
+            Some(formatter) => formatter
+                .highlight(line)
+                .into_iter()
+                .map(|(kind, text)| TSpan::styled(text, token_style(kind)))
+                .collect(),
+            None => vec![TSpan::raw(line.clone())],
+        };
+        let highlighted = highlight_matches(spans, &app.response_search.matches_on_line(i));
+        let points = wrap_points(line, area.width as usize);
+        rows.extend(split_line(highlighted, &points).into_iter().skip(skip));
+        skip = 0;
+    }
+    rows.truncate(height);
+    f.render_widget(Paragraph::new(rows), area);
+}
 
-    f.render_widget(paragraph, area);
+// Breaks a highlighted line into rows at the byte offsets from `wrap_points`.
+fn split_line<'a>(line: TLine<'a>, points: &[usize]) -> Vec<TLine<'a>> {
+    let mut rows = vec![Vec::new(); points.len()];
+    let mut offset = 0;
+    for span in line.spans {
+        let end = offset + span.content.len();
+        for (row, &start) in points.iter().enumerate() {
+            let stop = points.get(row + 1).copied().unwrap_or(end);
+            let (from, to) = (start.max(offset), stop.min(end));
+            if from < to {
+                rows[row].push(TSpan::styled(span.content[from - offset..to - offset].to_string(), span.style));
+            }
+        }
+        offset = end;
+    }
+    rows.into_iter().map(TLine::from).collect()
 }
 
-// Helper function to colorize each line of a JSON string.
-// Keys are colored yellow, and string values are colored green.
-fn colorize_json(json_str: &str) -> Vec<TLine> {
-    let mut lines_vec = Vec::new();
-    for line in json_str.lines() {
-        let mut spans = Vec::new();
-        let parts: Vec<&str> = line.split('"').collect();
-        for (i, part) in parts.iter().enumerate() {
-            if i % 2 == 0 {
-                spans.push(TSpan::raw(*part));
+// Structural JSON view. Rows are not wrapped so that a row always maps to one
+// screen line, which keeps the selection and search scrolling exact.
+fn draw_json_tree(f: &mut Frame, app: &App, area: Rect) {
+    let json = match &app.response_json {
+        Some(json) => json,
+        None => return,
+    };
+    let focused = app.active_field == Field::ResponseBody;
+    let start = app.response_scroll;
+    let graphql = app.graphql.is_some();
+
+    let lines: Vec<TLine> = app.json_tree.rows(json)
+        .iter()
+        .enumerate()
+        .skip(start)
+        .take(area.height as usize)
+        .map(|(i, row)| {
+            let is_error = graphql && is_graphql_error_path(&row.path);
+            let spans = row.segments()
+                .into_iter()
+                .map(|(kind, text)| match kind {
+                    TokenKind::Marker | TokenKind::Summary => TSpan::styled(text, token_style(kind)),
+                    _ if is_error => TSpan::styled(text, Style::default().fg(Color::Red)),
+                    _ => TSpan::styled(text, token_style(kind)),
+                })
+                .collect();
+            let line = highlight_matches(spans, &app.response_search.matches_on_line(i));
+            if focused && i == app.json_tree.selected {
+                line.style(Style::default().add_modifier(Modifier::REVERSED))
             } else {
-                // If the part is immediately followed by a colon in the next segment, treat it as a key.
-                let style = if i + 1 < parts.len() && parts[i + 1].trim_start().starts_with(':') {
-                    Style::default().fg(Color::Yellow)
-                } else {
-                    Style::default().fg(Color::Green)
-                };
-                spans.push(TSpan::styled(*part, style));
+                line
             }
-            if i < parts.len() - 1 {
-                spans.push(TSpan::raw("\""));
+        })
+        .collect();
+
+    f.render_widget(Paragraph::new(lines), area);
+}
+
+// Number of entries in the `errors` array of a GraphQL response
+fn graphql_error_count(app: &App) -> usize {
+    if app.graphql.is_none() {
+        return 0;
+    }
+    app.response_json.as_ref()
+        .and_then(|json| json.get("errors"))
+        .and_then(|errors| errors.as_array())
+        .map(Vec::len)
+        .unwrap_or(0)
+}
+
+fn is_graphql_error_path(path: &str) -> bool {
+    path.strip_prefix("$.errors")
+        .map(|rest| rest.is_empty() || rest.starts_with('[') || rest.starts_with('.'))
+        .unwrap_or(false)
+}
+
+fn token_style(kind: TokenKind) -> Style {
+    match kind {
+        TokenKind::Plain | TokenKind::Punctuation => Style::default(),
+        TokenKind::Marker => Style::default().fg(Color::DarkGray),
+        TokenKind::Key => Style::default().fg(Color::Yellow),
+        TokenKind::String => Style::default().fg(Color::Green),
+        TokenKind::Number => Style::default().fg(Color::Cyan),
+        TokenKind::Bool => Style::default().fg(Color::Magenta),
+        TokenKind::Null => Style::default().fg(Color::Red),
+        TokenKind::Summary => Style::default().fg(Color::DarkGray).add_modifier(Modifier::ITALIC),
+        TokenKind::Tag => Style::default().fg(Color::Blue),
+        TokenKind::Attribute => Style::default().fg(Color::Cyan),
+        TokenKind::Comment => Style::default().fg(Color::DarkGray),
+    }
+}
+
+fn draw_filter_bar(f: &mut Frame, app: &App, editing: bool, area: Rect) {
+    let mut spans = vec![TSpan::styled("filter: ", Style::default().fg(Color::Cyan))];
+    if editing {
+        spans.extend(input_with_cursor(&app.response_filter, app.response_filter_cursor));
+    } else if app.response_filter.is_empty() {
+        spans.push(TSpan::styled(
+            "press 'f' for a JSONPath filter, e.g. $.items[*].id",
+            Style::default().fg(Color::DarkGray),
+        ));
+    } else {
+        spans.push(TSpan::raw(app.response_filter.clone()));
+    }
+    if let Some(error) = &app.response_filter_error {
+        spans.push(TSpan::styled(format!("  {}", error), Style::default().fg(Color::Red)));
+    }
+    f.render_widget(Paragraph::new(TLine::from(spans)), area);
+}
+
+fn input_with_cursor(text: &str, cursor: usize) -> Vec<TSpan<'static>> {
+    let chars: Vec<char> = text.chars().collect();
+    let pos = cursor.min(chars.len());
+    let mut spans = vec![TSpan::raw(chars[..pos].iter().collect::<String>())];
+    if pos < chars.len() {
+        spans.push(TSpan::styled(chars[pos].to_string(), Style::default().bg(Color::Yellow)));
+        spans.push(TSpan::raw(chars[pos + 1..].iter().collect::<String>()));
+    } else {
+        spans.push(TSpan::styled(" ", Style::default().bg(Color::Yellow)));
+    }
+    spans
+}
+
+fn draw_search_bar(f: &mut Frame, app: &App, editing: bool, area: Rect) {
+    let search = &app.response_search;
+    let mut spans = vec![TSpan::styled("/", Style::default().fg(Color::Yellow))];
+    if editing {
+        spans.extend(input_with_cursor(&search.query, search.cursor));
+    } else {
+        spans.push(TSpan::raw(search.query.clone()));
+        spans.push(TSpan::styled("  (n/N next/prev, Esc clear)", Style::default().fg(Color::DarkGray)));
+    }
+    if let Some(error) = &search.error {
+        spans.push(TSpan::styled(format!("  {}", error), Style::default().fg(Color::Red)));
+    }
+    f.render_widget(Paragraph::new(TLine::from(spans)), area);
+}
+
+// Binary bodies are shown as a hex/ASCII dump. Only the visible window is
+// formatted, since a multi-megabyte body would otherwise be re-dumped every frame.
+fn draw_hex_view(f: &mut Frame, app: &App, bytes: &[u8], area: Rect) {
+    let start = app.response_scroll;
+    let lines: Vec<TLine> = hex_dump(bytes, start, area.height.saturating_sub(1) as usize)
+        .into_iter()
+        .enumerate()
+        .map(|(i, line)| {
+            // offset | hex | ascii
+            let (offset, rest) = line.split_at(8);
+            let (hex, ascii) = rest.split_at(rest.find('|').unwrap_or(rest.len()));
+            let spans = vec![
+                TSpan::styled(offset.to_string(), Style::default().fg(Color::DarkGray)),
+                TSpan::raw(hex.to_string()),
+                TSpan::styled(ascii.to_string(), Style::default().fg(Color::Cyan)),
+            ];
+            highlight_matches(spans, &app.response_search.matches_on_line(start + i))
+        })
+        .collect();
+
+    let info = TLine::from(format!("binary, {} bytes ('s' to save)", bytes.len()))
+        .style(Style::default().fg(Color::DarkGray));
+    let mut all = vec![info];
+    all.extend(lines);
+    f.render_widget(Paragraph::new(all), area);
+}
+
+// Overlays search match highlighting onto already-styled spans. `ranges` are
+// byte offsets into the concatenated line text.
+fn highlight_matches<'a>(spans: Vec<TSpan<'a>>, ranges: &[(usize, usize, bool)]) -> TLine<'a> {
+    if ranges.is_empty() {
+        return TLine::from(spans);
+    }
+
+    let mut result = Vec::new();
+    let mut offset = 0;
+    for span in spans {
+        let text = span.content.to_string();
+        let span_end = offset + text.len();
+        let mut pos = offset;
+
+        for &(start, end, current) in ranges {
+            let (start, end) = (start.max(pos), end.min(span_end));
+            if start >= end {
+                continue;
             }
+            if start > pos {
+                result.push(TSpan::styled(text[pos - offset..start - offset].to_string(), span.style));
+            }
+            let style = if current {
+                span.style.bg(Color::Yellow).fg(Color::Black)
+            } else {
+                span.style.bg(Color::DarkGray)
+            };
+            result.push(TSpan::styled(text[start - offset..end - offset].to_string(), style));
+            pos = end;
         }
-        lines_vec.push(TLine::from(spans));
+        if pos < span_end {
+            result.push(TSpan::styled(text[pos - offset..].to_string(), span.style));
+        }
+        offset = span_end;
     }
-    lines_vec
-} 
\ No newline at end of file
+    TLine::from(result)
+}
@@ -0,0 +1,37 @@
+use ratatui::{
+    prelude::*,
+    widgets::{Block, Borders, Paragraph, Wrap},
+};
+use crate::app::App;
+use super::tabs_title;
+
+// Result of checking the response against the OpenAPI operation the request
+// was imported from: one line per violation, or a tick when it matches.
+pub fn draw_response_contract(f: &mut Frame, app: &mut App, area: Rect) {
+    let contract_block = Block::default()
+        .title(tabs_title(app, area.width))
+        .borders(Borders::ALL);
+
+    let dim = Style::default().fg(Color::DarkGray);
+    let red = Style::default().fg(Color::Red);
+    let mut lines = Vec::new();
+    match &app.contract_check {
+        Some(Ok(check)) if check.violations.is_empty() => {
+            lines.push(Line::styled(format!("✓ Matches {}", check.operation), Style::default().fg(Color::Green)));
+        }
+        Some(Ok(check)) => {
+            lines.push(Line::raw(check.operation.clone()));
+            for violation in &check.violations {
+                lines.push(Line::styled(format!("✗ {}", violation), red));
+            }
+        }
+        Some(Err(e)) => lines.push(Line::styled(format!("Could not check the response: {}", e), red)),
+        None if app.openapi_link.is_some() => lines.push(Line::styled("Checked against the OpenAPI spec when sent", dim)),
+        None => lines.push(Line::styled("Not linked to an OpenAPI operation (import a spec from Collections)", dim)),
+    }
+
+    let paragraph = Paragraph::new(lines)
+        .block(contract_block)
+        .wrap(Wrap { trim: false });
+    f.render_widget(paragraph, area);
+}
@@ -0,0 +1,56 @@
+use ratatui::{
+    prelude::*,
+    widgets::{Block, Borders, Paragraph, Wrap},
+};
+use crate::app::App;
+use super::tabs_title;
+
+// Cookies set by the current response, one block of lines per `Set-Cookie`.
+pub fn draw_response_cookies(f: &mut Frame, app: &mut App, area: Rect) {
+    let cookies_block = Block::default()
+        .title(tabs_title(app, area.width))
+        .borders(Borders::ALL);
+
+    let dim = Style::default().fg(Color::DarkGray);
+    let mut lines = Vec::new();
+    for (raw, cookie) in app.response_cookies() {
+        match cookie {
+            Some(cookie) => {
+                lines.push(Line::from(vec![
+                    Span::styled(cookie.name.clone(), Style::default().fg(Color::Yellow)),
+                    Span::raw(" = "),
+                    Span::styled(cookie.value.clone(), Style::default().fg(Color::Green)),
+                ]));
+                let mut flags = Vec::new();
+                if cookie.secure {
+                    flags.push("Secure".to_string());
+                }
+                if cookie.http_only {
+                    flags.push("HttpOnly".to_string());
+                }
+                if let Some(same_site) = &cookie.same_site {
+                    flags.push(format!("SameSite={}", same_site));
+                }
+                lines.push(Line::styled(
+                    format!("  {}{}  expires {}", cookie.domain, cookie.path, cookie.expires_display()),
+                    dim,
+                ));
+                if !flags.is_empty() {
+                    lines.push(Line::styled(format!("  {}", flags.join(" ")), dim));
+                }
+            }
+            None => {
+                lines.push(Line::from(raw));
+                lines.push(Line::styled("  (rejected: malformed, foreign or public suffix domain, or Secure over http)", Style::default().fg(Color::Red)));
+            }
+        }
+    }
+    if lines.is_empty() {
+        lines.push(Line::styled("No cookies set by this response", dim));
+    }
+
+    let paragraph = Paragraph::new(lines)
+        .block(cookies_block)
+        .wrap(Wrap { trim: false });
+    f.render_widget(paragraph, area);
+}
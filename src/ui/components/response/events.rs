@@ -0,0 +1,62 @@
+use ratatui::{
+    prelude::*,
+    widgets::{Block, Borders, Paragraph},
+};
+use crate::app::{App, Field};
+use crate::models::sse::StreamEntry;
+
+// Server-Sent Events as they arrive, newest at the bottom: arrival time,
+// event name, id and data on one line. `event_stream_scroll` counts entries
+// scrolled back from the end.
+pub fn draw_event_stream(f: &mut Frame, app: &mut App, area: Rect) {
+    let Some(stream) = &app.event_stream else { return };
+    let entries = stream.log.entries();
+    let dim = Style::default().fg(Color::DarkGray);
+    let events = entries.iter().filter(|e| matches!(e, StreamEntry::Event(_))).count();
+
+    let mut title = vec![Span::raw(format!("Events ({})", events))];
+    title.push(if stream.log.is_open() {
+        Span::styled(" ─ streaming", Style::default().fg(Color::Green))
+    } else {
+        Span::styled(" ─ closed", dim)
+    });
+    let mut block = Block::default()
+        .title(Line::from(title))
+        .borders(Borders::ALL)
+        .border_style(if app.active_field == Field::ResponseBody {
+            Style::default().fg(Color::Yellow)
+        } else {
+            Style::default()
+        });
+    if let Some(message) = &app.status_message {
+        block = block.title_bottom(Line::from(format!(" {} ", message)).right_aligned());
+    }
+    let inner = block.inner(area);
+    f.render_widget(block, area);
+
+    let height = inner.height as usize;
+    let end = entries.len().saturating_sub(app.event_stream_scroll.min(entries.len()));
+    let start = end.saturating_sub(height);
+    let time = |at: &chrono::DateTime<chrono::Utc>| {
+        Span::styled(at.with_timezone(&chrono::Local).format("%H:%M:%S%.3f ").to_string(), dim)
+    };
+    let lines: Vec<Line> = entries[start..end]
+        .iter()
+        .map(|entry| match entry {
+            StreamEntry::Event(event) => {
+                let mut spans = vec![
+                    time(&event.received_at),
+                    Span::styled(event.event.clone(), Style::default().fg(Color::Cyan)),
+                ];
+                if let Some(id) = &event.id {
+                    spans.push(Span::styled(format!(" #{}", id), Style::default().fg(Color::Magenta)));
+                }
+                spans.push(Span::raw(format!(" {}", event.data.replace('\n', "⏎"))));
+                Line::from(spans)
+            }
+            StreamEntry::Info { at, text } => Line::from(vec![time(at), Span::styled(format!("• {}", text), dim)]),
+        })
+        .collect();
+
+    f.render_widget(Paragraph::new(lines), inner);
+}
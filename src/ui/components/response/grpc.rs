@@ -0,0 +1,56 @@
+use ratatui::{
+    prelude::*,
+    widgets::{Block, Borders, Paragraph},
+};
+use crate::app::{App, Field};
+use crate::models::grpc::GrpcEntry;
+
+// Messages of a server-streaming gRPC call, newest at the bottom, one per
+// line as compact JSON. `grpc_stream_scroll` counts entries scrolled back
+// from the end.
+pub fn draw_grpc_stream(f: &mut Frame, app: &mut App, area: Rect) {
+    let Some(stream) = &app.grpc_stream else { return };
+    let entries = stream.log.entries();
+    let dim = Style::default().fg(Color::DarkGray);
+    let messages = entries.iter().filter(|e| matches!(e, GrpcEntry::Message { .. })).count();
+
+    let mut title = vec![Span::raw(format!("Messages ({})", messages))];
+    title.push(if stream.log.is_open() {
+        Span::styled(" ─ streaming", Style::default().fg(Color::Green))
+    } else {
+        Span::styled(" ─ finished", dim)
+    });
+    let mut block = Block::default()
+        .title(Line::from(title))
+        .borders(Borders::ALL)
+        .border_style(if app.active_field == Field::ResponseBody {
+            Style::default().fg(Color::Yellow)
+        } else {
+            Style::default()
+        });
+    if let Some(message) = &app.status_message {
+        block = block.title_bottom(Line::from(format!(" {} ", message)).right_aligned());
+    }
+    let inner = block.inner(area);
+    f.render_widget(block, area);
+
+    let height = inner.height as usize;
+    let end = entries.len().saturating_sub(app.grpc_stream_scroll.min(entries.len()));
+    let start = end.saturating_sub(height);
+    let time = |at: &chrono::DateTime<chrono::Utc>| {
+        Span::styled(at.with_timezone(&chrono::Local).format("%H:%M:%S%.3f ").to_string(), dim)
+    };
+    let lines: Vec<Line> = entries[start..end]
+        .iter()
+        .map(|entry| match entry {
+            GrpcEntry::Message { received_at, json } => Line::from(vec![
+                time(received_at),
+                Span::styled("← ", Style::default().fg(Color::Green)),
+                Span::raw(json.clone()),
+            ]),
+            GrpcEntry::Info { at, text } => Line::from(vec![time(at), Span::styled(format!("• {}", text), dim)]),
+        })
+        .collect();
+
+    f.render_widget(Paragraph::new(lines), inner);
+}
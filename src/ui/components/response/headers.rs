@@ -3,10 +3,11 @@ use ratatui::{
     widgets::{Block, Borders, List, ListItem},
 };
 use crate::app::App;
+use super::tabs_title;
 
 pub fn draw_response_headers(f: &mut Frame, app: &mut App, area: Rect) {
     let headers_block = Block::default()
-        .title("Headers")
+        .title(tabs_title(app, area.width))
         .borders(Borders::ALL);
 
     let headers: Vec<ListItem> = if let Some(metadata) = &app.response_metadata {
@@ -24,4 +25,4 @@ pub fn draw_response_headers(f: &mut Frame, app: &mut App, area: Rect) {
         .block(headers_block);
 
     f.render_widget(headers_list, area);
-} 
\ No newline at end of file
+}
@@ -1,7 +1,69 @@
 mod status;
 mod headers;
+mod cookies;
+mod redirects;
+mod timing;
 mod body;
+mod websocket;
+mod events;
+mod grpc;
+mod contract;
+
+use ratatui::prelude::*;
+use crate::app::{App, ResponseTab};
 
 pub use status::draw_response_status;
 pub use headers::draw_response_headers;
-pub use body::draw_response_body; 
\ No newline at end of file
+pub use cookies::draw_response_cookies;
+pub use redirects::draw_response_redirects;
+pub use timing::draw_response_timing;
+pub use body::draw_response_body;
+pub use websocket::draw_websocket_log;
+pub use events::draw_event_stream;
+pub use grpc::draw_grpc_stream;
+pub use contract::draw_response_contract;
+
+// Title for the panel next to the body, e.g. "Headers │ Cookies (2) │ Redirects"
+// with the active tab highlighted. When the tabs don't fit in `width`, only the
+// active one is shown with its position. `[`/`]` switch tabs.
+fn tabs_title(app: &App, width: u16) -> Line<'static> {
+    let tabs = ResponseTab::all();
+    let labels: Vec<String> = tabs
+        .iter()
+        .map(|tab| {
+            let count = app.response_metadata.as_ref().map(|m| match tab {
+                ResponseTab::Headers | ResponseTab::Timing => 0,
+                ResponseTab::Cookies => m.set_cookies.len(),
+                ResponseTab::Redirects => m.redirects.len(),
+                ResponseTab::Contract => match &app.contract_check {
+                    Some(Ok(check)) => check.violations.len(),
+                    _ => 0,
+                },
+            });
+            match count {
+                Some(count) if count > 0 => format!("{} ({})", tab.as_str(), count),
+                _ => tab.as_str().to_string(),
+            }
+        })
+        .collect();
+    let active = tabs.iter().position(|t| *t == app.response_tab).unwrap_or(0);
+    let active_style = Style::default().fg(Color::Yellow).add_modifier(Modifier::BOLD);
+    let dim = Style::default().fg(Color::DarkGray);
+
+    let full_width: usize = labels.iter().map(|l| l.chars().count()).sum::<usize>() + 3 * (labels.len() - 1);
+    if full_width + 2 > width as usize {
+        return Line::from(vec![
+            Span::styled(labels[active].clone(), active_style),
+            Span::styled(format!(" {}/{} [ ]", active + 1, labels.len()), dim),
+        ]);
+    }
+
+    let mut spans = Vec::new();
+    for (i, label) in labels.into_iter().enumerate() {
+        if i > 0 {
+            spans.push(Span::styled(" │ ", dim));
+        }
+        spans.push(Span::styled(label, if i == active { active_style } else { dim }));
+    }
+    Line::from(spans)
+}
@@ -0,0 +1,52 @@
+use ratatui::{
+    prelude::*,
+    widgets::{Block, Borders, Paragraph, Wrap},
+};
+use crate::app::App;
+use super::tabs_title;
+
+// The redirect chain of the current response: every hop with its status and
+// Location, then the URL that produced the final response.
+pub fn draw_response_redirects(f: &mut Frame, app: &mut App, area: Rect) {
+    let redirects_block = Block::default()
+        .title(tabs_title(app, area.width))
+        .borders(Borders::ALL);
+
+    let dim = Style::default().fg(Color::DarkGray);
+    let mut lines = Vec::new();
+    if let Some(metadata) = &app.response_metadata {
+        for (i, hop) in metadata.redirects.iter().enumerate() {
+            lines.push(Line::from(vec![
+                Span::raw(format!("{}. ", i + 1)),
+                Span::styled(hop.status.to_string(), Style::default().fg(Color::Blue)),
+                Span::raw(format!(" {}", hop.url)),
+            ]));
+            lines.push(Line::styled(format!("   Location: {}", hop.location), dim));
+        }
+        if metadata.redirects.is_empty() {
+            lines.push(Line::styled("No redirects", dim));
+        } else {
+            lines.push(Line::from(vec![
+                Span::raw("→ "),
+                Span::styled(metadata.status.to_string(), Style::default().fg(Color::Green)),
+                Span::raw(format!(" {}", metadata.url)),
+            ]));
+        }
+    }
+
+    let (follow, max) = app.redirect_policy();
+    lines.push(Line::raw(""));
+    lines.push(Line::styled(
+        if follow {
+            format!("Following up to {} redirects ('r' on the URL bar to change)", max)
+        } else {
+            "Not following redirects ('r' on the URL bar to change)".to_string()
+        },
+        dim,
+    ));
+
+    let paragraph = Paragraph::new(lines)
+        .block(redirects_block)
+        .wrap(Wrap { trim: false });
+    f.render_widget(paragraph, area);
+}
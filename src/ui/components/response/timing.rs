@@ -0,0 +1,70 @@
+use ratatui::{
+    prelude::*,
+    widgets::{Block, Borders, Paragraph, Wrap},
+};
+use crate::app::App;
+use super::tabs_title;
+
+// Waterfall of the request phases: each bar starts where the previous phase
+// ended, so network and server time are easy to tell apart.
+pub fn draw_response_timing(f: &mut Frame, app: &mut App, area: Rect) {
+    let timing_block = Block::default()
+        .title(tabs_title(app, area.width))
+        .borders(Borders::ALL);
+    let inner = timing_block.inner(area);
+    f.render_widget(timing_block, area);
+
+    let dim = Style::default().fg(Color::DarkGray);
+    let timings = match &app.response_metadata {
+        Some(metadata) => &metadata.timings,
+        None => {
+            f.render_widget(Paragraph::new(Line::styled("No response yet", dim)), inner);
+            return;
+        }
+    };
+
+    let phases = [
+        ("DNS lookup", timings.dns_ms, Color::Cyan),
+        ("TCP connect", timings.connect_ms, Color::Blue),
+        ("TLS handshake", timings.tls_ms, Color::Magenta),
+        ("Waiting (TTFB)", Some(timings.ttfb_ms), Color::Yellow),
+        ("Download", Some(timings.download_ms), Color::Green),
+    ];
+    let total = timings.total_ms();
+    // label (15) + duration (11)
+    let bar_width = (inner.width as usize).saturating_sub(27).max(1);
+    let scale = |ms: f64| if total > 0.0 { (ms / total * bar_width as f64).round() as usize } else { 0 };
+
+    let mut lines = Vec::new();
+    let mut elapsed = 0.0;
+    for (label, ms, color) in phases {
+        let (duration, bar) = match ms {
+            Some(ms) => {
+                let offset = scale(elapsed).min(bar_width - 1);
+                let width = scale(elapsed + ms).saturating_sub(offset).max(1);
+                elapsed += ms;
+                (format!("{:>8.1} ms", ms), format!("{}{}", " ".repeat(offset), "█".repeat(width)))
+            }
+            None => (format!("{:>11}", "-"), String::new()),
+        };
+        lines.push(Line::from(vec![
+            Span::raw(format!("{:<15}", label)),
+            Span::raw(duration),
+            Span::raw(" "),
+            Span::styled(bar, Style::default().fg(color)),
+        ]));
+    }
+    lines.push(Line::from(vec![
+        Span::styled(format!("{:<15}", "Total"), Style::default().add_modifier(Modifier::BOLD)),
+        Span::styled(format!("{:>8.1} ms", total), Style::default().add_modifier(Modifier::BOLD)),
+    ]));
+    lines.push(Line::raw(""));
+    let note = match app.response_metadata.as_ref().and_then(|m| m.proxy.as_ref()) {
+        Some(proxy) => format!("Sent through {}; connection phases are part of waiting.", proxy),
+        None => "DNS is the request's own lookup; connect and TLS are measured on a probe to the same address. Waiting includes any redirects."
+            .to_string(),
+    };
+    lines.push(Line::styled(note, dim));
+
+    f.render_widget(Paragraph::new(lines).wrap(Wrap { trim: false }), inner);
+}
@@ -0,0 +1,73 @@
+use ratatui::{
+    prelude::*,
+    widgets::{Block, Borders, Paragraph},
+};
+use crate::app::{App, Field};
+use crate::models::websocket::{WsDirection, WsKind};
+
+// Message log of the WebSocket session, newest at the bottom. One message per
+// line; `websocket_scroll` counts lines scrolled back from the end.
+pub fn draw_websocket_log(f: &mut Frame, app: &mut App, area: Rect) {
+    let session = app.websocket.as_ref().filter(|ws| ws.url == app.url.trim());
+    let messages = session.map(|ws| ws.log.entries()).unwrap_or_default();
+    let dim = Style::default().fg(Color::DarkGray);
+
+    let mut title = vec![Span::raw(format!("Messages ({})", messages.len()))];
+    match session {
+        Some(ws) if ws.log.is_open() => title.push(Span::styled(" ─ connected", Style::default().fg(Color::Green))),
+        Some(_) => title.push(Span::styled(" ─ closed", dim)),
+        None => {}
+    }
+    let mut block = Block::default()
+        .title(Line::from(title))
+        .borders(Borders::ALL)
+        .border_style(if app.active_field == Field::ResponseBody {
+            Style::default().fg(Color::Yellow)
+        } else {
+            Style::default()
+        });
+    if let Some(message) = &app.status_message {
+        block = block.title_bottom(Line::from(format!(" {} ", message)).right_aligned());
+    }
+    let inner = block.inner(area);
+    f.render_widget(block, area);
+
+    if session.is_none() {
+        let text = match &app.response {
+            Some(error) => Line::styled(error.clone(), Style::default().fg(Color::Red)),
+            None => Line::styled("Press Go to connect", dim),
+        };
+        f.render_widget(Paragraph::new(text), inner);
+        return;
+    }
+
+    let height = inner.height as usize;
+    let end = messages.len().saturating_sub(app.websocket_scroll.min(messages.len()));
+    let start = end.saturating_sub(height);
+    let lines: Vec<Line> = messages[start..end]
+        .iter()
+        .map(|message| {
+            let (arrow, color) = match message.direction {
+                WsDirection::Sent => ("→", Color::Cyan),
+                WsDirection::Received => ("←", Color::Green),
+                WsDirection::Info => ("•", Color::DarkGray),
+            };
+            let mut spans = vec![
+                Span::styled(message.timestamp.with_timezone(&chrono::Local).format("%H:%M:%S%.3f ").to_string(), dim),
+                Span::styled(format!("{} ", arrow), Style::default().fg(color)),
+            ];
+            if message.direction != WsDirection::Info && message.kind != WsKind::Text {
+                spans.push(Span::styled(format!("[{}] ", message.kind.as_str()), Style::default().fg(Color::Magenta)));
+            }
+            let text = message.preview().replace('\n', "⏎");
+            spans.push(if message.direction == WsDirection::Info {
+                Span::styled(text, dim)
+            } else {
+                Span::raw(text)
+            });
+            Line::from(spans)
+        })
+        .collect();
+
+    f.render_widget(Paragraph::new(lines), inner);
+}
@@ -0,0 +1,102 @@
+use ratatui::{
+    prelude::*,
+    widgets::{Block, Borders, List, ListItem, ListState, Paragraph, Wrap},
+    style::{Color, Style},
+    text::Line,
+};
+use crate::app::App;
+
+// GraphQL schema explorer: the schema's types, or the fields of the type at
+// the end of `schema_path`, plus the details of the selected entry.
+pub fn draw_schema(f: &mut Frame, app: &mut App, area: Rect) {
+    let count = app.schema_entry_count();
+    if app.schema_selected_index >= count {
+        app.schema_selected_index = count.saturating_sub(1);
+    }
+    let Some(schema) = &app.graphql_schema else { return };
+
+    let layout = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([Constraint::Min(0), Constraint::Length(8)])
+        .split(area);
+
+    let dim = Style::default().fg(Color::DarkGray);
+    let type_style = Style::default().fg(Color::Cyan);
+    let selected = app.schema_selected_index;
+
+    let (title, items, details) = match app.schema_type() {
+        Some(schema_type) => {
+            let items: Vec<ListItem> = schema_type.fields.iter().map(|field| {
+                let mut spans = vec![Span::raw(field.name.clone())];
+                if !field.args.is_empty() {
+                    spans.push(Span::styled("(…)", dim));
+                }
+                if !field.type_display.is_empty() {
+                    spans.push(Span::raw(": "));
+                    spans.push(Span::styled(field.type_display.clone(), type_style));
+                }
+                if field.deprecated {
+                    spans.push(Span::styled(" deprecated", Style::default().fg(Color::Red)));
+                }
+                ListItem::new(Line::from(spans))
+            }).collect();
+
+            let mut details = Vec::new();
+            if let Some(field) = schema_type.fields.get(selected) {
+                let mut signature = vec![Span::raw(field.name.clone())];
+                if !field.type_display.is_empty() {
+                    signature.push(Span::raw(": "));
+                    signature.push(Span::styled(field.type_display.clone(), type_style));
+                }
+                details.push(Line::from(signature));
+                for arg in &field.args {
+                    let default = arg.default_value.as_ref().map(|d| format!(" = {}", d)).unwrap_or_default();
+                    details.push(Line::from(vec![
+                        Span::raw(format!("  {}: ", arg.name)),
+                        Span::styled(arg.type_display.clone(), type_style),
+                        Span::styled(default, dim),
+                    ]));
+                }
+                if let Some(description) = &field.description {
+                    details.push(Line::styled(description.clone(), dim));
+                }
+            } else if let Some(description) = &schema_type.description {
+                details.push(Line::styled(description.clone(), dim));
+            }
+            (
+                format!("{} ({})", app.schema_path.join(" › "), schema_type.kind),
+                items,
+                details,
+            )
+        }
+        None => {
+            let types = schema.browsable_types();
+            let items: Vec<ListItem> = types.iter().map(|t| {
+                ListItem::new(Line::from(vec![
+                    Span::raw(t.name.clone()),
+                    Span::styled(format!(" {}", t.kind), dim),
+                ]))
+            }).collect();
+            let details = types.get(selected)
+                .map(|t| vec![
+                    Line::from(format!("{} fields", t.fields.len())),
+                    Line::styled(t.description.clone().unwrap_or_default(), dim),
+                ])
+                .unwrap_or_default();
+            ("Types".to_string(), items, details)
+        }
+    };
+
+    let list = List::new(items)
+        .block(Block::default()
+            .title(format!("Schema: {} (↑↓ navigate, Enter/→ open type, ←/Backspace back, Esc close)", title))
+            .borders(Borders::ALL))
+        .highlight_style(Style::default().fg(Color::Yellow));
+    let mut list_state = ListState::default().with_selected((count > 0).then_some(selected));
+    f.render_stateful_widget(list, layout[0], &mut list_state);
+
+    let details_block = Block::default()
+        .title("Details")
+        .borders(Borders::ALL);
+    f.render_widget(Paragraph::new(details).wrap(Wrap { trim: false }).block(details_block), layout[1]);
+}
@@ -3,12 +3,12 @@ use ratatui::{
     widgets::{Block, Borders, List, ListItem, Clear},
     style::{Color, Style, Modifier},
 };
-use crate::app::{App, Field, NavItem, HttpMethod};
+use crate::app::{App, Field, NavItem, HttpMethod, ResponseTab};
 use super::components::{
     self, draw_collections, draw_history, draw_headers, 
     draw_request, draw_request_body, draw_response_headers, 
-    draw_response_body, draw_save_dialog, draw_response_status,
-    draw_method_selector,
+    draw_response_cookies, draw_response_redirects, draw_response_timing, draw_response_contract, draw_cookies, draw_response_body, draw_websocket_log, draw_event_stream, draw_grpc_stream, draw_save_dialog, draw_response_status,
+    draw_method_selector, draw_grpc_method_selector, draw_prompt, draw_schema, draw_recovery,
 };
 
 pub fn draw(f: &mut Frame, app: &mut App) {
@@ -27,10 +27,22 @@ pub fn draw(f: &mut Frame, app: &mut App) {
     // If collections or history is being shown, draw it over main content
     if app.show_collections && !app.save_dialog_visible {
         draw_collections(f, app, main_layout[1]);
+        draw_prompt(f, app, f.size());
         return;
     }
     if app.show_history {
         draw_history(f, app, main_layout[1]);
+        draw_prompt(f, app, f.size());
+        return;
+    }
+    if app.show_cookies {
+        draw_cookies(f, app, main_layout[1]);
+        draw_prompt(f, app, f.size());
+        return;
+    }
+    if app.show_schema {
+        draw_schema(f, app, main_layout[1]);
+        draw_prompt(f, app, f.size());
         return;
     }
 
@@ -122,13 +134,26 @@ pub fn draw(f: &mut Frame, app: &mut App) {
                     Span::raw(" "),
                     Span::styled("Response", Style::default()),
                     Span::raw(" "),
-                    Span::styled("─".repeat((content_layout[1].width as usize).saturating_sub(50)), Style::default()),
+                    // Shown from history, not received just now
+                    Span::styled(
+                        if metadata.from_history {
+                            format!("from history, sent {} ", metadata.timestamp.with_timezone(&chrono::Local).format("%a %-d %b %H:%M"))
+                        } else {
+                            String::new()
+                        },
+                        Style::default().fg(Color::Magenta)
+                    ),
+                    Span::styled("─".repeat((content_layout[1].width as usize).saturating_sub(if metadata.from_history { 85 } else { 60 })), Style::default()),
                     Span::raw(" "),
                     Span::raw("[ "),
                     Span::styled(
                         format!("{}  ", metadata.status_text),
                         status_style
                     ),
+                    Span::styled(
+                        format!("{}  ", metadata.http_version),
+                        Style::default().fg(Color::Cyan)
+                    ),
                     Span::raw(format!("{}ms  ", metadata.time_ms)),
                     Span::raw(format!("{}", size)),
                     Span::raw(" ]"),
@@ -161,18 +186,38 @@ pub fn draw(f: &mut Frame, app: &mut App) {
         ])
         .split(response_area);
 
-    draw_response_headers(f, app, response_content[0]);
-    draw_response_body(f, app, response_content[1]);
+    match app.response_tab {
+        ResponseTab::Headers => draw_response_headers(f, app, response_content[0]),
+        ResponseTab::Cookies => draw_response_cookies(f, app, response_content[0]),
+        ResponseTab::Redirects => draw_response_redirects(f, app, response_content[0]),
+        ResponseTab::Timing => draw_response_timing(f, app, response_content[0]),
+        ResponseTab::Contract => draw_response_contract(f, app, response_content[0]),
+    }
+    if app.is_websocket() {
+        draw_websocket_log(f, app, response_content[1]);
+    } else if app.event_stream.is_some() {
+        draw_event_stream(f, app, response_content[1]);
+    } else if app.grpc_stream.is_some() {
+        draw_grpc_stream(f, app, response_content[1]);
+    } else {
+        draw_response_body(f, app, response_content[1]);
+    }
 
     // Draw method selector
     if app.show_method_selector {
         draw_method_selector(f, app, f.size());
     }
+    if app.show_grpc_methods {
+        draw_grpc_method_selector(f, app, f.size());
+    }
 
     // Draw save dialog on top if visible
     if app.save_dialog_visible {
         draw_save_dialog(f, app, f.size());
     }
+
+    draw_prompt(f, app, f.size());
+    draw_recovery(f, app, f.size());
 }
 
 fn draw_nav_panel(f: &mut Frame, app: &mut App, area: Rect) {
@@ -199,15 +244,20 @@ fn draw_nav_panel(f: &mut Frame, app: &mut App, area: Rect) {
         })
         .collect();
 
+    let mut block = Block::default()
+        .title("Raquet (↑↓)")
+        .borders(Borders::ALL)
+        .border_style(if app.active_field == Field::NavPanel {
+            Style::default().fg(Color::Yellow)
+        } else {
+            Style::default()
+        });
+    // The project workspace in use, if any
+    if let Some(workspace) = &app.workspace {
+        block = block.title_bottom(Line::styled(format!(" ⌂ {} ", workspace.name()), Style::default().fg(Color::Cyan)));
+    }
     let nav_list = List::new(nav_items)
-        .block(Block::default()
-            .title("Raquet (↑↓)")
-            .borders(Borders::ALL)
-            .border_style(if app.active_field == Field::NavPanel {
-                Style::default().fg(Color::Yellow)
-            } else {
-                Style::default()
-            }))
+        .block(block)
         .highlight_style(Style::default().fg(Color::Yellow));
 
     f.render_widget(nav_list, area);
@@ -0,0 +1,628 @@
+use serde_json::Value;
+use unicode_width::UnicodeWidthChar;
+use super::http::mime_type;
+
+// Syntax classes shared by every formatter and the JSON tree view; the UI
+// maps them to colors.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum TokenKind {
+    Plain,
+    Punctuation,
+    Marker,
+    Key,
+    String,
+    Number,
+    Bool,
+    Null,
+    Summary,
+    Tag,
+    Attribute,
+    Comment,
+}
+
+pub type Token = (TokenKind, String);
+
+// Pretty-printing and highlighting for one family of content types.
+pub trait BodyFormatter: Sync {
+    fn name(&self) -> &'static str;
+
+    // `mime` is the bare, lowercased mime type without parameters.
+    fn matches(&self, mime: &str) -> bool;
+
+    fn format(&self, body: &str) -> String;
+
+    // Highlights one line of formatted output. The token texts must
+    // concatenate back to `line`.
+    fn highlight(&self, line: &str) -> Vec<Token>;
+}
+
+static FORMATTERS: &[&dyn BodyFormatter] = &[
+    &JsonFormatter,
+    &XmlFormatter,
+    &HtmlFormatter,
+    &YamlFormatter,
+    &FormFormatter,
+];
+
+pub fn formatter_for(content_type: &str) -> Option<&'static dyn BodyFormatter> {
+    let mime = mime_type(content_type);
+    FORMATTERS.iter().copied().find(|f| f.matches(&mime))
+}
+
+pub fn is_json(content_type: &str) -> bool {
+    JsonFormatter.matches(&mime_type(content_type))
+}
+
+// Byte offsets of the rows `line` is wrapped into at `width` columns, the
+// first always 0. Lines break between characters rather than words, so the
+// body view and search scrolling can both work out where a line starts.
+pub fn wrap_points(line: &str, width: usize) -> Vec<usize> {
+    let mut points = vec![0];
+    let mut columns = 0;
+    for (offset, c) in line.char_indices() {
+        let w = c.width().unwrap_or(0);
+        if columns + w > width.max(1) && columns > 0 {
+            points.push(offset);
+            columns = 0;
+        }
+        columns += w;
+    }
+    points
+}
+
+// ---- JSON ----------------------------------------------------------------
+
+pub struct JsonFormatter;
+
+impl BodyFormatter for JsonFormatter {
+    fn name(&self) -> &'static str {
+        "JSON"
+    }
+
+    // application/json, text/json and structured-syntax variants such as
+    // application/problem+json or application/vnd.api+json
+    fn matches(&self, mime: &str) -> bool {
+        matches!(mime, "application/json" | "text/json") || mime.ends_with("+json")
+    }
+
+    fn format(&self, body: &str) -> String {
+        serde_json::from_str::<Value>(body)
+            .ok()
+            .and_then(|json| serde_json::to_string_pretty(&json).ok())
+            .unwrap_or_else(|| body.to_string())
+    }
+
+    fn highlight(&self, line: &str) -> Vec<Token> {
+        let chars: Vec<char> = line.chars().collect();
+        let mut tokens = Vec::new();
+        let mut i = 0;
+        while i < chars.len() {
+            let c = chars[i];
+            let start = i;
+            let kind = if c == '"' {
+                i += 1;
+                while i < chars.len() && chars[i] != '"' {
+                    if chars[i] == '\\' {
+                        i += 1;
+                    }
+                    i += 1;
+                }
+                i = (i + 1).min(chars.len());
+                let rest: String = chars[i..].iter().collect();
+                if rest.trim_start().starts_with(':') { TokenKind::Key } else { TokenKind::String }
+            } else if c == '-' || c.is_ascii_digit() {
+                while i < chars.len() && (chars[i].is_ascii_digit() || "+-.eE".contains(chars[i])) {
+                    i += 1;
+                }
+                TokenKind::Number
+            } else if c.is_alphabetic() {
+                while i < chars.len() && chars[i].is_alphabetic() {
+                    i += 1;
+                }
+                match chars[start..i].iter().collect::<String>().as_str() {
+                    "true" | "false" => TokenKind::Bool,
+                    "null" => TokenKind::Null,
+                    _ => TokenKind::Plain,
+                }
+            } else if "{}[]:,".contains(c) {
+                i += 1;
+                TokenKind::Punctuation
+            } else {
+                i += 1;
+                TokenKind::Plain
+            };
+            push_token(&mut tokens, kind, chars[start..i].iter().collect());
+        }
+        tokens
+    }
+}
+
+// ---- XML / HTML ----------------------------------------------------------
+
+pub struct XmlFormatter;
+
+impl BodyFormatter for XmlFormatter {
+    fn name(&self) -> &'static str {
+        "XML"
+    }
+
+    // Covers SOAP (application/soap+xml) and other +xml types
+    fn matches(&self, mime: &str) -> bool {
+        matches!(mime, "application/xml" | "text/xml") || mime.ends_with("+xml")
+    }
+
+    fn format(&self, body: &str) -> String {
+        format_markup(body, false)
+    }
+
+    fn highlight(&self, line: &str) -> Vec<Token> {
+        highlight_markup(line)
+    }
+}
+
+pub struct HtmlFormatter;
+
+impl BodyFormatter for HtmlFormatter {
+    fn name(&self) -> &'static str {
+        "HTML"
+    }
+
+    fn matches(&self, mime: &str) -> bool {
+        mime == "text/html"
+    }
+
+    fn format(&self, body: &str) -> String {
+        format_markup(body, true)
+    }
+
+    fn highlight(&self, line: &str) -> Vec<Token> {
+        highlight_markup(line)
+    }
+}
+
+const HTML_VOID_ELEMENTS: &[&str] = &[
+    "area", "base", "br", "col", "embed", "hr", "img", "input", "link", "meta", "source", "track", "wbr",
+];
+
+// Elements whose content is kept verbatim
+const HTML_RAW_ELEMENTS: &[&str] = &["script", "style", "pre", "textarea"];
+
+#[derive(Debug, PartialEq)]
+enum MarkupNode<'a> {
+    Open(&'a str),
+    Close(&'a str),
+    // Self-closing tags, void elements, comments, declarations, CDATA
+    Leaf(&'a str),
+    Text(&'a str),
+}
+
+fn tag_name(tag: &str) -> String {
+    tag.trim_start_matches(['<', '/'])
+        .split(|c: char| c.is_whitespace() || c == '>' || c == '/')
+        .next()
+        .unwrap_or("")
+        .to_ascii_lowercase()
+}
+
+fn tokenize_markup(body: &str, html: bool) -> Vec<MarkupNode<'_>> {
+    let mut nodes = Vec::new();
+    let mut rest = body;
+    while !rest.is_empty() {
+        if !rest.starts_with('<') {
+            let end = rest.find('<').unwrap_or(rest.len());
+            nodes.push(MarkupNode::Text(&rest[..end]));
+            rest = &rest[end..];
+            continue;
+        }
+
+        let (terminator, is_leaf) = if rest.starts_with("<!--") {
+            ("-->", true)
+        } else if rest.starts_with("<![CDATA[") {
+            ("]]>", true)
+        } else if rest.starts_with("<?") {
+            ("?>", true)
+        } else {
+            (">", false)
+        };
+        let end = match rest.find(terminator) {
+            Some(pos) => pos + terminator.len(),
+            // Unterminated markup, keep the remainder as text
+            None => {
+                nodes.push(MarkupNode::Text(rest));
+                break;
+            }
+        };
+        let tag = &rest[..end];
+        rest = &rest[end..];
+
+        if is_leaf || tag.starts_with("<!") || tag.ends_with("/>") {
+            nodes.push(MarkupNode::Leaf(tag));
+        } else if tag.starts_with("</") {
+            nodes.push(MarkupNode::Close(tag));
+        } else {
+            let name = tag_name(tag);
+            if html && HTML_VOID_ELEMENTS.contains(&name.as_str()) {
+                nodes.push(MarkupNode::Leaf(tag));
+            } else if html && HTML_RAW_ELEMENTS.contains(&name.as_str()) {
+                let close = format!("</{}", name);
+                let content_end = rest.to_ascii_lowercase().find(&close).unwrap_or(rest.len());
+                nodes.push(MarkupNode::Open(tag));
+                if !rest[..content_end].trim().is_empty() {
+                    nodes.push(MarkupNode::Text(&rest[..content_end]));
+                }
+                rest = &rest[content_end..];
+            } else {
+                nodes.push(MarkupNode::Open(tag));
+            }
+        }
+    }
+    nodes
+}
+
+// Indents markup two spaces per level. Elements containing only text stay on
+// one line (`<name>value</name>`).
+fn format_markup(body: &str, html: bool) -> String {
+    let nodes = tokenize_markup(body, html);
+    let mut out: Vec<String> = Vec::new();
+    let mut depth: usize = 0;
+    let mut i = 0;
+
+    while i < nodes.len() {
+        let indent = "  ".repeat(depth);
+        match nodes[i] {
+            MarkupNode::Open(tag) => {
+                // <a>text</a> on a single line
+                if let (Some(MarkupNode::Text(text)), Some(MarkupNode::Close(close))) =
+                    (nodes.get(i + 1), nodes.get(i + 2))
+                {
+                    if text.trim().lines().count() <= 1 {
+                        out.push(format!("{}{}{}{}", indent, tag, text.trim(), close));
+                        i += 3;
+                        continue;
+                    }
+                }
+                if let Some(MarkupNode::Close(close)) = nodes.get(i + 1) {
+                    out.push(format!("{}{}{}", indent, tag, close));
+                    i += 2;
+                    continue;
+                }
+                out.push(format!("{}{}", indent, tag));
+                depth += 1;
+            }
+            MarkupNode::Close(tag) => {
+                depth = depth.saturating_sub(1);
+                out.push(format!("{}{}", "  ".repeat(depth), tag));
+            }
+            MarkupNode::Leaf(tag) => out.push(format!("{}{}", indent, tag)),
+            MarkupNode::Text(text) => {
+                for line in text.lines().map(str::trim).filter(|l| !l.is_empty()) {
+                    out.push(format!("{}{}", indent, line));
+                }
+            }
+        }
+        i += 1;
+    }
+    out.join("\n")
+}
+
+fn highlight_markup(line: &str) -> Vec<Token> {
+    let mut tokens = Vec::new();
+    let mut rest = line;
+    while !rest.is_empty() {
+        if rest.starts_with("<!--") {
+            let end = rest.find("-->").map(|p| p + 3).unwrap_or(rest.len());
+            push_token(&mut tokens, TokenKind::Comment, rest[..end].to_string());
+            rest = &rest[end..];
+        } else if is_tag_start(rest) {
+            let end = rest.find('>').map(|p| p + 1).unwrap_or(rest.len());
+            highlight_tag(&rest[..end], &mut tokens);
+            rest = &rest[end..];
+        } else {
+            // A `<` that doesn't open a tag (e.g. inside a script) is text
+            let skip = if rest.starts_with('<') { 1 } else { 0 };
+            let end = rest[skip..].find('<').map(|p| p + skip).unwrap_or(rest.len());
+            push_token(&mut tokens, TokenKind::Plain, rest[..end].to_string());
+            rest = &rest[end..];
+        }
+    }
+    tokens
+}
+
+fn is_tag_start(text: &str) -> bool {
+    let mut chars = text.chars();
+    chars.next() == Some('<')
+        && chars.next().is_some_and(|c| c.is_alphabetic() || matches!(c, '/' | '!' | '?'))
+}
+
+// `<ns:name attr="value" flag>`: punctuation, tag name, attribute names and quoted values
+fn highlight_tag(tag: &str, tokens: &mut Vec<Token>) {
+    let prefix_len = tag.find(|c: char| !matches!(c, '<' | '/' | '?' | '!')).unwrap_or(tag.len());
+    push_token(tokens, TokenKind::Punctuation, tag[..prefix_len].to_string());
+    let mut rest = &tag[prefix_len..];
+
+    let name_len = rest.find(|c: char| c.is_whitespace() || matches!(c, '>' | '/' | '?')).unwrap_or(rest.len());
+    push_token(tokens, TokenKind::Tag, rest[..name_len].to_string());
+    rest = &rest[name_len..];
+
+    while let Some(c) = rest.chars().next() {
+        let len = if c == '"' || c == '\'' {
+            let len = rest[1..].find(c).map(|p| p + 2).unwrap_or(rest.len());
+            push_token(tokens, TokenKind::String, rest[..len].to_string());
+            len
+        } else if c.is_whitespace() || matches!(c, '=' | '>' | '/' | '?') {
+            let len = c.len_utf8();
+            push_token(tokens, TokenKind::Punctuation, rest[..len].to_string());
+            len
+        } else {
+            let len = rest
+                .find(|c: char| c.is_whitespace() || matches!(c, '=' | '>' | '/'))
+                .unwrap_or(rest.len());
+            // Unquoted HTML attribute values follow an `=`
+            let kind = match tokens.last() {
+                Some((TokenKind::Punctuation, p)) if p == "=" => TokenKind::String,
+                _ => TokenKind::Attribute,
+            };
+            push_token(tokens, kind, rest[..len].to_string());
+            len
+        };
+        rest = &rest[len..];
+    }
+}
+
+// ---- YAML ----------------------------------------------------------------
+
+pub struct YamlFormatter;
+
+impl BodyFormatter for YamlFormatter {
+    fn name(&self) -> &'static str {
+        "YAML"
+    }
+
+    fn matches(&self, mime: &str) -> bool {
+        matches!(mime, "application/yaml" | "application/x-yaml" | "text/yaml" | "text/x-yaml")
+            || mime.ends_with("+yaml")
+    }
+
+    // YAML is indentation-sensitive and usually already readable; only tabs
+    // are expanded so the columns line up.
+    fn format(&self, body: &str) -> String {
+        body.replace('\t', "  ")
+    }
+
+    fn highlight(&self, line: &str) -> Vec<Token> {
+        let mut tokens = Vec::new();
+        let trimmed = line.trim_start();
+        push_token(&mut tokens, TokenKind::Plain, line[..line.len() - trimmed.len()].to_string());
+
+        if trimmed.starts_with('#') {
+            push_token(&mut tokens, TokenKind::Comment, trimmed.to_string());
+            return tokens;
+        }
+        if trimmed == "---" || trimmed == "..." {
+            push_token(&mut tokens, TokenKind::Punctuation, trimmed.to_string());
+            return tokens;
+        }
+
+        let mut rest = trimmed;
+        while let Some(item) = rest.strip_prefix("- ") {
+            push_token(&mut tokens, TokenKind::Punctuation, "- ".to_string());
+            rest = item;
+        }
+
+        // `key: value`; the colon must be followed by a space or end the line
+        let (content, comment) = split_yaml_comment(rest);
+        let key_end = content
+            .find(": ")
+            .or_else(|| content.strip_suffix(':').map(|k| k.len()))
+            .filter(|&end| !content[..end].starts_with(['"', '\'', '{', '[']) || content[..end].ends_with(['"', '\'']));
+        let value = match key_end {
+            Some(end) => {
+                push_token(&mut tokens, TokenKind::Key, content[..end].to_string());
+                push_token(&mut tokens, TokenKind::Punctuation, ":".to_string());
+                &content[end + 1..]
+            }
+            None => content,
+        };
+
+        let value_trimmed = value.trim_start();
+        push_token(&mut tokens, TokenKind::Plain, value[..value.len() - value_trimmed.len()].to_string());
+        push_token(&mut tokens, yaml_scalar_kind(value_trimmed.trim_end()), value_trimmed.to_string());
+        push_token(&mut tokens, TokenKind::Comment, comment.to_string());
+        tokens
+    }
+}
+
+// Splits off a trailing ` # comment` that isn't inside quotes.
+fn split_yaml_comment(text: &str) -> (&str, &str) {
+    let mut quote = None;
+    for (i, c) in text.char_indices() {
+        match (quote, c) {
+            (None, '"') | (None, '\'') => quote = Some(c),
+            (Some(q), c) if c == q => quote = None,
+            (None, '#') if i == 0 || text[..i].ends_with(' ') => return (&text[..i], &text[i..]),
+            _ => {}
+        }
+    }
+    (text, "")
+}
+
+fn yaml_scalar_kind(value: &str) -> TokenKind {
+    match value {
+        "" => TokenKind::Plain,
+        "true" | "false" | "yes" | "no" | "on" | "off" | "True" | "False" => TokenKind::Bool,
+        "null" | "~" | "Null" | "NULL" => TokenKind::Null,
+        v if v.parse::<f64>().is_ok() => TokenKind::Number,
+        v if v.starts_with(['"', '\'']) => TokenKind::String,
+        v if v.starts_with(['{', '[', '|', '>', '&', '*']) => TokenKind::Plain,
+        _ => TokenKind::String,
+    }
+}
+
+// ---- application/x-www-form-urlencoded ------------------------------------
+
+pub struct FormFormatter;
+
+impl BodyFormatter for FormFormatter {
+    fn name(&self) -> &'static str {
+        "Form"
+    }
+
+    fn matches(&self, mime: &str) -> bool {
+        mime == "application/x-www-form-urlencoded"
+    }
+
+    // One decoded `key = value` pair per line
+    fn format(&self, body: &str) -> String {
+        body.trim()
+            .split('&')
+            .filter(|pair| !pair.is_empty())
+            .map(|pair| {
+                let (key, value) = pair.split_once('=').unwrap_or((pair, ""));
+                format!("{} = {}", decode_form_component(key), decode_form_component(value))
+            })
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+
+    fn highlight(&self, line: &str) -> Vec<Token> {
+        let mut tokens = Vec::new();
+        match line.split_once(" = ") {
+            Some((key, value)) => {
+                push_token(&mut tokens, TokenKind::Key, key.to_string());
+                push_token(&mut tokens, TokenKind::Punctuation, " = ".to_string());
+                push_token(&mut tokens, TokenKind::String, value.to_string());
+            }
+            None => push_token(&mut tokens, TokenKind::Plain, line.to_string()),
+        }
+        tokens
+    }
+}
+
+pub fn decode_form_component(text: &str) -> String {
+    let bytes = text.as_bytes();
+    let mut out = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        match bytes[i] {
+            b'+' => out.push(b' '),
+            // from_str_radix alone would also take a sign, as in `%+1`
+            b'%' if i + 2 < bytes.len() && bytes[i + 1..i + 3].iter().all(u8::is_ascii_hexdigit) => {
+                let hex = std::str::from_utf8(&bytes[i + 1..i + 3]).unwrap_or("");
+                out.push(u8::from_str_radix(hex, 16).unwrap_or(b'%'));
+                i += 2;
+            }
+            b => out.push(b),
+        }
+        i += 1;
+    }
+    String::from_utf8_lossy(&out).into_owned()
+}
+
+// Appends a token, merging runs of plain text.
+fn push_token(tokens: &mut Vec<Token>, kind: TokenKind, text: String) {
+    if text.is_empty() {
+        return;
+    }
+    match tokens.last_mut() {
+        Some((TokenKind::Plain, last)) if kind == TokenKind::Plain => last.push_str(&text),
+        _ => tokens.push((kind, text)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn name_for(content_type: &str) -> Option<&'static str> {
+        formatter_for(content_type).map(|f| f.name())
+    }
+
+    // The tokens of every formatted line must add up to the line
+    fn assert_highlight_covers(formatter: &dyn BodyFormatter, body: &str) {
+        for line in formatter.format(body).lines() {
+            let joined: String = formatter.highlight(line).into_iter().map(|(_, text)| text).collect();
+            assert_eq!(joined, line, "{} highlighting", formatter.name());
+        }
+    }
+
+    #[test]
+    fn picks_a_formatter_by_content_type() {
+        assert_eq!(name_for("application/json"), Some("JSON"));
+        assert_eq!(name_for("Application/JSON; charset=utf-8"), Some("JSON"));
+        assert_eq!(name_for("application/problem+json"), Some("JSON"));
+        assert_eq!(name_for("text/xml"), Some("XML"));
+        assert_eq!(name_for("application/atom+xml"), Some("XML"));
+        assert_eq!(name_for("text/html; charset=utf-8"), Some("HTML"));
+        assert_eq!(name_for("application/x-yaml"), Some("YAML"));
+        assert_eq!(name_for("application/vnd.k8s+yaml"), Some("YAML"));
+        assert_eq!(name_for("application/x-www-form-urlencoded"), Some("Form"));
+        assert_eq!(name_for("text/plain"), None);
+        assert_eq!(name_for(""), None);
+        assert!(is_json("application/vnd.api+json"));
+        assert!(!is_json("text/html"));
+    }
+
+    #[test]
+    fn formats_json() {
+        assert_eq!(JsonFormatter.format(r#"{"a":[1,true]}"#), "{\n  \"a\": [\n    1,\n    true\n  ]\n}");
+        assert_eq!(JsonFormatter.format("not json"), "not json");
+        assert_eq!(JsonFormatter.highlight(r#"  "id": -1.5e3,"#), vec![
+            (TokenKind::Plain, "  ".to_string()),
+            (TokenKind::Key, "\"id\"".to_string()),
+            (TokenKind::Punctuation, ":".to_string()),
+            (TokenKind::Plain, " ".to_string()),
+            (TokenKind::Number, "-1.5e3".to_string()),
+            (TokenKind::Punctuation, ",".to_string()),
+        ]);
+        assert_highlight_covers(&JsonFormatter, r#"{"s":"a \"quoted\" ü","n":null,"b":false,"x":[{}]}"#);
+    }
+
+    #[test]
+    fn formats_markup() {
+        assert_eq!(XmlFormatter.format("<a><b>text</b><c/></a>"), "<a>\n  <b>text</b>\n  <c/>\n</a>");
+        assert_highlight_covers(&XmlFormatter, r#"<?xml version="1.0"?><a x="1"><!-- note --><b>t</b></a>"#);
+        // Void elements don't open a level; script content isn't parsed as tags
+        let html = HtmlFormatter.format("<div><br><script>if (a<b) { x = '<p>'; }</script></div>");
+        assert_eq!(html, "<div>\n  <br>\n  <script>if (a<b) { x = '<p>'; }</script>\n</div>");
+        assert_highlight_covers(&HtmlFormatter, "<html><body class=\"x\"><p>Hi<br>there</p></body></html>");
+    }
+
+    #[test]
+    fn formats_yaml() {
+        let body = "name: raquet # the client\nitems:\n  - 1\n  - ~\nok: true\n";
+        assert_eq!(YamlFormatter.format(body).trim_end(), body.trim_end());
+        assert_eq!(yaml_scalar_kind("3.5"), TokenKind::Number);
+        assert_eq!(yaml_scalar_kind("~"), TokenKind::Null);
+        assert_eq!(yaml_scalar_kind("'x'"), TokenKind::String);
+        assert_eq!(split_yaml_comment("a: '#x' # c"), ("a: '#x' ", "# c"));
+        assert_highlight_covers(&YamlFormatter, body);
+    }
+
+    #[test]
+    fn formats_forms() {
+        assert_eq!(FormFormatter.format("a=1&b=hello+world&c=%C3%BC&&d"), "a = 1\nb = hello world\nc = ü\nd = ");
+        assert_highlight_covers(&FormFormatter, "q=x+y&empty=");
+    }
+
+    #[test]
+    fn decodes_form_components() {
+        assert_eq!(decode_form_component("a%20b+c"), "a b c");
+        assert_eq!(decode_form_component("%41%4a%4A"), "AJJ");
+        assert_eq!(decode_form_component("100%"), "100%");
+        assert_eq!(decode_form_component("%4"), "%4");
+        assert_eq!(decode_form_component("%zz"), "%zz");
+        assert_eq!(decode_form_component("%+1"), "% 1");
+        assert_eq!(decode_form_component("%-1"), "%-1");
+        assert_eq!(decode_form_component("%ff"), "\u{fffd}");
+    }
+
+    #[test]
+    fn wraps_by_display_width() {
+        assert_eq!(wrap_points("", 4), vec![0]);
+        assert_eq!(wrap_points("abcd", 4), vec![0]);
+        assert_eq!(wrap_points("abcdefghij", 4), vec![0, 4, 8]);
+        // Wide characters take two columns and are never split
+        assert_eq!(wrap_points("a日本語", 4), vec![0, 4]);
+        assert_eq!(wrap_points("abc", 0), vec![0, 1, 2]);
+    }
+}
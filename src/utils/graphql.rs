@@ -0,0 +1,427 @@
+use std::collections::BTreeMap;
+use anyhow::{anyhow, Context, Result};
+use serde_json::{json, Value};
+
+pub const INTROSPECTION_QUERY: &str = "query IntrospectionQuery {
+  __schema {
+    queryType { name }
+    mutationType { name }
+    subscriptionType { name }
+    types {
+      kind name description
+      fields(includeDeprecated: true) {
+        name description isDeprecated
+        args { name description defaultValue type { ...TypeRef } }
+        type { ...TypeRef }
+      }
+      inputFields { name description defaultValue type { ...TypeRef } }
+      enumValues(includeDeprecated: true) { name description isDeprecated }
+    }
+  }
+}
+fragment TypeRef on __Type {
+  kind name
+  ofType { kind name ofType { kind name ofType { kind name ofType { kind name } } } }
+}";
+
+// The `{"query", "variables", "operationName"}` payload. Variables must be a
+// JSON object when given; empty variables and operation name are left out.
+pub fn payload(query: &str, variables: &str, operation_name: &str) -> Result<String> {
+    let mut body = json!({ "query": query });
+    if !variables.trim().is_empty() {
+        let variables: Value = serde_json::from_str(variables).context("variables are not valid JSON")?;
+        if !variables.is_object() {
+            return Err(anyhow!("variables must be a JSON object"));
+        }
+        body["variables"] = variables;
+    }
+    if !operation_name.trim().is_empty() {
+        body["operationName"] = json!(operation_name.trim());
+    }
+    Ok(body.to_string())
+}
+
+#[derive(Debug, Clone)]
+pub struct SchemaField {
+    pub name: String,
+    pub description: Option<String>,
+    // Type as written in SDL, e.g. `[User!]!`
+    pub type_display: String,
+    // Named type with list and non-null wrappers removed
+    pub type_name: String,
+    pub args: Vec<SchemaArg>,
+    pub deprecated: bool,
+}
+
+#[derive(Debug, Clone)]
+pub struct SchemaArg {
+    pub name: String,
+    pub type_display: String,
+    pub default_value: Option<String>,
+}
+
+#[derive(Debug, Clone)]
+pub struct SchemaType {
+    pub name: String,
+    // OBJECT, INTERFACE, UNION, ENUM, INPUT_OBJECT or SCALAR
+    pub kind: String,
+    pub description: Option<String>,
+    // Object and interface fields, input object fields, or enum values
+    pub fields: Vec<SchemaField>,
+}
+
+// Schema fetched with the introspection query, reduced to what the
+// explorer and field completion need.
+#[derive(Debug, Clone)]
+pub struct GraphQlSchema {
+    pub query_type: Option<String>,
+    pub mutation_type: Option<String>,
+    pub subscription_type: Option<String>,
+    pub types: BTreeMap<String, SchemaType>,
+}
+
+impl GraphQlSchema {
+    // Parses an introspection response, with or without the `data` wrapper.
+    pub fn from_introspection(response: &Value) -> Result<Self> {
+        let schema = response.get("data").unwrap_or(response)
+            .get("__schema")
+            .ok_or_else(|| match response.get("errors") {
+                Some(errors) => anyhow!("introspection failed: {}", first_error(errors)),
+                None => anyhow!("response has no __schema"),
+            })?;
+        let root = |key: &str| schema.get(key).and_then(|t| t.get("name")).and_then(Value::as_str).map(str::to_string);
+
+        let mut types = BTreeMap::new();
+        for t in schema.get("types").and_then(Value::as_array).into_iter().flatten() {
+            let Some(name) = t.get("name").and_then(Value::as_str) else { continue };
+            let fields = ["fields", "inputFields"]
+                .iter()
+                .filter_map(|key| t.get(*key).and_then(Value::as_array))
+                .flatten()
+                .map(parse_field)
+                .chain(t.get("enumValues").and_then(Value::as_array).into_iter().flatten().map(|v| SchemaField {
+                    name: str_field(v, "name"),
+                    description: v.get("description").and_then(Value::as_str).map(str::to_string),
+                    type_display: String::new(),
+                    type_name: String::new(),
+                    args: Vec::new(),
+                    deprecated: v.get("isDeprecated").and_then(Value::as_bool).unwrap_or(false),
+                }))
+                .collect();
+            types.insert(name.to_string(), SchemaType {
+                name: name.to_string(),
+                kind: str_field(t, "kind"),
+                description: t.get("description").and_then(Value::as_str).map(str::to_string),
+                fields,
+            });
+        }
+
+        Ok(GraphQlSchema {
+            query_type: root("queryType"),
+            mutation_type: root("mutationType"),
+            subscription_type: root("subscriptionType"),
+            types,
+        })
+    }
+
+    // Types a user would browse: roots first, then the rest by name, without
+    // the introspection types.
+    pub fn browsable_types(&self) -> Vec<&SchemaType> {
+        let roots: Vec<&String> = [&self.query_type, &self.mutation_type, &self.subscription_type]
+            .into_iter()
+            .flatten()
+            .collect();
+        let mut types: Vec<&SchemaType> = roots.iter().filter_map(|name| self.types.get(*name)).collect();
+        types.extend(self.types.values().filter(|t| !t.name.starts_with("__") && !roots.contains(&&t.name)));
+        types
+    }
+
+    // Field names of the selection set around `cursor` that start with the
+    // identifier being typed there.
+    pub fn completions(&self, query: &str, cursor: usize) -> Vec<String> {
+        let before = &query[..cursor.min(query.len())];
+        let start = before
+            .rfind(|c: char| !(c.is_alphanumeric() || c == '_'))
+            .map(|i| i + before[i..].chars().next().map_or(1, char::len_utf8))
+            .unwrap_or(0);
+        let prefix = &before[start..];
+        let Some(type_name) = self.selection_type(&before[..start]) else { return Vec::new() };
+        let Some(schema_type) = self.types.get(&type_name) else { return Vec::new() };
+        let mut names: Vec<String> = schema_type.fields.iter()
+            .map(|f| f.name.clone())
+            .filter(|name| name.starts_with(prefix) && name != prefix)
+            .collect();
+        if matches!(schema_type.kind.as_str(), "OBJECT" | "INTERFACE" | "UNION") && "__typename".starts_with(prefix) {
+            names.push("__typename".to_string());
+        }
+        names
+    }
+
+    // Type whose fields can be selected at the end of `text`, found by
+    // following the open braces from the operation's root type.
+    fn selection_type(&self, text: &str) -> Option<String> {
+        let mut stack: Vec<Option<String>> = Vec::new();
+        let mut words: Vec<String> = Vec::new();
+        let mut chars = text.chars().peekable();
+        while let Some(c) = chars.next() {
+            match c {
+                '#' => {
+                    while chars.next_if(|c| *c != '\n').is_some() {}
+                }
+                '"' => {
+                    while let Some(c) = chars.next() {
+                        match c {
+                            '\\' => { chars.next(); }
+                            '"' => break,
+                            _ => {}
+                        }
+                    }
+                }
+                '(' => {
+                    let mut depth = 1;
+                    for c in chars.by_ref() {
+                        match c {
+                            '(' => depth += 1,
+                            ')' => { depth -= 1; if depth == 0 { break; } }
+                            _ => {}
+                        }
+                    }
+                }
+                '{' => {
+                    let next = if words.len() >= 2 && words[words.len() - 2] == "on" {
+                        // `... on Type {` and `fragment Name on Type {`
+                        words.last().cloned()
+                    } else if stack.is_empty() {
+                        // `{`, `query Name {`, `mutation {`, ...
+                        match words.first().map(String::as_str) {
+                            Some("mutation") => self.mutation_type.clone(),
+                            Some("subscription") => self.subscription_type.clone(),
+                            _ => self.query_type.clone(),
+                        }
+                    } else {
+                        match (stack.last().cloned().flatten(), words.last()) {
+                            (Some(parent), Some(field)) => self.field_type(&parent, field),
+                            _ => None,
+                        }
+                    };
+                    stack.push(next);
+                    words.clear();
+                }
+                // Directive names aren't fields
+                '@' => {
+                    while chars.next_if(|c| c.is_alphanumeric() || *c == '_').is_some() {}
+                }
+                '}' => {
+                    stack.pop();
+                    words.clear();
+                }
+                c if c.is_alphanumeric() || c == '_' => {
+                    let mut word = c.to_string();
+                    while let Some(c) = chars.next_if(|c| c.is_alphanumeric() || *c == '_') {
+                        word.push(c);
+                    }
+                    words.push(word);
+                }
+                _ => {}
+            }
+        }
+        stack.pop().flatten()
+    }
+
+    fn field_type(&self, type_name: &str, field: &str) -> Option<String> {
+        self.types.get(type_name)?
+            .fields.iter()
+            .find(|f| f.name == field)
+            .map(|f| f.type_name.clone())
+    }
+}
+
+fn parse_field(value: &Value) -> SchemaField {
+    let type_ref = value.get("type").unwrap_or(&Value::Null);
+    SchemaField {
+        name: str_field(value, "name"),
+        description: value.get("description").and_then(Value::as_str).map(str::to_string),
+        type_display: type_display(type_ref),
+        type_name: named_type(type_ref),
+        args: value.get("args").and_then(Value::as_array).into_iter().flatten()
+            .map(|arg| SchemaArg {
+                name: str_field(arg, "name"),
+                type_display: type_display(arg.get("type").unwrap_or(&Value::Null)),
+                default_value: arg.get("defaultValue").and_then(Value::as_str).map(str::to_string),
+            })
+            .collect(),
+        deprecated: value.get("isDeprecated").and_then(Value::as_bool).unwrap_or(false),
+    }
+}
+
+fn type_display(type_ref: &Value) -> String {
+    let inner = type_ref.get("ofType").unwrap_or(&Value::Null);
+    match type_ref.get("kind").and_then(Value::as_str) {
+        Some("NON_NULL") => format!("{}!", type_display(inner)),
+        Some("LIST") => format!("[{}]", type_display(inner)),
+        _ => str_field(type_ref, "name"),
+    }
+}
+
+fn named_type(type_ref: &Value) -> String {
+    match type_ref.get("kind").and_then(Value::as_str) {
+        Some("NON_NULL") | Some("LIST") => named_type(type_ref.get("ofType").unwrap_or(&Value::Null)),
+        _ => str_field(type_ref, "name"),
+    }
+}
+
+fn str_field(value: &Value, key: &str) -> String {
+    value.get(key).and_then(Value::as_str).unwrap_or_default().to_string()
+}
+
+fn first_error(errors: &Value) -> String {
+    errors.get(0)
+        .and_then(|e| e.get("message"))
+        .and_then(Value::as_str)
+        .unwrap_or("unknown error")
+        .to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn named(kind: &str, name: &str) -> Value {
+        json!({ "kind": kind, "name": name, "ofType": null })
+    }
+
+    fn non_null(inner: Value) -> Value {
+        json!({ "kind": "NON_NULL", "name": null, "ofType": inner })
+    }
+
+    fn list(inner: Value) -> Value {
+        json!({ "kind": "LIST", "name": null, "ofType": inner })
+    }
+
+    fn field(name: &str, type_ref: Value) -> Value {
+        json!({ "name": name, "description": null, "isDeprecated": false, "args": [], "type": type_ref })
+    }
+
+    fn object(name: &str, fields: Vec<Value>) -> Value {
+        json!({ "kind": "OBJECT", "name": name, "description": null, "fields": fields })
+    }
+
+    // Introspection result of a small user directory
+    fn introspection() -> Value {
+        let user = || named("OBJECT", "User");
+        let mut user_field = field("user", user());
+        user_field["args"] = json!([
+            { "name": "id", "description": null, "defaultValue": null, "type": non_null(named("SCALAR", "ID")) },
+            { "name": "active", "description": null, "defaultValue": "true", "type": named("SCALAR", "Boolean") },
+        ]);
+        let mut legacy = field("legacyName", named("SCALAR", "String"));
+        legacy["isDeprecated"] = json!(true);
+        json!({ "data": { "__schema": {
+            "queryType": { "name": "Query" },
+            "mutationType": { "name": "Mutation" },
+            "subscriptionType": null,
+            "types": [
+                object("Query", vec![user_field, field("users", non_null(list(non_null(user()))))]),
+                object("Mutation", vec![field("createUser", user())]),
+                object("User", vec![
+                    field("id", non_null(named("SCALAR", "ID"))),
+                    field("name", named("SCALAR", "String")),
+                    field("nickname", named("SCALAR", "String")),
+                    field("friends", non_null(list(non_null(user())))),
+                    field("role", named("ENUM", "Role")),
+                    legacy,
+                ]),
+                { "kind": "ENUM", "name": "Role", "description": "Access level", "enumValues": [
+                    { "name": "ADMIN", "description": null, "isDeprecated": false },
+                    { "name": "GUEST", "description": null, "isDeprecated": true },
+                ] },
+                { "kind": "INPUT_OBJECT", "name": "UserInput", "description": null, "inputFields": [
+                    { "name": "name", "description": null, "defaultValue": null, "type": non_null(named("SCALAR", "String")) },
+                ] },
+                named("SCALAR", "ID"),
+                named("SCALAR", "String"),
+                object("__Schema", vec![]),
+            ],
+        } } })
+    }
+
+    fn schema() -> GraphQlSchema {
+        GraphQlSchema::from_introspection(&introspection()).unwrap()
+    }
+
+    // Completions with the cursor at the `|` in `query`
+    fn complete(query: &str) -> Vec<String> {
+        let cursor = query.find('|').unwrap();
+        schema().completions(&query.replace('|', ""), cursor)
+    }
+
+    #[test]
+    fn parses_introspection() {
+        let schema = schema();
+        assert_eq!(schema.query_type.as_deref(), Some("Query"));
+        assert_eq!(schema.mutation_type.as_deref(), Some("Mutation"));
+        assert_eq!(schema.subscription_type, None);
+
+        let users = &schema.types["Query"].fields[1];
+        assert_eq!((users.type_display.as_str(), users.type_name.as_str()), ("[User!]!", "User"));
+        let user = &schema.types["Query"].fields[0];
+        let args: Vec<_> = user.args.iter()
+            .map(|a| (a.name.as_str(), a.type_display.as_str(), a.default_value.as_deref()))
+            .collect();
+        assert_eq!(args, vec![("id", "ID!", None), ("active", "Boolean", Some("true"))]);
+        assert!(schema.types["User"].fields.iter().any(|f| f.name == "legacyName" && f.deprecated));
+
+        let role = &schema.types["Role"];
+        assert_eq!(role.description.as_deref(), Some("Access level"));
+        let values: Vec<_> = role.fields.iter().map(|f| (f.name.as_str(), f.deprecated)).collect();
+        assert_eq!(values, vec![("ADMIN", false), ("GUEST", true)]);
+        assert_eq!(schema.types["UserInput"].fields[0].type_display, "String!");
+
+        // Roots first, introspection types left out
+        let browsable: Vec<_> = schema.browsable_types().iter().map(|t| t.name.as_str()).collect();
+        assert_eq!(browsable, vec!["Query", "Mutation", "ID", "Role", "String", "User", "UserInput"]);
+    }
+
+    #[test]
+    fn introspection_errors() {
+        let schema = GraphQlSchema::from_introspection(&introspection()["data"]).unwrap();
+        assert_eq!(schema.types.len(), 8);
+        let error = GraphQlSchema::from_introspection(&json!({ "errors": [{ "message": "introspection is disabled" }] }))
+            .unwrap_err();
+        assert_eq!(error.to_string(), "introspection failed: introspection is disabled");
+        assert!(GraphQlSchema::from_introspection(&json!({ "data": {} })).is_err());
+    }
+
+    #[test]
+    fn completes_fields_of_the_root_type() {
+        assert_eq!(complete("{ us|"), vec!["user", "users"]);
+        assert_eq!(complete("query Q { |"), vec!["user", "users", "__typename"]);
+        assert_eq!(complete("mutation { cr|"), vec!["createUser"]);
+    }
+
+    #[test]
+    fn completes_fields_of_nested_selections() {
+        assert_eq!(complete("{ user(id: \"1\") { n|"), vec!["name", "nickname"]);
+        assert_eq!(complete("{ users { friends { ro|"), vec!["role"]);
+        // Back in the outer selection after a closed one
+        assert_eq!(complete("{ users { friends { id } na|"), vec!["name"]);
+        assert_eq!(complete("{ user { ...on User { fr|"), vec!["friends"]);
+        assert_eq!(complete("fragment F on User { le|"), vec!["legacyName"]);
+    }
+
+    #[test]
+    fn ignores_strings_comments_and_arguments() {
+        assert_eq!(complete("{ user(id: \"{ x\") { # { users {\n na|"), vec!["name"]);
+        assert_eq!(complete("{ user(filter: { nested: 1 }) @include(if: true) { ro|"), vec!["role"]);
+        // The cursor in the middle of the query only looks at what's before it
+        assert_eq!(complete("{ users { i| } }"), vec!["id"]);
+    }
+
+    #[test]
+    fn no_completions_outside_known_selections() {
+        assert!(complete("us|").is_empty());
+        assert!(complete("{ name { |").is_empty());
+        // A complete name isn't offered again
+        assert_eq!(complete("{ users { name|"), Vec::<String>::new());
+    }
+}
@@ -0,0 +1,384 @@
+use std::collections::HashMap;
+use std::path::Path;
+use anyhow::{anyhow, Context, Result};
+use chrono::{DateTime, Utc};
+use serde_json::{json, Value};
+use crate::data::history::{HistoryEntry, RequestData, ResponseData};
+use crate::models::collection::{
+    next_request_id, Collection, CollectionInfo, CollectionItem, Folder, Request, RequestOptions, SavedRequest,
+};
+use crate::models::Timings;
+
+// HAR 1.2 (http://www.softwareishard.com/blog/har-12-spec/) log of history
+// entries. Unknown sizes and timings are -1, as the spec asks.
+pub fn to_har(entries: &[HistoryEntry]) -> Value {
+    json!({
+        "log": {
+            "version": "1.2",
+            "creator": { "name": "raquet", "version": env!("CARGO_PKG_VERSION") },
+            "pages": [],
+            "entries": entries.iter().map(har_entry).collect::<Vec<_>>(),
+        }
+    })
+}
+
+pub fn export_file(path: &Path, entries: &[HistoryEntry]) -> Result<()> {
+    let json = serde_json::to_string_pretty(&to_har(entries))?;
+    std::fs::write(path, json).with_context(|| format!("could not write {}", path.display()))
+}
+
+pub fn import_file(path: &Path) -> Result<Vec<HistoryEntry>> {
+    let text = std::fs::read_to_string(path)
+        .with_context(|| format!("could not read {}", path.display()))?;
+    parse(&text)
+}
+
+pub fn is_har(value: &Value) -> bool {
+    value.get("log").and_then(|log| log.get("entries")).is_some_and(Value::is_array)
+}
+
+pub fn parse(text: &str) -> Result<Vec<HistoryEntry>> {
+    let har: Value = serde_json::from_str(text).context("not a JSON document")?;
+    from_har(&har)
+}
+
+pub fn from_har(har: &Value) -> Result<Vec<HistoryEntry>> {
+    if !is_har(har) {
+        return Err(anyhow!("not a HAR file: no log.entries"));
+    }
+    Ok(har["log"]["entries"].as_array().into_iter().flatten().filter_map(history_entry).collect())
+}
+
+// One request per entry, in a folder per host
+pub fn to_collection(name: &str, entries: &[HistoryEntry]) -> Collection {
+    let mut folders: Vec<Folder> = Vec::new();
+    for entry in entries {
+        let url = reqwest::Url::parse(&entry.request.url).ok();
+        let host = url.as_ref().and_then(|u| u.host_str()).unwrap_or("other").to_string();
+        let path = url.as_ref().map(|u| u.path().to_string()).unwrap_or_else(|| entry.request.url.clone());
+        let item = CollectionItem::Request(Box::new(SavedRequest {
+            id: next_request_id(),
+            name: path,
+            request: Request {
+                method: entry.request.method.clone(),
+                url: entry.request.url.clone(),
+                headers: entry.request.headers.clone(),
+                body: entry.request.body.clone(),
+                options: RequestOptions::default(),
+                graphql: None,
+                grpc: None,
+            },
+            response_filter: None,
+            openapi: None,
+        }));
+        match folders.iter_mut().find(|folder| folder.name == host) {
+            Some(folder) => folder.item.push(item),
+            None => folders.push(Folder { name: host, item: vec![item] }),
+        }
+    }
+    Collection {
+        info: CollectionInfo {
+            name: name.to_string(),
+            description: format!("Imported from HAR, {} requests", entries.len()),
+            created_at: Utc::now(),
+        },
+        requests: folders.into_iter().map(CollectionItem::Folder).collect(),
+        variables: Default::default(),
+        source: None,
+    }
+}
+
+fn har_entry(entry: &HistoryEntry) -> Value {
+    let request = &entry.request;
+    let query: Vec<Value> = reqwest::Url::parse(&request.url)
+        .map(|url| url.query_pairs().map(|(name, value)| json!({ "name": name, "value": value })).collect())
+        .unwrap_or_default();
+    // The request went over the connection the response came back on
+    let http_version = entry.response.as_ref().and_then(|r| r.http_version.clone()).unwrap_or_default();
+    let mut har_request = json!({
+        "method": request.method,
+        "url": request.url,
+        "httpVersion": http_version,
+        "cookies": [],
+        "headers": har_headers(request.headers.iter()),
+        "queryString": query,
+        "headersSize": -1,
+        "bodySize": request.body.as_ref().map_or(0, |body| body.len() as i64),
+    });
+    if let Some(body) = &request.body {
+        har_request["postData"] = json!({
+            "mimeType": header(&request.headers, "content-type").unwrap_or_default(),
+            "text": body,
+        });
+    }
+
+    // A request that got no response has status 0, like browsers write it
+    let (har_response, timings, time) = match &entry.response {
+        Some(response) => {
+            let content_type = response_header(response, "content-type").unwrap_or_default();
+            let mut har_response = json!({
+                "status": response.status.unwrap_or(0),
+                "statusText": response.status_text.as_deref()
+                    .map(|text| text.split_once(' ').map_or(text, |(_, reason)| reason))
+                    .unwrap_or_default(),
+                "httpVersion": http_version,
+                "cookies": [],
+                "headers": response.headers.iter()
+                    .map(|(name, value)| json!({ "name": name, "value": value }))
+                    .collect::<Vec<_>>(),
+                "content": {
+                    "size": response.size_bytes,
+                    "mimeType": content_type,
+                    "text": response.body,
+                },
+                "redirectURL": response_header(response, "location").unwrap_or_default(),
+                "headersSize": -1,
+                "bodySize": response.size_bytes,
+            });
+            if let Some(encoding) = &response.encoding {
+                har_response["content"]["encoding"] = json!(encoding);
+            }
+            let (timings, time) = match &response.timings {
+                Some(t) => {
+                    // HAR's connect includes the TLS handshake
+                    let connect = t.connect_ms.map(|ms| ms + t.tls_ms.unwrap_or(0.0));
+                    (json!({
+                        "blocked": -1,
+                        "dns": t.dns_ms.unwrap_or(-1.0),
+                        "connect": connect.unwrap_or(-1.0),
+                        "ssl": t.tls_ms.unwrap_or(-1.0),
+                        "send": 0,
+                        "wait": t.ttfb_ms,
+                        "receive": t.download_ms,
+                    }), t.total_ms())
+                }
+                None => (json!({ "send": 0, "wait": response.time_ms as f64, "receive": 0 }), response.time_ms as f64),
+            };
+            (har_response, timings, time)
+        }
+        None => (
+            json!({
+                "status": 0, "statusText": "", "httpVersion": "", "cookies": [], "headers": [],
+                "content": { "size": 0, "mimeType": "" }, "redirectURL": "", "headersSize": -1, "bodySize": -1,
+            }),
+            json!({ "send": 0, "wait": 0, "receive": 0 }),
+            0.0,
+        ),
+    };
+
+    json!({
+        "startedDateTime": entry.timestamp.to_rfc3339(),
+        "time": time,
+        "request": har_request,
+        "response": har_response,
+        "cache": {},
+        "timings": timings,
+    })
+}
+
+fn history_entry(entry: &Value) -> Option<HistoryEntry> {
+    let request = entry.get("request")?;
+    let timestamp = entry.get("startedDateTime")
+        .and_then(Value::as_str)
+        .and_then(|t| DateTime::parse_from_rfc3339(t).ok())
+        .map(|t| t.with_timezone(&Utc))
+        .unwrap_or_else(Utc::now);
+    let body = request.get("postData")
+        .and_then(|data| data.get("text"))
+        .and_then(Value::as_str)
+        .map(str::to_string);
+
+    let response = entry.get("response")
+        .filter(|response| response.get("status").and_then(Value::as_u64).unwrap_or(0) > 0)
+        .map(|response| {
+            let status = response.get("status").and_then(Value::as_u64).unwrap_or(0) as u16;
+            // Browsers leave the reason phrase empty for HTTP/2
+            let status_text = response.get("statusText").and_then(Value::as_str)
+                .filter(|text| !text.is_empty())
+                .or_else(|| reqwest::StatusCode::from_u16(status).ok().and_then(|s| s.canonical_reason()))
+                .unwrap_or_default();
+            let content = response.get("content").unwrap_or(&Value::Null);
+            // Binary bodies are base64 encoded
+            let encoding = content.get("encoding").and_then(Value::as_str)
+                .filter(|encoding| *encoding == "base64")
+                .map(str::to_string);
+            let body = content.get("text").and_then(Value::as_str).unwrap_or_default().to_string();
+            let size = content.get("size").and_then(Value::as_i64).filter(|size| *size >= 0)
+                .map_or(body.len(), |size| size as usize);
+            ResponseData {
+                status: Some(status),
+                status_text: Some(format!("{} {}", status, status_text).trim_end().to_string()),
+                headers: header_list(response.get("headers")),
+                body,
+                encoding,
+                time_ms: entry.get("time").and_then(Value::as_f64).unwrap_or(0.0).max(0.0) as u128,
+                size_bytes: size,
+                http_version: response.get("httpVersion").and_then(Value::as_str)
+                    .filter(|version| !version.is_empty())
+                    .map(str::to_string),
+                timings: entry.get("timings").map(timings),
+            }
+        });
+
+    Some(HistoryEntry {
+        id: next_request_id(),
+        timestamp,
+        request: RequestData {
+            url: request.get("url")?.as_str()?.to_string(),
+            method: request.get("method").and_then(Value::as_str).unwrap_or("GET").to_uppercase(),
+            headers: header_list(request.get("headers")).into_iter().collect(),
+            disabled_headers: HashMap::new(),
+            body,
+        },
+        response,
+    })
+}
+
+fn timings(har: &Value) -> Timings {
+    // -1 means the phase didn't happen or isn't known
+    let phase = |name: &str| har.get(name).and_then(Value::as_f64).filter(|ms| *ms >= 0.0);
+    let tls = phase("ssl");
+    Timings {
+        dns_ms: phase("dns"),
+        connect_ms: phase("connect").map(|ms| (ms - tls.unwrap_or(0.0)).max(0.0)),
+        tls_ms: tls,
+        ttfb_ms: phase("send").unwrap_or(0.0) + phase("wait").unwrap_or(0.0),
+        download_ms: phase("receive").unwrap_or(0.0),
+    }
+}
+
+fn har_headers<'a>(headers: impl Iterator<Item = (&'a String, &'a String)>) -> Vec<Value> {
+    let mut headers: Vec<(&String, &String)> = headers.collect();
+    headers.sort();
+    headers.into_iter().map(|(name, value)| json!({ "name": name, "value": value })).collect()
+}
+
+// Every header in order, repeated ones included. HTTP/2 pseudo-headers like
+// `:authority` aren't sent as headers.
+fn header_list(headers: Option<&Value>) -> Vec<(String, String)> {
+    headers.and_then(Value::as_array)
+        .into_iter()
+        .flatten()
+        .filter_map(|header| Some((header.get("name")?.as_str()?, header.get("value")?.as_str()?)))
+        .filter(|(name, _)| !name.starts_with(':'))
+        .map(|(name, value)| (name.to_string(), value.to_string()))
+        .collect()
+}
+
+fn header(headers: &HashMap<String, String>, name: &str) -> Option<String> {
+    headers.iter()
+        .find(|(key, _)| key.eq_ignore_ascii_case(name))
+        .map(|(_, value)| value.clone())
+}
+
+fn response_header(response: &ResponseData, name: &str) -> Option<String> {
+    response.headers.iter()
+        .find(|(key, _)| key.eq_ignore_ascii_case(name))
+        .map(|(_, value)| value.clone())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(at: &str, url: &str, response: Option<ResponseData>) -> HistoryEntry {
+        HistoryEntry {
+            id: next_request_id(),
+            timestamp: DateTime::parse_from_rfc3339(at).unwrap().with_timezone(&Utc),
+            request: RequestData {
+                url: url.to_string(),
+                method: "POST".to_string(),
+                headers: HashMap::from([("Content-Type".to_string(), "application/json".to_string())]),
+                disabled_headers: HashMap::new(),
+                body: Some("{\"name\":\"Rex\"}".to_string()),
+            },
+            response,
+        }
+    }
+
+    fn response(headers: &[(&str, &str)], body: &str, encoding: Option<&str>, size_bytes: usize) -> ResponseData {
+        ResponseData {
+            status: Some(201),
+            status_text: Some("201 Created".to_string()),
+            headers: headers.iter().map(|(name, value)| (name.to_string(), value.to_string())).collect(),
+            body: body.to_string(),
+            encoding: encoding.map(str::to_string),
+            time_ms: 12,
+            size_bytes,
+            http_version: Some("HTTP/2.0".to_string()),
+            timings: Some(Timings {
+                dns_ms: Some(1.5),
+                connect_ms: Some(2.0),
+                tls_ms: Some(4.25),
+                ttfb_ms: 3.0,
+                download_ms: 1.25,
+            }),
+        }
+    }
+
+    #[test]
+    fn export_and_import_round_trip() {
+        let entries = vec![
+            entry("2026-10-18T09:30:00Z", "https://api.example.com/pets?limit=2", Some(response(
+                &[("content-type", "application/json"), ("set-cookie", "a=1"), ("set-cookie", "b=2")],
+                "{\"id\":1}",
+                None,
+                8,
+            ))),
+            entry("2026-10-18T09:31:00Z", "https://api.example.com/logo.png", Some(response(
+                &[("content-type", "image/png")],
+                "iVBORw0KGgo=",
+                Some("base64"),
+                8,
+            ))),
+            entry("2026-10-18T09:32:00Z", "https://down.example.com/", None),
+        ];
+        let har = to_har(&entries);
+        let exported = &har["log"]["entries"];
+        assert_eq!(exported[0]["request"]["httpVersion"], "HTTP/2.0");
+        assert_eq!(exported[0]["response"]["httpVersion"], "HTTP/2.0");
+        assert_eq!(exported[0]["request"]["queryString"], json!([{ "name": "limit", "value": "2" }]));
+        assert_eq!(exported[0]["response"]["headers"].as_array().unwrap().len(), 3);
+        assert_eq!(exported[0]["timings"]["connect"], 6.25);
+        assert_eq!(exported[1]["response"]["content"], json!({
+            "size": 8, "mimeType": "image/png", "text": "iVBORw0KGgo=", "encoding": "base64",
+        }));
+        assert_eq!(exported[2]["response"]["status"], 0);
+
+        let imported = parse(&serde_json::to_string(&har).unwrap()).unwrap();
+        assert_eq!(imported[..2], entries[..2]);
+        // The time of an entry without a response isn't known
+        assert_eq!(imported[2].response, None);
+        assert_eq!(imported[2].request, entries[2].request);
+        assert_eq!(imported[1].response.as_ref().unwrap().body_bytes().unwrap(), b"\x89PNG\r\n\x1a\n");
+    }
+
+    #[test]
+    fn imports_browser_entries() {
+        let har = json!({ "log": { "entries": [{
+            "startedDateTime": "2026-10-18T09:30:00.123Z",
+            "time": 50.7,
+            "request": { "method": "get", "url": "https://example.com/", "headers": [
+                { "name": ":authority", "value": "example.com" },
+                { "name": "accept", "value": "*/*" },
+            ] },
+            "response": { "status": 200, "statusText": "", "httpVersion": "h2", "headers": [
+                { "name": "set-cookie", "value": "a=1" },
+                { "name": "set-cookie", "value": "b=2" },
+            ], "content": { "size": -1, "mimeType": "text/html", "text": "<p>" } },
+            "timings": { "blocked": 1, "dns": -1, "connect": -1, "ssl": -1, "send": 0.5, "wait": 40, "receive": 10.2 },
+        }] } });
+        let entries = from_har(&har).unwrap();
+        let request = &entries[0].request;
+        assert_eq!(request.method, "GET");
+        assert_eq!(request.headers, HashMap::from([("accept".to_string(), "*/*".to_string())]));
+        let response = entries[0].response.as_ref().unwrap();
+        assert_eq!(response.status_text.as_deref(), Some("200 OK"));
+        assert_eq!(response.headers.len(), 2);
+        assert_eq!((response.size_bytes, response.time_ms), (3, 50));
+        assert_eq!(response.http_version.as_deref(), Some("h2"));
+        let timings = response.timings.as_ref().unwrap();
+        assert_eq!((timings.dns_ms, timings.connect_ms, timings.ttfb_ms), (None, None, 40.5));
+        assert!(from_har(&json!({ "entries": [] })).is_err());
+    }
+}
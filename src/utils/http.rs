@@ -1,14 +1,215 @@
-use serde_json::Value;
+use super::format::formatter_for;
 
+// Pretty-prints a response body with the formatter registered for its content
+// type, or returns it unchanged when there is none.
 pub fn format_response_body(content_type: &str, body: &str) -> String {
-    match content_type.split(';').next().unwrap_or("").trim() {
-        "application/json" => {
-            if let Ok(json) = serde_json::from_str::<Value>(body) {
-                serde_json::to_string_pretty(&json).unwrap_or_else(|_| body.to_string())
-            } else {
-                body.to_string()
+    match formatter_for(content_type) {
+        Some(formatter) => formatter.format(body),
+        None => body.to_string(),
+    }
+}
+
+const BYTES_PER_LINE: usize = 16;
+
+// Returns the bare mime type ("image/png") without parameters, lowercased.
+pub fn mime_type(content_type: &str) -> String {
+    content_type
+        .split(';')
+        .next()
+        .unwrap_or("")
+        .trim()
+        .to_ascii_lowercase()
+}
+
+// Decides whether a response body should be treated as raw bytes rather than text.
+// The content type wins when it is conclusive; otherwise the bytes are sniffed.
+pub fn is_binary_body(content_type: &str, content_encoding: &str, bytes: &[u8]) -> bool {
+    let encoding = content_encoding.trim().to_ascii_lowercase();
+    if !encoding.is_empty() && encoding != "identity" {
+        // reqwest is built without decompression, so the body is still compressed
+        return true;
+    }
+
+    let mime = mime_type(content_type);
+    if mime.starts_with("text/")
+        || mime.ends_with("+json")
+        || mime.ends_with("+xml")
+        || mime.ends_with("+yaml")
+    {
+        return false;
+    }
+    match mime.as_str() {
+        "application/json"
+        | "application/xml"
+        | "application/javascript"
+        | "application/ecmascript"
+        | "application/x-www-form-urlencoded"
+        | "application/yaml"
+        | "application/x-yaml"
+        | "application/graphql"
+        | "application/x-ndjson" => return false,
+        _ => {}
+    }
+    if mime.starts_with("image/")
+        || mime.starts_with("audio/")
+        || mime.starts_with("video/")
+        || mime.starts_with("font/")
+    {
+        return true;
+    }
+    match mime.as_str() {
+        "application/octet-stream"
+        | "application/pdf"
+        | "application/zip"
+        | "application/gzip"
+        | "application/x-gzip"
+        | "application/x-tar"
+        | "application/protobuf"
+        | "application/x-protobuf"
+        | "application/grpc"
+        | "application/wasm" => return true,
+        _ => {}
+    }
+
+    let sample = &bytes[..bytes.len().min(8192)];
+    sample.contains(&0) || std::str::from_utf8(bytes).is_err()
+}
+
+// Number of lines `hex_dump` produces for a body of the given size.
+pub fn hex_dump_line_count(len: usize) -> usize {
+    len.div_ceil(BYTES_PER_LINE)
+}
+
+// Renders `count` lines of an `xxd`-style dump starting at line `start`:
+// "00000010  89 50 4e 47 0d 0a 1a 0a  00 00 00 0d 49 48 44 52  |.PNG........IHDR|"
+pub fn hex_dump(bytes: &[u8], start: usize, count: usize) -> Vec<String> {
+    bytes
+        .chunks(BYTES_PER_LINE)
+        .enumerate()
+        .skip(start)
+        .take(count)
+        .map(|(line, chunk)| {
+            let mut hex = String::with_capacity(BYTES_PER_LINE * 3 + 1);
+            for i in 0..BYTES_PER_LINE {
+                if i == BYTES_PER_LINE / 2 {
+                    hex.push(' ');
+                }
+                match chunk.get(i) {
+                    Some(b) => hex.push_str(&format!("{:02x} ", b)),
+                    None => hex.push_str("   "),
+                }
             }
-        }
-        _ => body.to_string()
+            let ascii: String = chunk
+                .iter()
+                .map(|&b| if b.is_ascii_graphic() || b == b' ' { b as char } else { '.' })
+                .collect();
+            format!("{:08x}  {} |{}|", line * BYTES_PER_LINE, hex, ascii)
+        })
+        .collect()
+}
+
+// Suggests a file name for saving a response body, based on the last URL
+// path segment and the content type.
+pub fn suggested_file_name(url: &str, content_type: &str) -> String {
+    let segment = reqwest::Url::parse(url)
+        .ok()
+        .and_then(|u| {
+            u.path_segments()
+                .and_then(|mut s| s.next_back().map(|s| s.to_string()))
+        })
+        .filter(|s| !s.is_empty())
+        .unwrap_or_else(|| "response".to_string());
+
+    if segment.contains('.') {
+        return segment;
+    }
+    match extension_for_content_type(content_type) {
+        Some(ext) => format!("{}.{}", segment, ext),
+        None => segment,
+    }
+}
+
+fn extension_for_content_type(content_type: &str) -> Option<&'static str> {
+    let ext = match mime_type(content_type).as_str() {
+        "application/json" => "json",
+        "application/xml" | "text/xml" => "xml",
+        "text/html" => "html",
+        "text/plain" => "txt",
+        "text/css" => "css",
+        "text/csv" => "csv",
+        "application/javascript" | "text/javascript" => "js",
+        "application/pdf" => "pdf",
+        "application/zip" => "zip",
+        "application/gzip" | "application/x-gzip" => "gz",
+        "application/protobuf" | "application/x-protobuf" => "pb",
+        "application/wasm" => "wasm",
+        "image/png" => "png",
+        "image/jpeg" => "jpg",
+        "image/gif" => "gif",
+        "image/webp" => "webp",
+        "image/svg+xml" => "svg",
+        "application/octet-stream" => "bin",
+        _ => return None,
+    };
+    Some(ext)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn content_type_decides_when_conclusive() {
+        let png = b"\x89PNG\r\n\x1a\n";
+        assert!(!is_binary_body("application/json; charset=utf-8", "", b"{}"));
+        assert!(!is_binary_body("text/html", "", b"<p>hi</p>"));
+        assert!(!is_binary_body("application/problem+json", "", b"{}"));
+        assert!(!is_binary_body("application/atom+xml", "", b"<feed/>"));
+        assert!(is_binary_body("image/png", "", png));
+        assert!(is_binary_body("application/octet-stream", "", b"plain words"));
+        assert!(is_binary_body("application/PDF", "", b"%PDF-1.7"));
+        // A text type wins over odd bytes in the body
+        assert!(!is_binary_body("text/plain", "", b"a\0b"));
+    }
+
+    #[test]
+    fn unknown_types_are_sniffed() {
+        assert!(!is_binary_body("", "", b"just text"));
+        assert!(!is_binary_body("application/x-custom", "", "héllo".as_bytes()));
+        assert!(is_binary_body("", "", b"text with a \0 byte"));
+        assert!(is_binary_body("application/x-custom", "", &[0xff, 0xfe, 0x41]));
+    }
+
+    #[test]
+    fn compressed_bodies_are_binary() {
+        assert!(is_binary_body("application/json", "gzip", b"{}"));
+        assert!(is_binary_body("text/plain", " BR ", b"text"));
+        assert!(!is_binary_body("application/json", "identity", b"{}"));
+    }
+
+    #[test]
+    fn hex_dump_lines() {
+        let bytes: Vec<u8> = (0x41..0x41 + 20).collect();
+        assert_eq!(hex_dump_line_count(bytes.len()), 2);
+        assert_eq!(hex_dump_line_count(16), 1);
+        assert_eq!(hex_dump_line_count(0), 0);
+
+        let lines = hex_dump(&bytes, 0, 10);
+        assert_eq!(lines, vec![
+            "00000000  41 42 43 44 45 46 47 48  49 4a 4b 4c 4d 4e 4f 50  |ABCDEFGHIJKLMNOP|",
+            "00000010  51 52 53 54                                       |QRST|",
+        ]);
+        // Every line has the hex columns at the same width
+        assert_eq!(lines[0].find('|'), lines[1].find('|'));
+    }
+
+    #[test]
+    fn hex_dump_window_and_unprintable_bytes() {
+        let mut bytes: Vec<u8> = (0..48).collect();
+        bytes[17] = b' ';
+        let lines = hex_dump(&bytes, 1, 5);
+        assert_eq!(lines.len(), 2);
+        assert_eq!(lines[0], "00000010  10 20 12 13 14 15 16 17  18 19 1a 1b 1c 1d 1e 1f  |. ..............|");
+        assert!(lines[1].starts_with("00000020  20 21 22"), "{}", lines[1]);
     }
 }
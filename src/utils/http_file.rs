@@ -0,0 +1,470 @@
+use std::collections::{BTreeMap, HashMap};
+use std::ops::Range;
+use std::path::Path;
+use anyhow::{anyhow, Context, Result};
+use crate::app::state::HttpMethod;
+use crate::data::storage;
+use crate::models::collection::{
+    next_request_id, resolve_variables, Collection, CollectionInfo, CollectionItem, Request, RequestOptions, SavedRequest,
+};
+
+// `.http` / `.rest` files as written for the VS Code REST Client and the
+// JetBrains HTTP client: requests separated by `###` lines, `@name = value`
+// file variables, `# @name` request names and `{{name}}` references.
+
+const METHODS: [&str; 9] = ["GET", "POST", "PUT", "DELETE", "PATCH", "HEAD", "OPTIONS", "TRACE", "CONNECT"];
+
+pub struct HttpFile {
+    pub variables: BTreeMap<String, String>,
+    pub requests: Vec<HttpRequest>,
+}
+
+pub struct HttpRequest {
+    // `# @name`, the text after `###`, or the request line
+    pub name: String,
+    pub request: Request,
+    // Headers in the order they're written
+    header_order: Vec<String>,
+    // Trailing `HTTP/1.1` of the request line
+    version: Option<String>,
+    // Bytes from the request line to the end of the body. Comments, names
+    // and variables before it, and response handlers after it, aren't part
+    // of it and survive a rewrite.
+    span: Range<usize>,
+}
+
+pub fn is_http_file(path: &Path) -> bool {
+    path.extension()
+        .and_then(|ext| ext.to_str())
+        .is_some_and(|ext| ext.eq_ignore_ascii_case("http") || ext.eq_ignore_ascii_case("rest"))
+}
+
+pub fn read_file(path: &Path) -> Result<HttpFile> {
+    let text = std::fs::read_to_string(path)
+        .with_context(|| format!("could not read {}", path.display()))?;
+    Ok(parse(&text))
+}
+
+pub fn import_file(path: &Path) -> Result<Collection> {
+    let path = path.canonicalize().with_context(|| format!("could not read {}", path.display()))?;
+    let file = read_file(&path)?;
+    if file.requests.is_empty() {
+        return Err(anyhow!("no requests in {}", path.display()));
+    }
+    let name = path.file_name().map(|name| name.to_string_lossy().into_owned()).unwrap_or_default();
+    Ok(Collection {
+        info: CollectionInfo {
+            name,
+            description: format!("Read from {}", path.display()),
+            created_at: chrono::Utc::now(),
+        },
+        requests: file.items(),
+        variables: file.variables,
+        source: Some(path.to_string_lossy().into_owned()),
+    })
+}
+
+impl HttpFile {
+    pub fn items(&self) -> Vec<CollectionItem> {
+        self.requests.iter()
+            .map(|request| CollectionItem::Request(Box::new(SavedRequest {
+                id: next_request_id(),
+                name: request.name.clone(),
+                request: request.request.clone(),
+                response_filter: None,
+                openapi: None,
+            })))
+            .collect()
+    }
+}
+
+pub fn parse(text: &str) -> HttpFile {
+    let mut variables = BTreeMap::new();
+    let mut requests: Vec<HttpRequest> = Vec::new();
+
+    let mut block: Vec<(usize, &str)> = Vec::new();
+    let mut title = None;
+    let mut offset = 0;
+    for line in text.split_inclusive('\n') {
+        let content = line.trim_end_matches(['\r', '\n']);
+        if let Some(rest) = content.strip_prefix("###") {
+            requests.extend(parse_block(&block, title.take(), &mut variables));
+            block.clear();
+            title = Some(rest.trim().to_string()).filter(|title| !title.is_empty());
+        } else {
+            block.push((offset, content));
+        }
+        offset += line.len();
+    }
+    requests.extend(parse_block(&block, title, &mut variables));
+
+    // Variables may refer to ones defined before them
+    let mut resolved = variables.clone();
+    for _ in 0..variables.len() {
+        let next: BTreeMap<String, String> = resolved.iter()
+            .map(|(name, value)| (name.clone(), resolve_variables(&resolved, value)))
+            .collect();
+        if next == resolved {
+            break;
+        }
+        resolved = next;
+    }
+
+    // Names are how saved edits find their request again
+    let mut seen: HashMap<String, usize> = HashMap::new();
+    for request in &mut requests {
+        let count = seen.entry(request.name.clone()).or_default();
+        *count += 1;
+        if *count > 1 {
+            request.name = format!("{} ({})", request.name, count);
+        }
+    }
+    HttpFile { variables: resolved, requests }
+}
+
+enum Section {
+    Preamble,
+    Headers,
+    Body,
+}
+
+fn parse_block(lines: &[(usize, &str)], title: Option<String>,
+               variables: &mut BTreeMap<String, String>) -> Option<HttpRequest> {
+    let mut name = title;
+    let mut section = Section::Preamble;
+    let mut request: Option<HttpRequest> = None;
+    let mut body: Vec<&str> = Vec::new();
+
+    for &(offset, line) in lines {
+        let trimmed = line.trim();
+        match section {
+            Section::Preamble => {
+                if trimmed.is_empty() {
+                    continue;
+                }
+                if let Some(comment) = comment(trimmed) {
+                    if let Some(value) = comment.strip_prefix("@name") {
+                        let value = value.trim_start_matches([' ', '\t', '=']).trim();
+                        if !value.is_empty() {
+                            name = Some(value.to_string());
+                        }
+                    }
+                } else if let Some((var, value)) = trimmed.strip_prefix('@').and_then(|def| def.split_once('=')) {
+                    variables.insert(var.trim().to_string(), value.trim().to_string());
+                } else {
+                    let (method, url, version) = request_line(trimmed);
+                    request = Some(HttpRequest {
+                        name: String::new(),
+                        request: Request {
+                            method,
+                            url,
+                            headers: HashMap::new(),
+                            body: None,
+                            options: RequestOptions::default(),
+                            graphql: None,
+                            grpc: None,
+                        },
+                        header_order: Vec::new(),
+                        version,
+                        span: offset..offset + line.len(),
+                    });
+                    section = Section::Headers;
+                }
+            }
+            Section::Headers => {
+                let current = request.as_mut()?;
+                if trimmed.is_empty() {
+                    section = Section::Body;
+                    continue;
+                }
+                current.span.end = offset + line.len();
+                if comment(trimmed).is_some() {
+                    continue;
+                }
+                // A long query string may go on the following lines
+                if current.header_order.is_empty() && line.starts_with([' ', '\t'])
+                    && (trimmed.starts_with('?') || trimmed.starts_with('&')) {
+                    current.request.url.push_str(trimmed);
+                } else if let Some((header, value)) = trimmed.split_once(':') {
+                    let header = header.trim().to_string();
+                    current.request.headers.insert(header.clone(), value.trim().to_string());
+                    current.header_order.push(header);
+                }
+            }
+            Section::Body => {
+                // Response handlers and redirects end the body
+                if trimmed.starts_with("> ") || trimmed.starts_with(">>") || trimmed.starts_with("<> ") {
+                    break;
+                }
+                if !trimmed.is_empty() {
+                    request.as_mut()?.span.end = offset + line.len();
+                }
+                body.push(line);
+            }
+        }
+    }
+
+    let mut request = request?;
+    while body.last().is_some_and(|line| line.trim().is_empty()) {
+        body.pop();
+    }
+    if !body.is_empty() {
+        request.request.body = Some(body.join("\n"));
+    }
+    request.name = name.unwrap_or_else(|| format!("{} {}", request.request.method, request.request.url));
+    Some(request)
+}
+
+fn comment(line: &str) -> Option<&str> {
+    line.strip_prefix('#').or_else(|| line.strip_prefix("//")).map(str::trim)
+}
+
+// `METHOD url HTTP/1.1`, where only the url is required. Standard methods
+// may be written in any case; any other token is a custom method, kept as
+// written, as long as a url follows it.
+fn request_line(line: &str) -> (String, String, Option<String>) {
+    let (method, rest) = match line.split_once(char::is_whitespace) {
+        Some((first, rest)) if METHODS.contains(&first.to_uppercase().as_str()) => (first.to_uppercase(), rest.trim()),
+        Some((first, rest)) if HttpMethod::is_token(first) && !url_and_version(rest.trim()).0.is_empty() => {
+            (first.to_string(), rest.trim())
+        }
+        _ if METHODS.contains(&line.to_uppercase().as_str()) => (line.to_uppercase(), ""),
+        _ => ("GET".to_string(), line),
+    };
+    let (url, version) = url_and_version(rest);
+    (method, url.to_string(), version.map(str::to_string))
+}
+
+fn url_and_version(rest: &str) -> (&str, Option<&str>) {
+    match rest.rsplit_once(char::is_whitespace) {
+        Some((url, version)) if version.starts_with("HTTP/") => (url.trim(), Some(version)),
+        _ if rest.starts_with("HTTP/") => ("", Some(rest)),
+        _ => (rest, None),
+    }
+}
+
+// Writes `request` back to the file. The request named `original` is
+// rewritten in place, anything else in the file is left as it is; without
+// one the request is added at the end. Values that still match what the
+// file's `{{variables}}` resolve to are kept as written.
+pub fn save_request(path: &Path, collection: &Collection, original: Option<&str>, request: &Request) -> Result<()> {
+    let text = std::fs::read_to_string(path)
+        .with_context(|| format!("could not read {}", path.display()))?;
+    let file = parse(&text);
+    let existing = original.and_then(|name| file.requests.iter().find(|r| r.name == name));
+    // Written lines end the way the file's do
+    let newline = if text.contains("\r\n") { "\r\n" } else { "\n" };
+    let lines = |text: &str| text.replace("\r\n", "\n").replace('\n', newline);
+
+    let text = match existing {
+        Some(existing) => {
+            if same_request(collection, &existing.request, request) {
+                return Ok(());
+            }
+            let rendered = lines(&render(collection, Some(existing), request));
+            format!("{}{}{}", &text[..existing.span.start], rendered, &text[existing.span.end..])
+        }
+        None => {
+            let mut text = text;
+            if !text.is_empty() && !text.ends_with('\n') {
+                text.push_str(newline);
+            }
+            if !file.requests.is_empty() {
+                text.push_str(&lines("\n###\n"));
+            }
+            text.push_str(&lines(&render(collection, None, request)));
+            text.push_str(newline);
+            text
+        }
+    };
+    storage::write_atomic(path, text.as_bytes()).with_context(|| format!("could not write {}", path.display()))
+}
+
+fn same_request(collection: &Collection, written: &Request, request: &Request) -> bool {
+    let resolve = |text: &str| collection.resolve(text);
+    let headers: HashMap<String, String> = written.headers.iter()
+        .map(|(name, value)| (name.clone(), resolve(value)))
+        .collect();
+    written.method == request.method
+        && resolve(&written.url) == request.url
+        && headers == request.headers
+        && written.body.as_deref().map(resolve).unwrap_or_default() == request.body.clone().unwrap_or_default()
+}
+
+fn render(collection: &Collection, existing: Option<&HttpRequest>, request: &Request) -> String {
+    // The written form is kept when it still resolves to the new value
+    let keep = |written: Option<&String>, value: &str| match written {
+        Some(written) if collection.resolve(written) == value => written.clone(),
+        _ => value.to_string(),
+    };
+
+    let mut text = format!("{} {}", request.method,
+        keep(existing.map(|e| &e.request.url), &request.url));
+    if let Some(version) = existing.and_then(|e| e.version.as_ref()) {
+        text.push(' ');
+        text.push_str(version);
+    }
+    text.push('\n');
+
+    let mut names: Vec<&String> = existing
+        .map(|e| e.header_order.iter().filter(|name| request.headers.contains_key(*name)).collect())
+        .unwrap_or_default();
+    let mut added: Vec<&String> = request.headers.keys().filter(|name| !names.contains(name)).collect();
+    added.sort();
+    names.extend(added);
+    for name in names {
+        let written = existing.and_then(|e| e.request.headers.get(name));
+        text.push_str(&format!("{}: {}\n", name, keep(written, &request.headers[name])));
+    }
+
+    if let Some(body) = request.body.as_deref().filter(|body| !body.trim().is_empty()) {
+        text.push('\n');
+        text.push_str(&keep(existing.and_then(|e| e.request.body.as_ref()), body));
+    }
+    text.truncate(text.trim_end_matches('\n').len());
+    text
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const FILE: &str = "@host = https://api.example.com
+@token = secret
+
+# @name list
+GET {{host}}/users HTTP/1.1
+Accept: application/json
+Authorization: Bearer {{token}}
+
+###
+# @name create
+POST {{host}}/users
+Content-Type: application/json
+
+{\"name\": \"Ann\"}
+
+> {% client.global.set(\"id\", response.body.id); %}
+";
+
+    fn collection(text: &str) -> Collection {
+        let file = parse(text);
+        Collection {
+            info: CollectionInfo {
+                name: "test.http".to_string(),
+                description: String::new(),
+                created_at: chrono::Utc::now(),
+            },
+            requests: file.items(),
+            variables: file.variables,
+            source: None,
+        }
+    }
+
+    // The request as the editor shows it, with the variables filled in
+    fn opened(collection: &Collection, name: &str) -> Request {
+        let request = collection.requests.iter()
+            .find_map(|item| match item {
+                CollectionItem::Request(saved) if saved.name == name => Some(saved.request.clone()),
+                _ => None,
+            })
+            .unwrap();
+        Request {
+            url: collection.resolve(&request.url),
+            headers: request.headers.iter().map(|(k, v)| (k.clone(), collection.resolve(v))).collect(),
+            body: request.body.as_deref().map(|body| collection.resolve(body)),
+            ..request
+        }
+    }
+
+    // Saves `request` over `original` in a file holding `text` and returns the result
+    fn save(text: &str, original: Option<&str>, request: &Request) -> String {
+        static COUNT: std::sync::atomic::AtomicUsize = std::sync::atomic::AtomicUsize::new(0);
+        let count = COUNT.fetch_add(1, std::sync::atomic::Ordering::Relaxed);
+        let path = std::env::temp_dir().join(format!("raquet-test-{}-{}.http", std::process::id(), count));
+        std::fs::write(&path, text).unwrap();
+        save_request(&path, &collection(text), original, request).unwrap();
+        let saved = std::fs::read_to_string(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        saved
+    }
+
+    #[test]
+    fn untouched_requests_leave_the_file_as_it_is() {
+        let collection = collection(FILE);
+        for name in ["list", "create"] {
+            assert_eq!(save(FILE, Some(name), &opened(&collection, name)), FILE);
+        }
+    }
+
+    #[test]
+    fn editing_one_request_keeps_the_rest() {
+        let collection = collection(FILE);
+        let mut request = opened(&collection, "create");
+        request.body = Some("{\"name\": \"Bob\"}".to_string());
+        let saved = save(FILE, Some("create"), &request);
+        assert_eq!(saved, FILE.replace("Ann", "Bob"));
+    }
+
+    #[test]
+    fn variables_are_kept_where_values_still_match() {
+        let collection = collection(FILE);
+        let mut request = opened(&collection, "list");
+        request.headers.insert("Accept".to_string(), "text/plain".to_string());
+        let saved = save(FILE, Some("list"), &request);
+        assert_eq!(saved, FILE.replace("Accept: application/json", "Accept: text/plain"));
+        assert!(saved.contains("GET {{host}}/users HTTP/1.1"));
+        assert!(saved.contains("Authorization: Bearer {{token}}"));
+    }
+
+    #[test]
+    fn new_requests_are_appended() {
+        let collection = collection(FILE);
+        let mut request = opened(&collection, "list");
+        request.url = "https://api.example.com/health".to_string();
+        request.headers.clear();
+        let saved = save(FILE, None, &request);
+        assert_eq!(saved, format!("{}\n###\nGET https://api.example.com/health\n", FILE));
+        assert_eq!(parse(&saved).requests.len(), 3);
+    }
+
+    #[test]
+    fn crlf_files_keep_their_line_endings() {
+        let text = FILE.replace('\n', "\r\n");
+        let collection = collection(&text);
+        assert_eq!(opened(&collection, "create").body.as_deref(), Some("{\"name\": \"Ann\"}"));
+        assert_eq!(save(&text, Some("create"), &opened(&collection, "create")), text);
+
+        let mut request = opened(&collection, "list");
+        request.headers.insert("X-Trace".to_string(), "1".to_string());
+        let saved = save(&text, Some("list"), &request);
+        assert_eq!(saved, text.replace("{{token}}\r\n", "{{token}}\r\nX-Trace: 1\r\n"));
+    }
+
+    #[test]
+    fn custom_methods_round_trip() {
+        let text = "@host = https://cdn.example\n\n# @name purge\nPURGE {{host}}/cache HTTP/1.1\n";
+        let collection = collection(text);
+        let mut request = opened(&collection, "purge");
+        assert_eq!(request.method, "PURGE");
+        assert_eq!(save(text, Some("purge"), &request), text);
+
+        request.headers.insert("Surrogate-Key".to_string(), "users".to_string());
+        let saved = save(text, Some("purge"), &request);
+        assert_eq!(saved, format!("{}Surrogate-Key: users\n", text));
+        let reparsed = parse(&saved);
+        assert_eq!(reparsed.requests[0].request.method, "PURGE");
+        assert_eq!(reparsed.requests[0].request.url, "{{host}}/cache");
+
+        // Custom methods keep their case, standard ones are upper-cased
+        request.method = "propFind".to_string();
+        let saved = save(text, None, &request);
+        let methods: Vec<_> = parse(&saved).requests.iter().map(|r| r.request.method.clone()).collect();
+        assert_eq!(methods, ["PURGE", "propFind"]);
+        assert_eq!(parse("get https://example.com").requests[0].request.method, "GET");
+        // A lone host with a version is still a GET
+        let host_only = parse("example.com HTTP/1.1");
+        assert_eq!(host_only.requests[0].request.method, "GET");
+        assert_eq!(host_only.requests[0].request.url, "example.com");
+    }
+}
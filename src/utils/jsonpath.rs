@@ -0,0 +1,573 @@
+use anyhow::{anyhow, bail, Result};
+use serde_json::Value;
+
+// A JSONPath subset, plus the jq-style shorthands people type out of habit:
+//
+//   $.store.book[0].title      .store.book[0].title
+//   $..author                  $.items[*].id        .items[].id
+//   $['key with spaces']       $.items[-1]          $.items[1:3]
+//   $.items[0,2]               $.items[?(@.price < 10)]
+//   $.items[?(@.tags)]         $.items[?(@.name == "raquet")]
+//
+// Every expression yields a list of matched values.
+pub fn query(root: &Value, expr: &str) -> Result<Vec<Value>> {
+    let segments = parse(expr)?;
+    let mut current = vec![root];
+    for segment in &segments {
+        let mut next = Vec::new();
+        for value in current {
+            apply(segment, value, &mut next);
+        }
+        current = next;
+    }
+    Ok(current.into_iter().cloned().collect())
+}
+
+// Runs `query` and folds the result into a single value for display: one
+// match is shown as-is, several as an array.
+pub fn filter(root: &Value, expr: &str) -> Result<Value> {
+    let mut matches = query(root, expr)?;
+    Ok(if matches.len() == 1 {
+        matches.remove(0)
+    } else {
+        Value::Array(matches)
+    })
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Segment {
+    Key(String),
+    Index(i64),
+    Wildcard,
+    Slice(Option<i64>, Option<i64>),
+    Union(Vec<Selector>),
+    Filter(Predicate),
+    // `..` followed by the segment to apply at every depth
+    Descendant(Box<Segment>),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Selector {
+    Key(String),
+    Index(i64),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+struct Predicate {
+    path: Vec<String>,
+    comparison: Option<(Op, Value)>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Op {
+    Eq,
+    Ne,
+    Lt,
+    Le,
+    Gt,
+    Ge,
+}
+
+fn apply<'a>(segment: &Segment, value: &'a Value, out: &mut Vec<&'a Value>) {
+    match segment {
+        Segment::Key(key) => {
+            if let Some(v) = value.get(key) {
+                out.push(v);
+            }
+        }
+        Segment::Index(index) => {
+            if let Some(v) = index_array(value, *index) {
+                out.push(v);
+            }
+        }
+        Segment::Wildcard => match value {
+            Value::Array(items) => out.extend(items.iter()),
+            Value::Object(map) => out.extend(map.values()),
+            _ => {}
+        },
+        Segment::Slice(start, end) => {
+            if let Value::Array(items) = value {
+                let len = items.len() as i64;
+                let clamp = |i: i64| if i < 0 { (len + i).max(0) } else { i.min(len) };
+                let start = clamp(start.unwrap_or(0));
+                let end = clamp(end.unwrap_or(len));
+                if start < end {
+                    out.extend(items[start as usize..end as usize].iter());
+                }
+            }
+        }
+        Segment::Union(selectors) => {
+            for selector in selectors {
+                let v = match selector {
+                    Selector::Key(key) => value.get(key),
+                    Selector::Index(index) => index_array(value, *index),
+                };
+                if let Some(v) = v {
+                    out.push(v);
+                }
+            }
+        }
+        Segment::Filter(predicate) => {
+            let candidates: Vec<&Value> = match value {
+                Value::Array(items) => items.iter().collect(),
+                Value::Object(map) => map.values().collect(),
+                _ => Vec::new(),
+            };
+            out.extend(candidates.into_iter().filter(|v| predicate.matches(v)));
+        }
+        Segment::Descendant(inner) => {
+            apply(inner, value, out);
+            match value {
+                Value::Array(items) => {
+                    for item in items {
+                        apply(segment, item, out);
+                    }
+                }
+                Value::Object(map) => {
+                    for item in map.values() {
+                        apply(segment, item, out);
+                    }
+                }
+                _ => {}
+            }
+        }
+    }
+}
+
+fn index_array(value: &Value, index: i64) -> Option<&Value> {
+    let items = value.as_array()?;
+    let index = if index < 0 { items.len() as i64 + index } else { index };
+    usize::try_from(index).ok().and_then(|i| items.get(i))
+}
+
+impl Predicate {
+    fn matches(&self, value: &Value) -> bool {
+        let mut target = value;
+        for key in &self.path {
+            match target.get(key) {
+                Some(v) => target = v,
+                None => return false,
+            }
+        }
+        let (op, expected) = match &self.comparison {
+            Some(c) => c,
+            None => return true,
+        };
+        match (target, expected) {
+            (Value::Number(a), Value::Number(b)) => {
+                let (a, b) = (a.as_f64().unwrap_or(f64::NAN), b.as_f64().unwrap_or(f64::NAN));
+                match op {
+                    Op::Eq => a == b,
+                    Op::Ne => a != b,
+                    Op::Lt => a < b,
+                    Op::Le => a <= b,
+                    Op::Gt => a > b,
+                    Op::Ge => a >= b,
+                }
+            }
+            (Value::String(a), Value::String(b)) => match op {
+                Op::Eq => a == b,
+                Op::Ne => a != b,
+                Op::Lt => a < b,
+                Op::Le => a <= b,
+                Op::Gt => a > b,
+                Op::Ge => a >= b,
+            },
+            (a, b) => match op {
+                Op::Eq => a == b,
+                Op::Ne => a != b,
+                _ => false,
+            },
+        }
+    }
+}
+
+struct Parser<'a> {
+    chars: Vec<char>,
+    pos: usize,
+    expr: &'a str,
+}
+
+// Positions in errors count characters of the trimmed expression, which is
+// also the one they quote.
+fn parse(expr: &str) -> Result<Vec<Segment>> {
+    let expr = expr.trim();
+    let mut parser = Parser { chars: expr.chars().collect(), pos: 0, expr };
+    parser.parse_path()
+}
+
+impl Parser<'_> {
+    fn peek(&self) -> Option<char> {
+        self.chars.get(self.pos).copied()
+    }
+
+    fn eat(&mut self, c: char) -> bool {
+        if self.peek() == Some(c) {
+            self.pos += 1;
+            true
+        } else {
+            false
+        }
+    }
+
+    fn skip_whitespace(&mut self) {
+        while self.peek().is_some_and(|c| c.is_whitespace()) {
+            self.pos += 1;
+        }
+    }
+
+    fn error(&self, message: &str) -> anyhow::Error {
+        anyhow!("{} at position {} in '{}'", message, self.pos, self.expr)
+    }
+
+    fn parse_path(&mut self) -> Result<Vec<Segment>> {
+        // `$` is optional so jq-style `.foo` works too
+        self.eat('$');
+        let mut segments = Vec::new();
+        while let Some(c) = self.peek() {
+            match c {
+                '.' => {
+                    self.pos += 1;
+                    if self.eat('.') {
+                        let inner = if self.peek() == Some('[') {
+                            self.parse_bracket()?
+                        } else {
+                            self.parse_dot_member()?
+                        };
+                        segments.push(Segment::Descendant(Box::new(inner)));
+                    } else if self.peek() == Some('[') {
+                        // jq's `.[0]`
+                        segments.push(self.parse_bracket()?);
+                    } else if self.peek().is_none() {
+                        // A lone `.` (jq identity)
+                        break;
+                    } else {
+                        segments.push(self.parse_dot_member()?);
+                    }
+                }
+                '[' => segments.push(self.parse_bracket()?),
+                c if c.is_whitespace() => self.skip_whitespace(),
+                _ => return Err(self.error("unexpected character")),
+            }
+        }
+        Ok(segments)
+    }
+
+    fn parse_dot_member(&mut self) -> Result<Segment> {
+        if self.eat('*') {
+            return Ok(Segment::Wildcard);
+        }
+        let name = self.parse_identifier();
+        if name.is_empty() {
+            return Err(self.error("expected a member name"));
+        }
+        Ok(Segment::Key(name))
+    }
+
+    fn parse_identifier(&mut self) -> String {
+        let start = self.pos;
+        while self.peek().is_some_and(|c| c.is_alphanumeric() || c == '_' || c == '-' || c == '$') {
+            self.pos += 1;
+        }
+        self.chars[start..self.pos].iter().collect()
+    }
+
+    fn parse_bracket(&mut self) -> Result<Segment> {
+        self.eat('[');
+        self.skip_whitespace();
+
+        let segment = if self.eat(']') {
+            // jq's `.[]`
+            return Ok(Segment::Wildcard);
+        } else if self.eat('*') {
+            Segment::Wildcard
+        } else if self.eat('?') {
+            Segment::Filter(self.parse_filter()?)
+        } else {
+            let mut selectors = Vec::new();
+            let mut slice = None;
+            loop {
+                self.skip_whitespace();
+                match self.peek() {
+                    Some('\'') | Some('"') => selectors.push(Selector::Key(self.parse_string()?)),
+                    Some(':') => {
+                        slice = Some(self.parse_slice(None)?);
+                        break;
+                    }
+                    _ => {
+                        let index = self.parse_int()?;
+                        self.skip_whitespace();
+                        if self.peek() == Some(':') {
+                            slice = Some(self.parse_slice(Some(index))?);
+                            break;
+                        }
+                        selectors.push(Selector::Index(index));
+                    }
+                }
+                self.skip_whitespace();
+                if !self.eat(',') {
+                    break;
+                }
+            }
+            match slice {
+                Some(slice) => slice,
+                None if selectors.len() == 1 => match selectors.remove(0) {
+                    Selector::Key(key) => Segment::Key(key),
+                    Selector::Index(index) => Segment::Index(index),
+                },
+                None => Segment::Union(selectors),
+            }
+        };
+
+        self.skip_whitespace();
+        if !self.eat(']') {
+            return Err(self.error("expected ']'"));
+        }
+        Ok(segment)
+    }
+
+    fn parse_slice(&mut self, start: Option<i64>) -> Result<Segment> {
+        self.eat(':');
+        self.skip_whitespace();
+        let end = if self.peek().is_some_and(|c| c == '-' || c.is_ascii_digit()) {
+            Some(self.parse_int()?)
+        } else {
+            None
+        };
+        Ok(Segment::Slice(start, end))
+    }
+
+    fn parse_int(&mut self) -> Result<i64> {
+        let start = self.pos;
+        self.eat('-');
+        while self.peek().is_some_and(|c| c.is_ascii_digit()) {
+            self.pos += 1;
+        }
+        let text: String = self.chars[start..self.pos].iter().collect();
+        text.parse().map_err(|_| self.error("expected an index"))
+    }
+
+    fn parse_string(&mut self) -> Result<String> {
+        let quote = self.peek().ok_or_else(|| self.error("expected a string"))?;
+        self.pos += 1;
+        let mut result = String::new();
+        loop {
+            match self.peek() {
+                None => return Err(self.error("unterminated string")),
+                Some('\\') => {
+                    self.pos += 1;
+                    if let Some(c) = self.peek() {
+                        result.push(c);
+                        self.pos += 1;
+                    }
+                }
+                Some(c) if c == quote => {
+                    self.pos += 1;
+                    return Ok(result);
+                }
+                Some(c) => {
+                    result.push(c);
+                    self.pos += 1;
+                }
+            }
+        }
+    }
+
+    // `?(@.a.b)` or `?(@.a.b <op> literal)`
+    fn parse_filter(&mut self) -> Result<Predicate> {
+        self.skip_whitespace();
+        let parenthesized = self.eat('(');
+        self.skip_whitespace();
+        if !self.eat('@') {
+            return Err(self.error("filters must start with '@'"));
+        }
+        let mut path = Vec::new();
+        loop {
+            if self.eat('.') {
+                let name = self.parse_identifier();
+                if name.is_empty() {
+                    return Err(self.error("expected a member name"));
+                }
+                path.push(name);
+            } else if self.peek() == Some('[') {
+                self.pos += 1;
+                self.skip_whitespace();
+                path.push(self.parse_string()?);
+                self.skip_whitespace();
+                if !self.eat(']') {
+                    return Err(self.error("expected ']'"));
+                }
+            } else {
+                break;
+            }
+        }
+
+        self.skip_whitespace();
+        let op = if self.eat('=') {
+            self.eat('=');
+            Some(Op::Eq)
+        } else if self.eat('!') {
+            if !self.eat('=') {
+                return Err(self.error("expected '!='"));
+            }
+            Some(Op::Ne)
+        } else if self.eat('<') {
+            Some(if self.eat('=') { Op::Le } else { Op::Lt })
+        } else if self.eat('>') {
+            Some(if self.eat('=') { Op::Ge } else { Op::Gt })
+        } else {
+            None
+        };
+
+        let comparison = match op {
+            Some(op) => {
+                self.skip_whitespace();
+                Some((op, self.parse_literal()?))
+            }
+            None => None,
+        };
+
+        self.skip_whitespace();
+        if parenthesized && !self.eat(')') {
+            return Err(self.error("expected ')'"));
+        }
+        Ok(Predicate { path, comparison })
+    }
+
+    fn parse_literal(&mut self) -> Result<Value> {
+        if matches!(self.peek(), Some('\'') | Some('"')) {
+            return Ok(Value::String(self.parse_string()?));
+        }
+        let start = self.pos;
+        while self.peek().is_some_and(|c| !matches!(c, ')' | ']') && !c.is_whitespace()) {
+            self.pos += 1;
+        }
+        let text: String = self.chars[start..self.pos].iter().collect();
+        if text.is_empty() {
+            bail!("expected a value after the comparison in '{}'", self.expr);
+        }
+        serde_json::from_str(&text).map_err(|_| self.error("invalid literal"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn store() -> Value {
+        json!({
+            "store": {
+                "book": [
+                    {"title": "Sayings", "author": "Rees", "price": 8.95, "tags": ["quotes"]},
+                    {"title": "Sword", "author": "Waugh", "price": 12.99},
+                    {"title": "Moby Dick", "author": "Melville", "price": 8.99, "isbn": "0-553"},
+                ],
+                "bicycle": {"color": "red", "price": 19.95},
+                "key with spaces": true,
+            }
+        })
+    }
+
+    fn only_filter(expr: &str) -> Predicate {
+        match parse(expr).unwrap().as_slice() {
+            [Segment::Filter(predicate)] => predicate.clone(),
+            other => panic!("not a single filter: {:?}", other),
+        }
+    }
+
+    fn titles(expr: &str) -> Vec<Value> {
+        query(&store(), expr).unwrap()
+    }
+
+    #[test]
+    fn parses_members_indexes_and_wildcards() {
+        assert_eq!(parse("$.store.book[0].title").unwrap(), vec![
+            Segment::Key("store".into()),
+            Segment::Key("book".into()),
+            Segment::Index(0),
+            Segment::Key("title".into()),
+        ]);
+        assert_eq!(parse("$.a[*]").unwrap(), vec![Segment::Key("a".into()), Segment::Wildcard]);
+        assert_eq!(parse("$.a.*").unwrap(), vec![Segment::Key("a".into()), Segment::Wildcard]);
+        assert_eq!(parse("$..author").unwrap(), vec![Segment::Descendant(Box::new(Segment::Key("author".into())))]);
+        assert_eq!(parse("$").unwrap(), vec![]);
+    }
+
+    #[test]
+    fn parses_jq_shorthands() {
+        assert_eq!(parse(".store.book[0]").unwrap(), parse("$.store.book[0]").unwrap());
+        assert_eq!(parse(".items[].id").unwrap(), parse("$.items[*].id").unwrap());
+        assert_eq!(parse(".[0]").unwrap(), vec![Segment::Index(0)]);
+        assert_eq!(parse(".").unwrap(), vec![]);
+    }
+
+    #[test]
+    fn parses_brackets() {
+        assert_eq!(parse("$['key with spaces']").unwrap(), vec![Segment::Key("key with spaces".into())]);
+        assert_eq!(parse(r#"$["it's"]"#).unwrap(), vec![Segment::Key("it's".into())]);
+        assert_eq!(parse(r"$['a\'b']").unwrap(), vec![Segment::Key("a'b".into())]);
+        assert_eq!(parse("$[-1]").unwrap(), vec![Segment::Index(-1)]);
+        assert_eq!(parse("$[ 0 , 2 ]").unwrap(), vec![Segment::Union(vec![Selector::Index(0), Selector::Index(2)])]);
+        assert_eq!(parse("$['a','b']").unwrap(), vec![Segment::Union(vec![Selector::Key("a".into()), Selector::Key("b".into())])]);
+        assert_eq!(parse("$[1:3]").unwrap(), vec![Segment::Slice(Some(1), Some(3))]);
+        assert_eq!(parse("$[:2]").unwrap(), vec![Segment::Slice(None, Some(2))]);
+        assert_eq!(parse("$[-2:]").unwrap(), vec![Segment::Slice(Some(-2), None)]);
+    }
+
+    #[test]
+    fn parses_filters() {
+        let predicate = only_filter("$[?(@.price < 10)]");
+        assert_eq!(predicate.path, vec!["price"]);
+        assert_eq!(predicate.comparison, Some((Op::Lt, json!(10))));
+
+        let predicate = only_filter(r#"$[?(@['a b'].c != "x")]"#);
+        assert_eq!(predicate.path, vec!["a b", "c"]);
+        assert_eq!(predicate.comparison, Some((Op::Ne, json!("x"))));
+
+        let predicate = only_filter("$[?@.tags]");
+        assert_eq!(predicate.comparison, None);
+
+        for (expr, op) in [("==", Op::Eq), ("=", Op::Eq), ("<=", Op::Le), (">", Op::Gt), (">=", Op::Ge)] {
+            let predicate = only_filter(&format!("$[?(@.n {} null)]", expr));
+            assert_eq!(predicate.comparison, Some((op, Value::Null)));
+        }
+    }
+
+    #[test]
+    fn queries() {
+        assert_eq!(titles("$.store.book[0].title"), vec![json!("Sayings")]);
+        assert_eq!(titles("$.store.book[-1].title"), vec![json!("Moby Dick")]);
+        assert_eq!(titles("$.store.book[0,2].author"), vec![json!("Rees"), json!("Melville")]);
+        assert_eq!(titles("$.store.book[1:].price"), vec![json!(12.99), json!(8.99)]);
+        assert_eq!(titles("$.store.book[?(@.price < 10)].title"), vec![json!("Sayings"), json!("Moby Dick")]);
+        assert_eq!(titles("$.store.book[?(@.isbn)].title"), vec![json!("Moby Dick")]);
+        assert_eq!(titles(r#"$.store.book[?(@.author == "Waugh")].price"#), vec![json!(12.99)]);
+        assert_eq!(titles("$..price").len(), 4);
+        assert_eq!(titles("$['store']['key with spaces']"), vec![json!(true)]);
+        assert!(titles("$.store.missing").is_empty());
+        assert!(titles("$.store.book[9]").is_empty());
+    }
+
+    #[test]
+    fn filter_folds_single_matches() {
+        assert_eq!(filter(&store(), "$.store.bicycle.color").unwrap(), json!("red"));
+        assert_eq!(filter(&store(), "$.store.book[:2].author").unwrap(), json!(["Rees", "Waugh"]));
+        assert_eq!(filter(&store(), "$.nothing").unwrap(), json!([]));
+    }
+
+    #[test]
+    fn errors_point_into_the_expression_shown() {
+        let error = parse("  $.a[0").unwrap_err().to_string();
+        assert_eq!(error, "expected ']' at position 5 in '$.a[0'");
+        let error = parse(" $.a b").unwrap_err().to_string();
+        assert_eq!(error, "unexpected character at position 4 in '$.a b'");
+        let error = parse("$['a").unwrap_err().to_string();
+        assert_eq!(error, "unterminated string at position 4 in '$['a'");
+        let error = parse("$[?(@.a !x)]").unwrap_err().to_string();
+        assert_eq!(error, "expected '!=' at position 9 in '$[?(@.a !x)]'");
+        assert!(parse("$..").unwrap_err().to_string().starts_with("expected a member name"));
+        assert!(parse("$[?(x)]").unwrap_err().to_string().starts_with("filters must start with '@'"));
+        assert!(parse("$[?(@.a ==)]").unwrap_err().to_string().starts_with("expected a value"));
+    }
+}
@@ -1 +1,10 @@
-pub mod http; 
\ No newline at end of file
+pub mod http; 
+pub mod jsonpath;
+pub mod format;
+pub mod timing;
+pub mod proxy;
+pub mod sse;
+pub mod graphql;
+pub mod openapi;
+pub mod har;
+pub mod http_file;
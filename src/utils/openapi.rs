@@ -0,0 +1,831 @@
+use std::collections::{BTreeMap, HashMap};
+use std::path::Path;
+use anyhow::{anyhow, Context, Result};
+use serde_json::{json, Map, Value};
+use crate::models::collection::{
+    next_request_id, Collection, CollectionInfo, CollectionItem, Folder, OpenApiLink, Request, RequestOptions, SavedRequest,
+};
+use crate::utils::http::mime_type;
+
+const METHODS: &[&str] = &["get", "put", "post", "delete", "options", "head", "patch", "trace"];
+
+// How many `$ref`s deep examples and references are followed
+const MAX_DEPTH: usize = 8;
+
+pub const BASE_URL_VARIABLE: &str = "baseUrl";
+
+// Reads an OpenAPI 3 or Swagger 2 document, YAML or JSON.
+pub fn import_file(path: &Path) -> Result<Collection> {
+    let root = read_spec(path)?;
+    let spec_path = path.canonicalize().unwrap_or_else(|_| path.to_path_buf());
+    import(&root, &spec_path.to_string_lossy())
+}
+
+// One folder per tag and one request per operation. URLs start with
+// `{{baseUrl}}` and path parameters become `{{name}}` variables of the
+// collection. Requests are linked to their operation in `spec_path`.
+fn import(root: &Value, spec_path: &str) -> Result<Collection> {
+    let spec = Spec::new(root)?;
+
+    let info = root.get("info").unwrap_or(&Value::Null);
+    let mut variables = BTreeMap::from([(BASE_URL_VARIABLE.to_string(), spec.base_url())]);
+    let mut top_level = Vec::new();
+    let mut folders: Vec<Folder> = Vec::new();
+
+    for (path, path_item) in root.get("paths").and_then(Value::as_object).into_iter().flatten() {
+        let path_item = spec.resolve(path_item);
+        for method in METHODS {
+            let Some(operation) = path_item.get(*method) else { continue };
+            let mut request = spec.request(path, method, path_item, operation, &mut variables);
+            request.openapi = Some(OpenApiLink {
+                spec: spec_path.to_string(),
+                method: method.to_string(),
+                path: path.clone(),
+            });
+            let item = CollectionItem::Request(Box::new(request));
+            match operation.get("tags").and_then(|tags| tags.get(0)).and_then(Value::as_str) {
+                Some(tag) => match folders.iter_mut().find(|folder| folder.name == tag) {
+                    Some(folder) => folder.item.push(item),
+                    None => folders.push(Folder { name: tag.to_string(), item: vec![item] }),
+                },
+                None => top_level.push(item),
+            }
+        }
+    }
+
+    let version = root.get("openapi").or(root.get("swagger")).and_then(Value::as_str).unwrap_or_default();
+    let mut requests: Vec<CollectionItem> = folders.into_iter().map(CollectionItem::Folder).collect();
+    requests.extend(top_level);
+    Ok(Collection {
+        info: CollectionInfo {
+            name: str_field(info, "title").unwrap_or("Imported API").to_string(),
+            description: str_field(info, "description")
+                .map(str::to_string)
+                .unwrap_or_else(|| format!("Imported from OpenAPI {}", version)),
+            created_at: chrono::Utc::now(),
+        },
+        requests,
+        variables,
+        source: None,
+    })
+}
+
+pub fn request_count(items: &[CollectionItem]) -> usize {
+    items.iter()
+        .map(|item| match item {
+            CollectionItem::Folder(folder) => request_count(&folder.item),
+            _ => 1,
+        })
+        .sum()
+}
+
+fn read_spec(path: &Path) -> Result<Value> {
+    let text = std::fs::read_to_string(path)
+        .with_context(|| format!("could not read {}", path.display()))?;
+    parse(&text)
+}
+
+fn parse(text: &str) -> Result<Value> {
+    if let Ok(value) = serde_json::from_str(text) {
+        return Ok(value);
+    }
+    let yaml: serde_yaml::Value = serde_yaml::from_str(text).context("not a valid JSON or YAML document")?;
+    Ok(yaml_to_json(yaml))
+}
+
+// YAML allows non-string keys, like the status codes under `responses`
+fn yaml_to_json(value: serde_yaml::Value) -> Value {
+    match value {
+        serde_yaml::Value::Null => Value::Null,
+        serde_yaml::Value::Bool(b) => Value::Bool(b),
+        serde_yaml::Value::Number(n) => serde_json::to_value(n).unwrap_or(Value::Null),
+        serde_yaml::Value::String(s) => Value::String(s),
+        serde_yaml::Value::Sequence(items) => Value::Array(items.into_iter().map(yaml_to_json).collect()),
+        serde_yaml::Value::Mapping(map) => Value::Object(map.into_iter()
+            .map(|(key, value)| {
+                let key = match yaml_to_json(key) {
+                    Value::String(s) => s,
+                    other => other.to_string(),
+                };
+                (key, yaml_to_json(value))
+            })
+            .collect()),
+        serde_yaml::Value::Tagged(tagged) => yaml_to_json(tagged.value),
+    }
+}
+
+struct Spec<'a> {
+    root: &'a Value,
+    swagger2: bool,
+}
+
+impl<'a> Spec<'a> {
+    fn new(root: &'a Value) -> Result<Self> {
+        let swagger2 = root.get("swagger").and_then(Value::as_str).is_some_and(|v| v.starts_with('2'));
+        let openapi3 = root.get("openapi").and_then(Value::as_str).is_some_and(|v| v.starts_with('3'));
+        if !swagger2 && !openapi3 {
+            return Err(anyhow!("not an OpenAPI 3 or Swagger 2 document"));
+        }
+        Ok(Spec { root, swagger2 })
+    }
+
+    // Follows local `$ref`s; references to other files resolve to null.
+    fn resolve(&self, mut value: &'a Value) -> &'a Value {
+        for _ in 0..MAX_DEPTH {
+            let Some(reference) = value.get("$ref").and_then(Value::as_str) else { break };
+            value = reference.strip_prefix('#')
+                .and_then(|pointer| self.root.pointer(pointer))
+                .unwrap_or(&Value::Null);
+        }
+        value
+    }
+
+    fn base_url(&self) -> String {
+        let url = if self.swagger2 {
+            let scheme = self.root.get("schemes").and_then(|s| s.get(0)).and_then(Value::as_str).unwrap_or("https");
+            let host = str_field(self.root, "host").unwrap_or("localhost");
+            format!("{}://{}{}", scheme, host, str_field(self.root, "basePath").unwrap_or_default())
+        } else {
+            match self.root.get("servers").and_then(|s| s.get(0)) {
+                Some(server) => {
+                    // Server variables take their defaults
+                    let mut url = str_field(server, "url").unwrap_or_default().to_string();
+                    for (name, variable) in server.get("variables").and_then(Value::as_object).into_iter().flatten() {
+                        if let Some(default) = variable.get("default") {
+                            url = url.replace(&format!("{{{}}}", name), &scalar(default));
+                        }
+                    }
+                    url
+                }
+                None => "http://localhost".to_string(),
+            }
+        };
+        url.trim_end_matches('/').to_string()
+    }
+
+    fn request(&self, path: &str, method: &str, path_item: &'a Value, operation: &'a Value,
+               variables: &mut BTreeMap<String, String>) -> SavedRequest {
+        // Operation parameters override the path's by name and location
+        let mut parameters: Vec<&Value> = Vec::new();
+        for list in [path_item.get("parameters"), operation.get("parameters")] {
+            for parameter in list.and_then(Value::as_array).into_iter().flatten() {
+                let parameter = self.resolve(parameter);
+                parameters.retain(|p| p.get("name") != parameter.get("name") || p.get("in") != parameter.get("in"));
+                parameters.push(parameter);
+            }
+        }
+
+        let mut url = format!("{{{{{}}}}}{}", BASE_URL_VARIABLE, path.replace('{', "{{").replace('}', "}}"));
+        let mut query = Vec::new();
+        let mut headers = HashMap::new();
+        let mut form = Vec::new();
+        let mut body = None;
+        for parameter in parameters {
+            let Some(name) = str_field(parameter, "name") else { continue };
+            let required = parameter.get("required").and_then(Value::as_bool).unwrap_or(false);
+            let example = self.parameter_example(parameter);
+            match str_field(parameter, "in") {
+                Some("path") => {
+                    let value = variables.entry(name.to_string()).or_default();
+                    if value.is_empty() {
+                        *value = example.unwrap_or_default();
+                    }
+                }
+                Some("query") if required => {
+                    query.push(format!("{}={}", name, example.unwrap_or_else(|| format!("{{{{{}}}}}", name))));
+                }
+                Some("header") if required => {
+                    headers.insert(name.to_string(), example.unwrap_or_else(|| format!("{{{{{}}}}}", name)));
+                }
+                Some("body") => {
+                    let schema = parameter.get("schema").unwrap_or(&Value::Null);
+                    body = self.example(schema, &mut Vec::new())
+                        .filter(|example| !example.is_null())
+                        .map(|example| pretty(&example));
+                    let content_type = self.swagger_consumes(operation).unwrap_or("application/json");
+                    headers.insert("Content-Type".to_string(), content_type.to_string());
+                }
+                Some("formData") => form.push(format!("{}={}", name, example.unwrap_or_default())),
+                _ => {}
+            }
+        }
+        if !query.is_empty() {
+            url = format!("{}?{}", url, query.join("&"));
+        }
+        if body.is_none() && !form.is_empty() {
+            body = Some(form.join("&"));
+            headers.insert("Content-Type".to_string(), "application/x-www-form-urlencoded".to_string());
+        }
+        if let Some((content_type, example)) = operation.get("requestBody").and_then(|b| self.request_body(b)) {
+            headers.insert("Content-Type".to_string(), content_type);
+            body = Some(example);
+        }
+
+        let name = str_field(operation, "summary")
+            .or(str_field(operation, "operationId"))
+            .map(|name| name.trim().to_string())
+            .filter(|name| !name.is_empty())
+            .unwrap_or_else(|| path.to_string());
+        SavedRequest {
+            id: next_request_id(),
+            name,
+            request: Request {
+                method: method.to_uppercase(),
+                url,
+                headers,
+                body,
+                options: RequestOptions::default(),
+                graphql: None,
+                grpc: None,
+            },
+            response_filter: None,
+            openapi: None,
+        }
+    }
+
+    // Content type and example of an OpenAPI 3 request body, preferring JSON
+    fn request_body(&self, body: &Value) -> Option<(String, String)> {
+        let content = self.resolve(body).get("content")?.as_object()?;
+        let (content_type, media) = content.iter()
+            .find(|(content_type, _)| content_type.contains("json"))
+            .or_else(|| content.iter().next())?;
+        let example = media.get("example").cloned()
+            .or_else(|| media.get("examples")
+                .and_then(Value::as_object)
+                .and_then(|examples| examples.values().next())
+                .and_then(|example| self.resolve(example).get("value").cloned()))
+            .or_else(|| self.example(media.get("schema").unwrap_or(&Value::Null), &mut Vec::new()))
+            .unwrap_or_default();
+        let text = match (&example, content_type.as_str()) {
+            (Value::String(s), _) => s.clone(),
+            (Value::Object(fields), "application/x-www-form-urlencoded") => fields.iter()
+                .map(|(key, value)| format!("{}={}", key, scalar(value)))
+                .collect::<Vec<_>>()
+                .join("&"),
+            (Value::Null, _) => String::new(),
+            _ => pretty(&example),
+        };
+        Some((content_type.clone(), text))
+    }
+
+    fn swagger_consumes(&self, operation: &'a Value) -> Option<&'a str> {
+        operation.get("consumes").or(self.root.get("consumes"))
+            .and_then(|c| c.get(0))
+            .and_then(Value::as_str)
+    }
+
+    fn parameter_example(&self, parameter: &Value) -> Option<String> {
+        let example = parameter.get("example")
+            .or_else(|| parameter.get("examples")
+                .and_then(Value::as_object)
+                .and_then(|examples| examples.values().next())
+                .and_then(|example| self.resolve(example).get("value")))
+            .cloned()
+            .or_else(|| {
+                // Swagger 2 puts the schema keywords on the parameter itself
+                let schema = parameter.get("schema").unwrap_or(parameter);
+                let schema = self.resolve(schema);
+                schema.get("example").or(schema.get("default"))
+                    .or_else(|| schema.get("enum").and_then(|e| e.get(0)))
+                    .cloned()
+            })?;
+        Some(scalar(&example))
+    }
+
+    // Example value for a schema: its `example` or `default` when given,
+    // otherwise built from the types. None for a schema that refers back to
+    // one it's nested in; those properties are left out.
+    fn example(&self, schema: &'a Value, refs: &mut Vec<&'a str>) -> Option<Value> {
+        let reference = schema.get("$ref").and_then(Value::as_str);
+        if let Some(reference) = reference {
+            if refs.contains(&reference) || refs.len() >= MAX_DEPTH {
+                return None;
+            }
+            refs.push(reference);
+        }
+        let example = self.schema_example(self.resolve(schema), refs);
+        if reference.is_some() {
+            refs.pop();
+        }
+        example
+    }
+
+    fn schema_example(&self, schema: &'a Value, refs: &mut Vec<&'a str>) -> Option<Value> {
+        if let Some(example) = schema.get("example").or(schema.get("default")) {
+            return Some(example.clone());
+        }
+        if let Some(first) = schema.get("enum").and_then(|e| e.get(0)) {
+            return Some(first.clone());
+        }
+        if let Some(parts) = schema.get("allOf").and_then(Value::as_array) {
+            let mut merged = Map::new();
+            for part in parts {
+                match self.example(part, refs)? {
+                    Value::Object(fields) => merged.extend(fields),
+                    other if merged.is_empty() => return Some(other),
+                    _ => {}
+                }
+            }
+            return Some(Value::Object(merged));
+        }
+        if let Some(first) = schema.get("oneOf").or(schema.get("anyOf")).and_then(|s| s.get(0)) {
+            return self.example(first, refs);
+        }
+
+        // OpenAPI 3.1 allows a list of types, like ["string", "null"]
+        let schema_type = match schema.get("type") {
+            Some(Value::Array(types)) => types.iter().filter_map(Value::as_str).find(|t| *t != "null"),
+            Some(t) => t.as_str(),
+            None if schema.get("properties").is_some() => Some("object"),
+            None => None,
+        };
+        Some(match schema_type {
+            Some("object") => {
+                let mut fields = Map::new();
+                for (name, property) in schema.get("properties").and_then(Value::as_object).into_iter().flatten() {
+                    // Read-only properties aren't sent in requests
+                    if self.resolve(property).get("readOnly").and_then(Value::as_bool).unwrap_or(false) {
+                        continue;
+                    }
+                    if let Some(value) = self.example(property, refs) {
+                        fields.insert(name.clone(), value);
+                    }
+                }
+                Value::Object(fields)
+            }
+            Some("array") => match schema.get("items") {
+                Some(items) => Value::Array(self.example(items, refs).into_iter().collect()),
+                None => json!([]),
+            },
+            Some("string") => json!(match str_field(schema, "format") {
+                Some("date-time") => "2024-01-01T00:00:00Z",
+                Some("date") => "2024-01-01",
+                Some("email") => "user@example.com",
+                Some("uuid") => "00000000-0000-0000-0000-000000000000",
+                Some("uri") | Some("url") => "https://example.com",
+                _ => "string",
+            }),
+            Some("integer") | Some("number") => json!(0),
+            Some("boolean") => json!(false),
+            _ => Value::Null,
+        })
+    }
+}
+
+// Outcome of checking a response against the operation its request was
+// imported from
+#[derive(Debug, Clone)]
+pub struct ContractCheck {
+    // e.g. "GET /pets/{petId} → 200"
+    pub operation: String,
+    pub violations: Vec<String>,
+}
+
+// Checks the status code, content type and JSON body of a response against
+// the operation's documented responses. The spec is read again every time so
+// edits to it are picked up.
+pub fn check_response(link: &OpenApiLink, status: u16, content_type: &str, body: &[u8]) -> Result<ContractCheck> {
+    let root = read_spec(Path::new(&link.spec))?;
+    let spec = Spec::new(&root)?;
+    let path_item = root.get("paths").and_then(|paths| paths.get(&link.path))
+        .map(|item| spec.resolve(item))
+        .ok_or_else(|| anyhow!("{} is no longer in the spec", link.path))?;
+    let operation = path_item.get(&link.method)
+        .ok_or_else(|| anyhow!("{} {} is no longer in the spec", link.method.to_uppercase(), link.path))?;
+    let mut check = ContractCheck {
+        operation: format!("{} {} → {}", link.method.to_uppercase(), link.path, status),
+        violations: Vec::new(),
+    };
+
+    // An exact code, then a range like `4XX`, then `default`
+    let responses = operation.get("responses").and_then(Value::as_object);
+    let documented = responses.and_then(|responses| {
+        let code = status.to_string();
+        let range = format!("{}XX", status / 100);
+        responses.get(&code)
+            .or_else(|| responses.iter().find(|(key, _)| key.eq_ignore_ascii_case(&range)).map(|(_, r)| r))
+            .or_else(|| responses.get("default"))
+    });
+    let Some(response) = documented.map(|r| spec.resolve(r)) else {
+        let codes: Vec<&str> = responses.into_iter().flatten().map(|(code, _)| code.as_str()).collect();
+        check.violations.push(format!("Undocumented status {} (documented: {})", status, codes.join(", ")));
+        return Ok(check);
+    };
+
+    let media_type = mime_type(content_type);
+    let schema = if spec.swagger2 {
+        let produces: Vec<&str> = operation.get("produces").or(root.get("produces"))
+            .and_then(Value::as_array)
+            .into_iter()
+            .flatten()
+            .filter_map(Value::as_str)
+            .collect();
+        if !body.is_empty() && !produces.is_empty() && !produces.iter().any(|p| media_type_matches(p, &media_type)) {
+            check.violations.push(format!("Undocumented content type {:?} (documented: {})", media_type, produces.join(", ")));
+            return Ok(check);
+        }
+        response.get("schema")
+    } else {
+        match response.get("content").and_then(Value::as_object).filter(|content| !content.is_empty()) {
+            Some(content) => match content.iter().find(|(documented, _)| media_type_matches(documented, &media_type)) {
+                Some((_, media)) => media.get("schema"),
+                None => {
+                    let documented: Vec<&str> = content.keys().map(String::as_str).collect();
+                    check.violations.push(format!("Undocumented content type {:?} (documented: {})", media_type, documented.join(", ")));
+                    return Ok(check);
+                }
+            },
+            None if !body.is_empty() => {
+                check.violations.push("Response has a body but none is documented".to_string());
+                return Ok(check);
+            }
+            None => None,
+        }
+    };
+
+    let is_json = media_type == "application/json" || media_type.ends_with("+json") || media_type.is_empty();
+    if let (Some(schema), true) = (schema, is_json) {
+        match serde_json::from_slice::<Value>(body) {
+            Ok(value) => spec.check(schema, &value, "$", &mut Vec::new(), &mut check.violations),
+            Err(e) => check.violations.push(format!("Body is not valid JSON: {}", e)),
+        }
+    }
+    Ok(check)
+}
+
+// Whether the path of `url` ends with the segments of a path template such
+// as `/pets/{petId}`; the base URL may add segments in front.
+pub fn url_matches_path(url: &str, template: &str) -> bool {
+    let url = url.trim();
+    let url = url.split(['?', '#']).next().unwrap_or_default();
+    let url_path = match url.split_once("://") {
+        Some((_, rest)) => rest.find('/').map(|i| &rest[i..]).unwrap_or_default(),
+        None => url,
+    };
+    let segments: Vec<&str> = url_path.split('/').filter(|s| !s.is_empty()).collect();
+    let expected: Vec<&str> = template.split('/').filter(|s| !s.is_empty()).collect();
+    if segments.len() < expected.len() {
+        return false;
+    }
+    segments[segments.len() - expected.len()..].iter().zip(&expected).all(|(segment, expected)| {
+        (expected.starts_with('{') && expected.ends_with('}')) || segment == expected
+    })
+}
+
+// `application/*` and `*/*` match any subtype and type
+fn media_type_matches(documented: &str, actual: &str) -> bool {
+    let documented = mime_type(documented);
+    match documented.split_once('/') {
+        Some(("*", "*")) => true,
+        Some((kind, "*")) => actual.split('/').next() == Some(kind),
+        _ => documented == actual,
+    }
+}
+
+impl<'a> Spec<'a> {
+    // Appends to `violations` where `value` doesn't match `schema`, with the
+    // JSONPath of the offending value.
+    fn check(&self, schema: &'a Value, value: &Value, path: &str, refs: &mut Vec<&'a str>, violations: &mut Vec<String>) {
+        let reference = schema.get("$ref").and_then(Value::as_str);
+        if let Some(reference) = reference {
+            // A value nested as deep as the schema recursion is checked no further
+            if refs.len() >= MAX_DEPTH * 4 {
+                return;
+            }
+            refs.push(reference);
+        }
+        self.check_resolved(self.resolve(schema), value, path, refs, violations);
+        if reference.is_some() {
+            refs.pop();
+        }
+    }
+
+    fn check_resolved(&self, schema: &'a Value, value: &Value, path: &str, refs: &mut Vec<&'a str>, violations: &mut Vec<String>) {
+        let types: Vec<&str> = match schema.get("type") {
+            Some(Value::Array(types)) => types.iter().filter_map(Value::as_str).collect(),
+            Some(Value::String(t)) => vec![t.as_str()],
+            _ if schema.get("properties").is_some() => vec!["object"],
+            _ => Vec::new(),
+        };
+        let nullable = types.contains(&"null")
+            || schema.get("nullable").and_then(Value::as_bool).unwrap_or(false)
+            || schema.get("x-nullable").and_then(Value::as_bool).unwrap_or(false);
+        if value.is_null() && nullable {
+            return;
+        }
+
+        for part in schema.get("allOf").and_then(Value::as_array).into_iter().flatten() {
+            self.check(part, value, path, refs, violations);
+        }
+        for keyword in ["oneOf", "anyOf"] {
+            let Some(options) = schema.get(keyword).and_then(Value::as_array) else { continue };
+            let matches = options.iter()
+                .filter(|option| {
+                    let mut option_violations = Vec::new();
+                    self.check(option, value, path, refs, &mut option_violations);
+                    option_violations.is_empty()
+                })
+                .count();
+            if matches == 0 {
+                violations.push(format!("{}: matches none of the {} schemas", path, keyword));
+            } else if matches > 1 && keyword == "oneOf" {
+                violations.push(format!("{}: matches {} of the oneOf schemas instead of exactly one", path, matches));
+            }
+        }
+
+        if let Some(allowed) = schema.get("enum").and_then(Value::as_array) {
+            if !allowed.contains(value) {
+                let allowed: Vec<String> = allowed.iter().map(Value::to_string).collect();
+                violations.push(format!("{}: {} is not one of {}", path, value, allowed.join(", ")));
+            }
+        }
+
+        if !types.is_empty() && !types.iter().any(|t| has_type(value, t)) {
+            violations.push(format!("{}: expected {}, got {}", path, types.join(" or "), type_name(value)));
+            return;
+        }
+        match value {
+            Value::Object(fields) => {
+                for required in schema.get("required").and_then(Value::as_array).into_iter().flatten().filter_map(Value::as_str) {
+                    if !fields.contains_key(required) {
+                        violations.push(format!("{}: missing required field {:?}", path, required));
+                    }
+                }
+                let properties = schema.get("properties").and_then(Value::as_object);
+                let additional = schema.get("additionalProperties");
+                for (name, field) in fields {
+                    let field_path = format!("{}.{}", path, name);
+                    match (properties.and_then(|p| p.get(name)), additional) {
+                        (Some(property), _) => self.check(property, field, &field_path, refs, violations),
+                        (None, Some(Value::Bool(false))) => violations.push(format!("{}: field not in the schema", field_path)),
+                        (None, Some(additional)) if additional.is_object() => {
+                            self.check(additional, field, &field_path, refs, violations)
+                        }
+                        _ => {}
+                    }
+                }
+            }
+            Value::Array(items) => {
+                if let Some(item_schema) = schema.get("items") {
+                    for (i, item) in items.iter().enumerate() {
+                        self.check(item_schema, item, &format!("{}[{}]", path, i), refs, violations);
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+fn has_type(value: &Value, schema_type: &str) -> bool {
+    match schema_type {
+        "object" => value.is_object(),
+        "array" => value.is_array(),
+        "string" => value.is_string(),
+        // JSON doesn't tell 1.0 from 1
+        "integer" => value.as_f64().is_some_and(|n| n.fract() == 0.0),
+        "number" => value.is_number(),
+        "boolean" => value.is_boolean(),
+        "null" => value.is_null(),
+        _ => true,
+    }
+}
+
+fn type_name(value: &Value) -> &'static str {
+    match value {
+        Value::Null => "null",
+        Value::Bool(_) => "boolean",
+        Value::Number(n) if n.is_f64() => "number",
+        Value::Number(_) => "integer",
+        Value::String(_) => "string",
+        Value::Array(_) => "array",
+        Value::Object(_) => "object",
+    }
+}
+
+fn str_field<'a>(value: &'a Value, key: &str) -> Option<&'a str> {
+    value.get(key).and_then(Value::as_str)
+}
+
+// Value as it goes into a URL or header: strings without quotes
+fn scalar(value: &Value) -> String {
+    match value {
+        Value::String(s) => s.clone(),
+        other => other.to_string(),
+    }
+}
+
+fn pretty(value: &Value) -> String {
+    serde_json::to_string_pretty(value).unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const PETS: &str = r#"
+openapi: 3.0.3
+info:
+  title: Pets
+servers:
+  - url: https://{env}.example.com/v1/
+    variables:
+      env:
+        default: api
+paths:
+  /pets/{petId}:
+    parameters:
+      - { name: petId, in: path, required: true, schema: { type: integer, example: 42 } }
+    get:
+      tags: [pets]
+      summary: Get a pet
+      parameters:
+        - { name: verbose, in: query, required: true, schema: { type: boolean, default: false } }
+        - { name: X-Trace, in: header, required: true, schema: { type: string } }
+        - { name: page, in: query, schema: { type: integer } }
+  /pets:
+    post:
+      tags: [pets]
+      operationId: createPet
+      requestBody:
+        content:
+          application/json:
+            examples:
+              rex: { value: { name: Rex, tag: dog } }
+    put:
+      requestBody:
+        content:
+          application/x-www-form-urlencoded:
+            schema:
+              type: object
+              properties:
+                name: { type: string }
+                age: { type: integer }
+"#;
+
+    fn request(item: &CollectionItem) -> &SavedRequest {
+        match item {
+            CollectionItem::Request(request) => request,
+            _ => panic!("not a request"),
+        }
+    }
+
+    #[test]
+    fn imports_operations() {
+        let collection = import(&parse(PETS).unwrap(), "/specs/pets.yaml").unwrap();
+        assert_eq!(collection.info.name, "Pets");
+        assert_eq!(collection.info.description, "Imported from OpenAPI 3.0.3");
+        // The first server with its variables filled in
+        assert_eq!(collection.variables["baseUrl"], "https://api.example.com/v1");
+        assert_eq!(collection.variables["petId"], "42");
+        assert_eq!(request_count(&collection.requests), 3);
+
+        // Tagged operations go in a folder, the rest after it
+        let CollectionItem::Folder(pets) = &collection.requests[0] else { panic!("not a folder") };
+        assert_eq!(pets.name, "pets");
+        let create = request(&pets.item[0]);
+        assert_eq!((create.name.as_str(), create.request.method.as_str()), ("createPet", "POST"));
+        assert_eq!(create.request.url, "{{baseUrl}}/pets");
+        assert_eq!(create.request.headers["Content-Type"], "application/json");
+        let body: Value = serde_json::from_str(create.request.body.as_deref().unwrap()).unwrap();
+        assert_eq!(body, json!({ "name": "Rex", "tag": "dog" }));
+
+        let get = request(&pets.item[1]);
+        assert_eq!(get.name, "Get a pet");
+        assert_eq!(get.request.url, "{{baseUrl}}/pets/{{petId}}?verbose=false");
+        assert_eq!(get.request.headers["X-Trace"], "{{X-Trace}}");
+        assert_eq!(get.request.body, None);
+        assert_eq!(get.openapi, Some(OpenApiLink {
+            spec: "/specs/pets.yaml".to_string(),
+            method: "get".to_string(),
+            path: "/pets/{petId}".to_string(),
+        }));
+
+        // A form body built from the schema
+        let put = request(&collection.requests[1]);
+        assert_eq!((put.name.as_str(), put.request.method.as_str()), ("/pets", "PUT"));
+        assert_eq!(put.request.headers["Content-Type"], "application/x-www-form-urlencoded");
+        assert_eq!(put.request.body.as_deref(), Some("age=0&name=string"));
+    }
+
+    #[test]
+    fn servers_default_to_localhost() {
+        let spec = json!({ "openapi": "3.1.0", "info": {}, "paths": {} });
+        let collection = import(&spec, "spec.json").unwrap();
+        assert_eq!(collection.variables["baseUrl"], "http://localhost");
+        assert_eq!(collection.info.name, "Imported API");
+        let swagger = json!({ "swagger": "2.0", "host": "api.example.com", "basePath": "/v2", "schemes": ["http"] });
+        assert_eq!(import(&swagger, "spec.json").unwrap().variables["baseUrl"], "http://api.example.com/v2");
+        assert!(import(&json!({ "info": {} }), "spec.json").is_err());
+    }
+
+    // Violations of `value` against `schema`, with `#/components/schemas/...`
+    // refs resolved against a spec holding `schemas`
+    fn violations(schemas: Value, schema: Value, value: Value) -> Vec<String> {
+        let root = json!({ "openapi": "3.0.0", "components": { "schemas": schemas } });
+        let spec = Spec::new(&root).unwrap();
+        let mut violations = Vec::new();
+        spec.check(&schema, &value, "$", &mut Vec::new(), &mut violations);
+        violations
+    }
+
+    fn check(schema: Value, value: Value) -> Vec<String> {
+        violations(json!({}), schema, value)
+    }
+
+    #[test]
+    fn type_mismatches() {
+        let pet = json!({ "type": "object", "properties": {
+            "id": { "type": "integer" },
+            "name": { "type": "string" },
+            "tags": { "type": "array", "items": { "type": "string" } },
+        } });
+        assert_eq!(check(pet.clone(), json!({ "id": 1, "name": "Rex", "tags": ["dog"] })), Vec::<String>::new());
+        assert_eq!(check(pet.clone(), json!({ "id": "1", "tags": ["dog", 2] })), vec![
+            "$.id: expected integer, got string",
+            "$.tags[1]: expected string, got integer",
+        ]);
+        assert_eq!(check(pet, json!([])), vec!["$: expected object, got array"]);
+    }
+
+    #[test]
+    fn integers_may_have_a_zero_fraction() {
+        let integer = json!({ "type": "integer" });
+        assert!(check(integer.clone(), json!(1.0)).is_empty());
+        assert!(check(integer.clone(), json!(-3)).is_empty());
+        assert_eq!(check(integer, json!(1.5)), vec!["$: expected integer, got number"]);
+        assert!(check(json!({ "type": "number" }), json!(7)).is_empty());
+    }
+
+    #[test]
+    fn missing_required_fields() {
+        let schemas = json!({ "Pet": { "type": "object", "required": ["id", "name"], "properties": {
+            "id": { "type": "integer" },
+            "owner": { "$ref": "#/components/schemas/Owner" },
+        } }, "Owner": { "required": ["email"] } });
+        let pet = json!({ "$ref": "#/components/schemas/Pet" });
+        assert_eq!(violations(schemas, pet, json!({ "id": 1, "owner": {} })), vec![
+            "$: missing required field \"name\"",
+            "$.owner: missing required field \"email\"",
+        ]);
+    }
+
+    #[test]
+    fn one_of_needs_exactly_one_match() {
+        let one_of = json!({ "oneOf": [
+            { "type": "object", "required": ["cat"] },
+            { "type": "object", "required": ["dog"] },
+        ] });
+        assert!(check(one_of.clone(), json!({ "cat": true })).is_empty());
+        assert_eq!(check(one_of.clone(), json!({ "cat": true, "dog": true })),
+                   vec!["$: matches 2 of the oneOf schemas instead of exactly one"]);
+        assert_eq!(check(one_of, json!({})), vec!["$: matches none of the oneOf schemas"]);
+        // anyOf is fine with several
+        let any_of = json!({ "anyOf": [{ "type": "integer" }, { "type": "number" }] });
+        assert!(check(any_of, json!(3)).is_empty());
+    }
+
+    #[test]
+    fn nullable_values() {
+        assert!(check(json!({ "type": "string", "nullable": true }), json!(null)).is_empty());
+        assert!(check(json!({ "type": ["string", "null"] }), json!(null)).is_empty());
+        assert!(check(json!({ "type": "string", "x-nullable": true }), json!(null)).is_empty());
+        assert_eq!(check(json!({ "type": "string" }), json!(null)), vec!["$: expected string, got null"]);
+        assert_eq!(check(json!({ "type": "string", "enum": ["a"], "nullable": true }), json!("b")),
+                   vec!["$: \"b\" is not one of \"a\""]);
+    }
+
+    #[test]
+    fn response_statuses() {
+        let path = std::env::temp_dir().join(format!("raquet-openapi-check-{}.yaml", std::process::id()));
+        std::fs::write(&path, r#"
+openapi: 3.0.0
+paths:
+  /pets/{petId}:
+    get:
+      responses:
+        "200":
+          content:
+            application/json:
+              schema: { type: object, required: [id] }
+        4XX:
+          description: Client error
+"#).unwrap();
+        let link = OpenApiLink {
+            spec: path.to_string_lossy().into_owned(),
+            method: "get".to_string(),
+            path: "/pets/{petId}".to_string(),
+        };
+        let violations = |status, content_type, body: &str| {
+            check_response(&link, status, content_type, body.as_bytes()).unwrap().violations
+        };
+        assert!(violations(200, "application/json; charset=utf-8", r#"{"id": 1}"#).is_empty());
+        assert_eq!(violations(200, "application/json", "{}"), vec!["$: missing required field \"id\""]);
+        assert_eq!(violations(200, "text/html", "<p>"), vec!["Undocumented content type \"text/html\" (documented: application/json)"]);
+        // A range without content
+        assert!(violations(404, "", "").is_empty());
+        assert_eq!(violations(404, "text/plain", "gone"), vec!["Response has a body but none is documented"]);
+        assert_eq!(violations(500, "", ""), vec!["Undocumented status 500 (documented: 200, 4XX)"]);
+        std::fs::remove_file(&path).unwrap();
+    }
+}
@@ -0,0 +1,215 @@
+use std::net::IpAddr;
+use anyhow::{anyhow, Context, Result};
+use reqwest::Url;
+use crate::data::ProxySettings;
+
+// Proxy routing resolved from the config and, unless disabled, the
+// HTTP_PROXY / HTTPS_PROXY / ALL_PROXY / NO_PROXY environment variables.
+// An explicit proxy in the config wins over the environment.
+#[derive(Debug, Clone, Default)]
+pub struct ProxyRoutes {
+    http: Option<Url>,
+    https: Option<Url>,
+    no_proxy: Vec<String>,
+}
+
+impl ProxyRoutes {
+    pub fn from_settings(settings: &ProxySettings) -> Result<Self> {
+        let mut no_proxy: Vec<String> = settings.no_proxy.iter()
+            .map(|entry| entry.trim().to_ascii_lowercase())
+            .filter(|entry| !entry.is_empty())
+            .collect();
+
+        let (http, https) = if !settings.url.trim().is_empty() {
+            let mut url = parse_proxy_url(&settings.url)?;
+            if !settings.username.is_empty() {
+                url.set_username(&settings.username)
+                    .map_err(|_| anyhow!("proxy URL can't carry credentials"))?;
+                url.set_password(Some(&settings.password))
+                    .map_err(|_| anyhow!("proxy URL can't carry credentials"))?;
+            }
+            (Some(url.clone()), Some(url))
+        } else if settings.use_env {
+            let all = env_var(&["ALL_PROXY", "all_proxy"]);
+            let http = env_var(&["HTTP_PROXY", "http_proxy"]).or_else(|| all.clone());
+            let https = env_var(&["HTTPS_PROXY", "https_proxy"]).or(all);
+            if let Some(list) = env_var(&["NO_PROXY", "no_proxy"]) {
+                no_proxy.extend(list.split(',').map(|e| e.trim().to_ascii_lowercase()).filter(|e| !e.is_empty()));
+            }
+            (
+                http.map(|u| parse_proxy_url(&u)).transpose()?,
+                https.map(|u| parse_proxy_url(&u)).transpose()?,
+            )
+        } else {
+            (None, None)
+        };
+
+        Ok(ProxyRoutes { http, https, no_proxy })
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.http.is_none() && self.https.is_none()
+    }
+
+    // The proxy a request to `target` goes through, if any.
+    pub fn proxy_for(&self, target: &Url) -> Option<Url> {
+        let proxy = match target.scheme() {
+            "https" | "wss" => self.https.as_ref(),
+            _ => self.http.as_ref(),
+        }?;
+        let host = target.host_str()?.trim_start_matches('[').trim_end_matches(']').to_ascii_lowercase();
+        let port = target.port_or_known_default();
+        if self.no_proxy.iter().any(|entry| no_proxy_matches(entry, &host, port)) {
+            return None;
+        }
+        Some(proxy.clone())
+    }
+}
+
+// `example.com` and `.example.com` match the domain and its subdomains,
+// `*` matches everything, and IP entries may be CIDR ranges. An entry with a
+// port (`example.com:8080`, `[::1]:8080`) only matches that port.
+fn no_proxy_matches(entry: &str, host: &str, port: Option<u16>) -> bool {
+    if entry == "*" {
+        return true;
+    }
+    let (entry, entry_port) = split_port(entry);
+    if entry_port.is_some() && entry_port != port {
+        return false;
+    }
+    if let Ok(ip) = host.parse::<IpAddr>() {
+        return match entry.split_once('/') {
+            Some((network, bits)) => match (network.parse::<IpAddr>(), bits.parse::<u32>()) {
+                (Ok(network), Ok(bits)) => in_cidr(ip, network, bits),
+                _ => false,
+            },
+            None => entry.parse::<IpAddr>().map(|e| e == ip).unwrap_or(false),
+        };
+    }
+    let domain = entry.trim_start_matches("*.").trim_start_matches('.');
+    host == domain || host.ends_with(&format!(".{}", domain))
+}
+
+// Bare IPv6 addresses have several colons and no port
+fn split_port(entry: &str) -> (&str, Option<u16>) {
+    if let Some(rest) = entry.strip_prefix('[') {
+        return match rest.split_once(']') {
+            Some((host, port)) => (host, port.strip_prefix(':').and_then(|p| p.parse().ok())),
+            None => (entry, None),
+        };
+    }
+    match entry.split_once(':') {
+        Some((host, port)) if !port.contains(':') => match port.parse() {
+            Ok(port) => (host, Some(port)),
+            Err(_) => (entry, None),
+        },
+        _ => (entry, None),
+    }
+}
+
+fn in_cidr(ip: IpAddr, network: IpAddr, bits: u32) -> bool {
+    match (ip, network) {
+        (IpAddr::V4(ip), IpAddr::V4(network)) if bits <= 32 => {
+            let mask = u32::MAX.checked_shl(32 - bits).unwrap_or(0);
+            u32::from(ip) & mask == u32::from(network) & mask
+        }
+        (IpAddr::V6(ip), IpAddr::V6(network)) if bits <= 128 => {
+            let mask = u128::MAX.checked_shl(128 - bits).unwrap_or(0);
+            u128::from(ip) & mask == u128::from(network) & mask
+        }
+        _ => false,
+    }
+}
+
+// Accepts http://, https://, socks5:// and socks5h:// proxies; a bare
+// `host:port` is taken as an HTTP proxy.
+fn parse_proxy_url(value: &str) -> Result<Url> {
+    let value = value.trim();
+    let value = if value.contains("://") { value.to_string() } else { format!("http://{}", value) };
+    let url = Url::parse(&value).with_context(|| format!("invalid proxy URL: {}", value))?;
+    match url.scheme() {
+        "http" | "https" | "socks5" | "socks5h" => Ok(url),
+        other => Err(anyhow!("unsupported proxy scheme: {}", other)),
+    }
+}
+
+fn env_var(names: &[&str]) -> Option<String> {
+    names.iter()
+        .find_map(|name| std::env::var(name).ok())
+        .filter(|value| !value.trim().is_empty())
+}
+
+// Proxy URL without credentials, for display
+pub fn display_proxy(proxy: &Url) -> String {
+    let mut proxy = proxy.clone();
+    let _ = proxy.set_username("");
+    let _ = proxy.set_password(None);
+    proxy.to_string().trim_end_matches('/').to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn routes(no_proxy: &[&str]) -> ProxyRoutes {
+        let settings = ProxySettings {
+            url: "http://proxy.local:3128".to_string(),
+            no_proxy: no_proxy.iter().map(|e| e.to_string()).collect(),
+            ..ProxySettings::default()
+        };
+        ProxyRoutes::from_settings(&settings).unwrap()
+    }
+
+    fn proxied(routes: &ProxyRoutes, url: &str) -> bool {
+        routes.proxy_for(&Url::parse(url).unwrap()).is_some()
+    }
+
+    #[test]
+    fn cidr_ranges() {
+        let ranges = routes(&["10.0.0.0/8", "fd00::/8", "192.168.1.5"]);
+        assert!(!proxied(&ranges, "http://10.20.30.40/"));
+        assert!(proxied(&ranges, "http://11.0.0.1/"));
+        assert!(!proxied(&ranges, "http://[fd12::1]/"));
+        assert!(proxied(&ranges, "http://[fe80::1]/"));
+        assert!(!proxied(&ranges, "http://192.168.1.5/"));
+        assert!(proxied(&ranges, "http://192.168.1.6/"));
+        // CIDR entries don't apply to names
+        assert!(proxied(&ranges, "http://ten.example/"));
+    }
+
+    #[test]
+    fn domains_match_their_subdomains() {
+        let domains = routes(&[".internal.example", "corp.example"]);
+        assert!(!proxied(&domains, "https://internal.example/"));
+        assert!(!proxied(&domains, "https://api.internal.example/"));
+        assert!(!proxied(&domains, "https://corp.example/"));
+        assert!(!proxied(&domains, "https://a.b.corp.example/"));
+        assert!(proxied(&domains, "https://notcorp.example/"));
+        assert!(proxied(&domains, "https://example/"));
+    }
+
+    #[test]
+    fn star_matches_everything() {
+        let all = routes(&["*"]);
+        assert!(!proxied(&all, "http://example.com/"));
+        assert!(!proxied(&all, "https://10.0.0.1:8443/"));
+    }
+
+    #[test]
+    fn entries_with_a_port_match_only_that_port() {
+        let with_ports = routes(&["example.com:8080", "127.0.0.1:3000", "[::1]:9000", "::2"]);
+        assert!(!proxied(&with_ports, "http://example.com:8080/"));
+        assert!(!proxied(&with_ports, "http://api.example.com:8080/"));
+        assert!(proxied(&with_ports, "http://example.com/"));
+        assert!(!proxied(&with_ports, "http://127.0.0.1:3000/"));
+        assert!(proxied(&with_ports, "http://127.0.0.1:3001/"));
+        assert!(!proxied(&with_ports, "http://[::1]:9000/"));
+        assert!(proxied(&with_ports, "http://[::1]/"));
+        // Bare IPv6 addresses have no port
+        assert!(!proxied(&with_ports, "http://[::2]:1234/"));
+        // Default ports count
+        let https_port = routes(&["example.com:443"]);
+        assert!(!proxied(&https_port, "https://example.com/"));
+        assert!(proxied(&https_port, "http://example.com/"));
+    }
+}
@@ -0,0 +1,225 @@
+use chrono::Utc;
+use crate::models::sse::SseEvent;
+
+const BOM: &[u8] = b"\xEF\xBB\xBF";
+
+// Incremental `text/event-stream` parser following the WHATWG EventSource
+// rules. Chunks may split lines, UTF-8 sequences and CRLF pairs anywhere.
+#[derive(Debug, Default)]
+pub struct SseParser {
+    line: Vec<u8>,
+    // The previous chunk ended in '\r'; a leading '\n' belongs to it
+    skip_lf: bool,
+    // The stream may start with a byte order mark, which isn't part of the
+    // first line. Set once it has been skipped or ruled out
+    past_bom: bool,
+    event: String,
+    data: String,
+    has_data: bool,
+    // Persists across events, sent back as `Last-Event-ID` on reconnect
+    pub last_event_id: Option<String>,
+    // Reconnection delay requested by the server
+    pub retry_ms: Option<u64>,
+}
+
+impl SseParser {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    // Starts over for a new connection. A half-received line or event of the
+    // old one is dropped; only the last event ID and retry delay carry over.
+    pub fn reset(&mut self) {
+        *self = SseParser {
+            last_event_id: self.last_event_id.take(),
+            retry_ms: self.retry_ms,
+            ..SseParser::default()
+        };
+    }
+
+    pub fn feed(&mut self, chunk: &[u8]) -> Vec<SseEvent> {
+        let mut events = Vec::new();
+        for &byte in chunk {
+            if std::mem::take(&mut self.skip_lf) && byte == b'\n' {
+                continue;
+            }
+            match byte {
+                b'\r' | b'\n' => {
+                    self.skip_lf = byte == b'\r';
+                    self.past_bom = true;
+                    let line = std::mem::take(&mut self.line);
+                    if let Some(event) = self.process_line(&String::from_utf8_lossy(&line)) {
+                        events.push(event);
+                    }
+                }
+                _ => {
+                    self.line.push(byte);
+                    if !self.past_bom && self.line == BOM {
+                        self.line.clear();
+                        self.past_bom = true;
+                    } else if !self.past_bom && !BOM.starts_with(&self.line) {
+                        self.past_bom = true;
+                    }
+                }
+            }
+        }
+        events
+    }
+
+    fn process_line(&mut self, line: &str) -> Option<SseEvent> {
+        if line.is_empty() {
+            return self.dispatch();
+        }
+        if line.starts_with(':') {
+            return None;
+        }
+        let (field, value) = match line.split_once(':') {
+            Some((field, value)) => (field, value.strip_prefix(' ').unwrap_or(value)),
+            None => (line, ""),
+        };
+        match field {
+            "event" => self.event = value.to_string(),
+            "data" => {
+                if self.has_data {
+                    self.data.push('\n');
+                }
+                self.data.push_str(value);
+                self.has_data = true;
+            }
+            "id" if !value.contains('\0') => self.last_event_id = Some(value.to_string()),
+            // Digits only; `parse` would also take a sign
+            "retry" if !value.is_empty() && value.bytes().all(|b| b.is_ascii_digit()) => {
+                if let Ok(ms) = value.parse() {
+                    self.retry_ms = Some(ms);
+                }
+            }
+            _ => {}
+        }
+        None
+    }
+
+    fn dispatch(&mut self) -> Option<SseEvent> {
+        let event = std::mem::take(&mut self.event);
+        if !std::mem::take(&mut self.has_data) {
+            return None;
+        }
+        Some(SseEvent {
+            received_at: Utc::now(),
+            event: if event.is_empty() { "message".to_string() } else { event },
+            id: self.last_event_id.clone(),
+            data: std::mem::take(&mut self.data),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // (event, id, data) of every event, feeding the chunks in order
+    fn parse(chunks: &[&[u8]]) -> (Vec<(String, Option<String>, String)>, SseParser) {
+        let mut parser = SseParser::new();
+        let events = chunks.iter()
+            .flat_map(|chunk| parser.feed(chunk))
+            .map(|e| (e.event, e.id, e.data))
+            .collect();
+        (events, parser)
+    }
+
+    fn message(data: &str) -> (String, Option<String>, String) {
+        ("message".to_string(), None, data.to_string())
+    }
+
+    // Every way of cutting `stream` in two gives the same events
+    fn assert_any_split(stream: &[u8], expected: &[(String, Option<String>, String)]) {
+        for at in 0..=stream.len() {
+            let (events, _) = parse(&[&stream[..at], &stream[at..]]);
+            assert_eq!(events, expected, "split at {}", at);
+        }
+    }
+
+    #[test]
+    fn parses_events() {
+        let (events, _) = parse(&[b"event: update\nid: 7\ndata: {\"a\":1}\n\n: comment\ndata:no space\n\n"]);
+        assert_eq!(events, vec![
+            ("update".to_string(), Some("7".to_string()), "{\"a\":1}".to_string()),
+            ("message".to_string(), Some("7".to_string()), "no space".to_string()),
+        ]);
+    }
+
+    #[test]
+    fn joins_multi_line_data() {
+        let (events, _) = parse(&[b"data: one\ndata\ndata:  three\n\n"]);
+        assert_eq!(events, vec![message("one\n\n three")]);
+    }
+
+    #[test]
+    fn waits_for_the_blank_line() {
+        let (events, _) = parse(&[b"data: pending\n"]);
+        assert!(events.is_empty());
+        // An event name alone doesn't make an event
+        let (events, _) = parse(&[b"event: ping\n\ndata: x\n\n"]);
+        assert_eq!(events, vec![message("x")]);
+    }
+
+    #[test]
+    fn handles_splits_inside_crlf() {
+        let stream = b"data: a\r\n\r\ndata: b\r\rdata: c\n\n";
+        assert_any_split(stream, &[message("a"), message("b"), message("c")]);
+    }
+
+    #[test]
+    fn handles_splits_inside_utf8() {
+        let stream = "data: grüße 🦀\n\n".as_bytes();
+        assert_any_split(stream, &[message("grüße 🦀")]);
+        let bytes: Vec<&[u8]> = stream.chunks(1).collect();
+        assert_eq!(parse(&bytes).0, vec![message("grüße 🦀")]);
+    }
+
+    #[test]
+    fn ignores_ids_containing_nul() {
+        let (events, parser) = parse(&[b"id: 1\ndata: a\n\nid: 2\0x\ndata: b\n\n"]);
+        assert_eq!(events[1].1.as_deref(), Some("1"));
+        assert_eq!(parser.last_event_id.as_deref(), Some("1"));
+        // An empty id resets it
+        let (_, parser) = parse(&[b"id: 1\nid\n"]);
+        assert_eq!(parser.last_event_id.as_deref(), Some(""));
+    }
+
+    #[test]
+    fn reads_retry() {
+        assert_eq!(parse(&[b"retry: 1500\n"]).1.retry_ms, Some(1500));
+        assert_eq!(parse(&[b"retry: 1500\nretry: soon\n"]).1.retry_ms, Some(1500));
+        assert_eq!(parse(&[b"retry: +5\n"]).1.retry_ms, None);
+        assert_eq!(parse(&[b"retry: -5\n"]).1.retry_ms, None);
+        assert_eq!(parse(&[b"retry:\n"]).1.retry_ms, None);
+    }
+
+    #[test]
+    fn skips_a_leading_bom() {
+        let stream = b"\xEF\xBB\xBFdata: first\n\n";
+        assert_any_split(stream, &[message("first")]);
+        // Only at the very start of the stream
+        let (events, _) = parse(&[b"data: a\n\n\xEF\xBB\xBFdata: b\n\n"]);
+        assert_eq!(events, vec![message("a")]);
+        let (events, _) = parse(&[b"\xEF\xBBdata: c\n\n"]);
+        assert!(events.is_empty());
+    }
+
+    #[test]
+    fn reset_drops_a_truncated_event() {
+        let mut parser = SseParser::new();
+        let events = parser.feed(b"retry: 2000\nid: 4\ndata: full\n\nevent: update\nid: 5\ndata: trunc");
+        assert_eq!(events.len(), 1);
+        parser.reset();
+        assert_eq!(parser.last_event_id.as_deref(), Some("5"));
+        assert_eq!(parser.retry_ms, Some(2000));
+
+        // The new stream starts from scratch, BOM included
+        let events: Vec<_> = parser.feed(b"\xEF\xBB\xBFated\n\ndata: next\n\n")
+            .into_iter()
+            .map(|e| (e.event, e.id, e.data))
+            .collect();
+        assert_eq!(events, vec![("message".to_string(), Some("5".to_string()), "next".to_string())]);
+    }
+}
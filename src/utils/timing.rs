@@ -0,0 +1,176 @@
+use std::collections::HashMap;
+use std::net::{IpAddr, SocketAddr};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use anyhow::{anyhow, Context, Result};
+use hyper::client::connect::dns::Name;
+use reqwest::dns::{Addrs, Resolve, Resolving};
+use reqwest::Url;
+use tokio::net::TcpStream;
+use tokio::time::timeout;
+use crate::models::Timings;
+
+// Milliseconds with microsecond precision
+pub fn millis(duration: Duration) -> f64 {
+    duration.as_micros() as f64 / 1000.0
+}
+
+// DNS resolver for reqwest that times every lookup it makes. The client
+// resolves through it, so the DNS time is the request's own lookup.
+#[derive(Clone, Default)]
+pub struct TimingResolver {
+    lookups: Arc<Mutex<HashMap<String, Lookup>>>,
+}
+
+#[derive(Debug, Clone)]
+pub struct Lookup {
+    pub ms: f64,
+    pub addrs: Vec<SocketAddr>,
+}
+
+impl TimingResolver {
+    pub fn lookup(&self, host: &str) -> Option<Lookup> {
+        self.lookups.lock().unwrap().get(host).cloned()
+    }
+}
+
+impl Resolve for TimingResolver {
+    fn resolve(&self, name: Name) -> Resolving {
+        let lookups = self.lookups.clone();
+        Box::pin(async move {
+            let host = name.as_str().to_string();
+            let start = Instant::now();
+            // The connector puts in the port
+            let addrs: Vec<SocketAddr> = tokio::net::lookup_host((host.as_str(), 0)).await?.collect();
+            lookups.lock().unwrap().insert(host, Lookup { ms: millis(start.elapsed()), addrs: addrs.clone() });
+            Ok(Box::new(addrs.into_iter()) as Addrs)
+        })
+    }
+}
+
+// TCP connect and TLS handshake times of a connection to the request's host
+#[derive(Debug, Clone, PartialEq)]
+pub struct ConnectionTimes {
+    pub connect_ms: f64,
+    pub tls_ms: Option<f64>,
+}
+
+// reqwest doesn't expose its connection, so connecting and the TLS handshake
+// are timed on a second connection to the address the request resolved to
+// (`addrs`), or to the URL's IP address.
+pub async fn probe_connection(url: &Url, addrs: &[SocketAddr], limit: Duration) -> Result<ConnectionTimes> {
+    let host = url.host_str().ok_or_else(|| anyhow!("URL has no host"))?;
+    let port = url.port_or_known_default().ok_or_else(|| anyhow!("URL has no port"))?;
+    // IPv6 literals come back bracketed from `host_str`
+    let bare_host = host.trim_start_matches('[').trim_end_matches(']');
+    let ip = match bare_host.parse::<IpAddr>() {
+        Ok(ip) => ip,
+        Err(_) => addrs.first().map(SocketAddr::ip).ok_or_else(|| anyhow!("{} wasn't resolved", host))?,
+    };
+
+    let start = Instant::now();
+    let stream = timeout(limit, TcpStream::connect(SocketAddr::new(ip, port)))
+        .await
+        .context("connect timed out")??;
+    let connect_ms = millis(start.elapsed());
+
+    let tls_ms = if url.scheme() == "https" {
+        // Certificates aren't verified by the client either
+        let connector = tokio_native_tls::native_tls::TlsConnector::builder()
+            .danger_accept_invalid_certs(true)
+            .danger_accept_invalid_hostnames(true)
+            .build()?;
+        let connector = tokio_native_tls::TlsConnector::from(connector);
+        let start = Instant::now();
+        timeout(limit, connector.connect(bare_host, stream))
+            .await
+            .context("TLS handshake timed out")??;
+        Some(millis(start.elapsed()))
+    } else {
+        None
+    };
+    Ok(ConnectionTimes { connect_ms, tls_ms })
+}
+
+// Splits the time until the response headers into curl-style phases. DNS,
+// connecting and the TLS handshake happened within `headers_ms`, so what's
+// left of it is waiting for the server (including any redirects).
+pub fn breakdown(dns_ms: Option<f64>, connection: Option<&ConnectionTimes>, headers_ms: f64, download_ms: f64) -> Timings {
+    let connect_ms = connection.map(|c| c.connect_ms);
+    let tls_ms = connection.and_then(|c| c.tls_ms);
+    let setup_ms = dns_ms.unwrap_or(0.0) + connect_ms.unwrap_or(0.0) + tls_ms.unwrap_or(0.0);
+    Timings {
+        dns_ms,
+        connect_ms,
+        tls_ms,
+        ttfb_ms: (headers_ms - setup_ms).max(0.0),
+        download_ms,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tokio::net::TcpListener;
+
+    #[test]
+    fn breakdown_takes_the_phases_out_of_waiting() {
+        let connection = ConnectionTimes { connect_ms: 10.0, tls_ms: Some(20.0) };
+        let timings = breakdown(Some(5.0), Some(&connection), 100.0, 7.5);
+        assert_eq!(timings, Timings {
+            dns_ms: Some(5.0),
+            connect_ms: Some(10.0),
+            tls_ms: Some(20.0),
+            ttfb_ms: 65.0,
+            download_ms: 7.5,
+        });
+        assert_eq!(timings.total_ms(), 107.5);
+    }
+
+    #[test]
+    fn breakdown_without_measured_phases() {
+        let timings = breakdown(None, None, 40.0, 1.0);
+        assert_eq!((timings.dns_ms, timings.connect_ms, timings.tls_ms), (None, None, None));
+        assert_eq!(timings.ttfb_ms, 40.0);
+
+        // Plain HTTP to an IP address: only connecting
+        let connection = ConnectionTimes { connect_ms: 3.0, tls_ms: None };
+        let timings = breakdown(None, Some(&connection), 40.0, 1.0);
+        assert_eq!((timings.connect_ms, timings.tls_ms, timings.ttfb_ms), (Some(3.0), None, 37.0));
+    }
+
+    #[test]
+    fn breakdown_never_waits_less_than_nothing() {
+        // The probe connection can be slower than the request's own
+        let connection = ConnectionTimes { connect_ms: 30.0, tls_ms: None };
+        assert_eq!(breakdown(Some(2.0), Some(&connection), 20.0, 0.0).ttfb_ms, 0.0);
+    }
+
+    #[tokio::test]
+    async fn resolver_times_lookups() {
+        let resolver = TimingResolver::default();
+        let addrs: Vec<SocketAddr> = resolver.resolve("localhost".parse().unwrap()).await.unwrap().collect();
+        let lookup = resolver.lookup("localhost").unwrap();
+        assert!(!addrs.is_empty());
+        assert_eq!(lookup.addrs, addrs);
+        assert!(lookup.ms >= 0.0);
+        assert!(resolver.lookup("example.com").is_none());
+    }
+
+    #[tokio::test]
+    async fn probe_connects_to_the_resolved_address() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let port = listener.local_addr().unwrap().port();
+        let url = Url::parse(&format!("http://some-host:{}/path", port)).unwrap();
+        let addrs = ["127.0.0.1:0".parse().unwrap()];
+        let times = probe_connection(&url, &addrs, Duration::from_secs(5)).await.unwrap();
+        assert!(times.connect_ms >= 0.0);
+        assert_eq!(times.tls_ms, None);
+
+        // An IP address needs no lookup
+        let url = Url::parse(&format!("http://127.0.0.1:{}/", port)).unwrap();
+        assert!(probe_connection(&url, &[], Duration::from_secs(5)).await.is_ok());
+        let url = Url::parse("http://unresolved.invalid/").unwrap();
+        assert!(probe_connection(&url, &[], Duration::from_secs(5)).await.is_err());
+    }
+}
@@ -0,0 +1,158 @@
+use std::path::PathBuf;
+use raquet::data::{collections, history, AppConfig, CollectionManager};
+use raquet::models::collection::CollectionItem;
+
+// Files as each version of raquet wrote them, in tests/fixtures
+
+fn fixture(path: &str) -> Vec<u8> {
+    let path = PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures").join(path);
+    std::fs::read(&path).unwrap_or_else(|e| panic!("{}: {}", path.display(), e))
+}
+
+fn config(path: &str) -> (AppConfig, u32) {
+    let text = String::from_utf8(fixture(path)).unwrap();
+    let (table, from) = AppConfig::parse_table(&text).unwrap();
+    (toml::Value::Table(table).try_into().unwrap(), from)
+}
+
+#[test]
+fn collections_keyed_by_name() {
+    let (collections, from) = collections::parse_file(&fixture("collections/v0.json")).unwrap();
+    assert_eq!(from, 0);
+    assert_eq!(collections.len(), 1);
+    assert_eq!(collections[0].info.name, "Default Collection");
+    let CollectionItem::Request(saved) = &collections[0].requests[0] else { panic!("not a request") };
+    assert_eq!(saved.request.url, "https://api.example.com/users");
+    assert_eq!(saved.request.headers["Accept"], "application/json");
+}
+
+#[test]
+fn collections_as_a_list() {
+    let (collections, from) = collections::parse_file(&fixture("collections/v1.json")).unwrap();
+    assert_eq!(from, 1);
+    assert_eq!(collections.len(), 2);
+    let petstore = &collections[0];
+    assert_eq!(petstore.variables["baseUrl"], "https://petstore.example.com/v1");
+    let CollectionItem::Folder(folder) = &petstore.requests[0] else { panic!("not a folder") };
+    let CollectionItem::Request(saved) = &folder.item[0] else { panic!("not a request") };
+    assert_eq!(saved.response_filter.as_deref(), Some("$[*].name"));
+    assert!(matches!(&petstore.requests[1], CollectionItem::WebSocket(ws) if ws.message.as_deref() == Some("ping")));
+}
+
+#[test]
+fn collections_upgraded_on_load_keep_the_original() {
+    let dir = std::env::temp_dir().join(format!("raquet-migrations-{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    let path = dir.join("collections.json");
+    std::fs::write(&path, fixture("collections/v1.json")).unwrap();
+
+    let manager = CollectionManager::in_file(path.clone());
+    assert!(manager.damaged().is_none());
+    assert_eq!(std::fs::read(dir.join("collections.json.v1")).unwrap(), fixture("collections/v1.json"));
+    let (rewritten, from) = collections::parse_file(&std::fs::read(&path).unwrap()).unwrap();
+    assert_eq!(from, collections::VERSION);
+    assert_eq!(rewritten.len(), 2);
+    assert_eq!(rewritten.iter().find(|c| c.info.name == "Petstore").unwrap().variables["baseUrl"], "https://petstore.example.com/v1");
+
+    // Loading the upgraded file again leaves the kept original alone
+    let manager = CollectionManager::in_file(path.clone());
+    assert_eq!(manager.get_collections().len(), 2);
+    assert_eq!(std::fs::read(dir.join("collections.json.v1")).unwrap(), fixture("collections/v1.json"));
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn collections_current() {
+    let (collections, from) = collections::parse_file(&fixture("collections/v2.json")).unwrap();
+    assert_eq!(from, collections::VERSION);
+    assert_eq!(collections[0].source.as_deref(), Some("/srv/api/api.http"));
+    let CollectionItem::Request(saved) = &collections[0].requests[0] else { panic!("not a request") };
+    assert!(saved.request.options.http_version.is_some());
+}
+
+#[test]
+fn collections_from_a_newer_version() {
+    let error = collections::parse_file(br#"{"version": 99, "collections": []}"#).unwrap_err();
+    assert!(error.contains("newer raquet"), "{}", error);
+    assert!(!collections::is_valid_file(br#"{"version": 99, "collections": []}"#));
+}
+
+#[test]
+fn history_as_a_list() {
+    let (entries, from) = history::parse_file(&fixture("history/v1.json")).unwrap();
+    assert_eq!(from, 1);
+    assert_eq!(entries.len(), 2);
+    assert_eq!(entries[0].response.as_ref().and_then(|r| r.status), Some(200));
+    assert!(entries[0].response.as_ref().unwrap().timings.is_none());
+    assert!(entries[1].response.is_none());
+}
+
+#[test]
+fn history_headers_in_a_map() {
+    let (entries, from) = history::parse_file(&fixture("history/v2.json")).unwrap();
+    assert_eq!(from, 2);
+    let response = entries[0].response.as_ref().unwrap();
+    assert_eq!(response.timings.as_ref().unwrap().ttfb_ms, 2.1);
+    assert_eq!(response.headers, vec![
+        ("content-type".to_string(), "application/json".to_string()),
+        ("set-cookie".to_string(), "session=2".to_string()),
+    ]);
+    assert_eq!((response.encoding.as_deref(), response.http_version.as_deref()), (None, None));
+}
+
+#[test]
+fn history_current() {
+    let (entries, from) = history::parse_file(&fixture("history/v3.json")).unwrap();
+    assert_eq!(from, history::VERSION);
+    let response = entries[0].response.as_ref().unwrap();
+    let cookies: Vec<&str> = response.headers.iter()
+        .filter(|(name, _)| name == "set-cookie")
+        .map(|(_, value)| value.as_str())
+        .collect();
+    assert_eq!(cookies, vec!["a=1", "b=2"]);
+    assert!(response.is_binary());
+    assert_eq!(response.body_bytes().unwrap(), b"\x89PNG\r\n\x1a\n");
+    assert_eq!(response.http_version.as_deref(), Some("HTTP/2.0"));
+}
+
+#[test]
+fn history_from_a_newer_version() {
+    let error = history::parse_file(br#"{"version": 99, "entries": []}"#).unwrap_err();
+    assert!(error.contains("newer raquet"), "{}", error);
+}
+
+#[test]
+fn config_settings_at_the_top_level() {
+    let (config, from) = config("config/v1-top-level.toml");
+    assert_eq!(from, 1);
+    assert_eq!(config.version, 2);
+    assert_eq!(config.app.timeout_seconds, 10);
+    assert_eq!(config.app.history_size, 50);
+    assert!(!config.app.follow_redirects);
+    assert_eq!(config.app.proxy.url, "http://127.0.0.1:8080");
+    assert_eq!(config.default_headers["User-Agent"], "raquet-test");
+}
+
+#[test]
+fn config_with_an_app_section() {
+    let (config, from) = config("config/v1.toml");
+    assert_eq!(from, 1);
+    assert_eq!(config.app.timeout_seconds, 15);
+    assert_eq!(config.app.max_response_size, 1048576);
+    assert_eq!(config.app.default_url, "https://api.example.com");
+}
+
+#[test]
+fn config_current() {
+    let (config, from) = config("config/v2.toml");
+    assert_eq!(from, 2);
+    assert_eq!(config.app.timeout_seconds, 20);
+    assert_eq!(config.app.collections_dir, "~/src/api-collections");
+    assert_eq!(config.app.proxy.no_proxy, vec!["localhost"]);
+}
+
+#[test]
+fn config_from_a_newer_version() {
+    let error = AppConfig::parse_table("version = 3\n").unwrap_err();
+    assert!(error.contains("newer raquet"), "{}", error);
+}